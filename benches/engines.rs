@@ -0,0 +1,15 @@
+// `cargo bench`: times every engine on the same workloads as the bench subcommand, with the same table
+// there's nothing like criterion to lean on offline, so it's just std::time
+use std::io;
+use game_of_life::workload;
+
+
+
+const BOARD_SIZE: u32 = 200;
+const GENERATIONS: u32 = 200;
+const SEED: u64 = 1;
+
+
+fn main() {
+    workload::time_workloads(&mut io::stdout().lock(), BOARD_SIZE, BOARD_SIZE, GENERATIONS, SEED, None).unwrap();
+}
@@ -0,0 +1,24 @@
+use std::process;
+use std::io;
+use game_of_life::workload;
+use crate::{check_board_size, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const DEFAULT_BOARD_SIZE: u16 = 200;
+const DEFAULT_SEED: u64 = 1; // the same soup every time unless --seed says otherwise, so runs can be compared
+
+
+// the "bench" subcommand: times each engine (or just --engine) on a few standard workloads and prints how many generations per second they manage
+pub fn run_bench(args: Args) {
+    let width = args.board_width.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    let height = args.board_height.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    if width == 0 || height == 0 {
+        eprintln!("there's nothing to time on a board with no cells");
+        process::exit(1);
+    }
+    check_board_size(&args, width, height, SIMULATION_BYTES_PER_CELL);
+    let seed = args.seed.unwrap_or(DEFAULT_SEED);
+    let generations = args.generations.max(1);
+    let _ = workload::time_workloads(&mut io::stdout().lock(), width, height, generations, seed, args.engine); // an error means whatever was reading the output stopped, which is fine
+}
@@ -0,0 +1,156 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::board::{CellMap, Point, ALIVE, DEAD};
+use crate::engine::{check_life_like, Engine};
+use crate::rule::Rule;
+
+
+
+// a dense engine that keeps every cell as one bit, 64 to a word, so a generation works out 64 cells at once
+// quicker than Board once most of the board is busy, and it takes the same amount of time no matter how many cells are alive
+pub struct BitBoard {
+    width: u32,
+    height: u32,
+    words_per_row: usize,
+    rows: Vec<u64>, // bit b of word w in a row is the cell at x = w * 64 + b
+    spare_rows: Vec<u64>, // the next generation gets built in here and then swapped in
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+
+// adds one bit to each of 64 counters at once, the counters are stored a bit at a time (counter[0] has the lowest bit of all 64)
+fn add_bits(counter: &mut [u64; 4], bits: u64) {
+    let mut carry = bits;
+    for counter_bits in counter.iter_mut() {
+        let overflow = *counter_bits & carry;
+        *counter_bits ^= carry;
+        carry = overflow;
+    }
+}
+
+
+impl BitBoard {
+    pub fn new(width: u32, height: u32, rule: &Rule) -> Result<BitBoard, String> {
+        check_life_like(rule, "bitboard")?;
+        let words_per_row = (width as usize).div_ceil(64);
+        let (mut birth, mut survival) = ([false; 9], [false; 9]);
+        birth.copy_from_slice(&rule.birth);
+        survival.copy_from_slice(&rule.survival);
+        birth[0] = false; // like Board, which only ever looks at cells with live neighbours
+        return Ok(BitBoard {
+            width,
+            height,
+            words_per_row,
+            rows: vec![0; words_per_row * height as usize],
+            spare_rows: vec![0; words_per_row * height as usize],
+            birth,
+            survival,
+        });
+    }
+
+    fn on_board(&self, cell: &Point) -> bool {
+        return cell.x >= 0 && cell.y >= 0 && cell.x < self.width as i16 && cell.y < self.height as i16;
+    }
+
+    // the word a cell is in and the bit it is in that word
+    fn position(&self, cell: &Point) -> (usize, u32) {
+        return (cell.y as usize * self.words_per_row + cell.x as usize / 64, cell.x as u32 % 64);
+    }
+
+    // word of row y, with everything off the board counting as empty
+    fn word(&self, y: i64, word: i64) -> u64 {
+        if y < 0 || y >= self.height as i64 || word < 0 || word >= self.words_per_row as i64 {
+            return 0;
+        }
+        return self.rows[y as usize * self.words_per_row + word as usize];
+    }
+}
+
+
+impl Engine for BitBoard {
+    fn step(&mut self) {
+        let last_word_mask = match self.width % 64 { // so nothing gets born past the right edge
+            0 => u64::MAX,
+            bits => (1 << bits) - 1
+        };
+        for y in 0..self.height as i64 {
+            for word in 0..self.words_per_row as i64 {
+                let mut counter = [0; 4];
+                for neighbour_y in [y - 1, y, y + 1] {
+                    let (left, middle, right) = (self.word(neighbour_y, word - 1), self.word(neighbour_y, word), self.word(neighbour_y, word + 1));
+                    add_bits(&mut counter, (middle << 1) | (left >> 63)); // the cells to the left of each cell
+                    add_bits(&mut counter, (middle >> 1) | (right << 63)); // and to the right
+                    if neighbour_y != y {
+                        add_bits(&mut counter, middle);
+                    }
+                }
+                let alive = self.word(y, word);
+                let mut next = 0;
+                for neighbours in 0..9 {
+                    if !self.birth[neighbours] && !self.survival[neighbours] {
+                        continue;
+                    }
+                    let mut count_is = u64::MAX; // every counter that's equal to neighbours
+                    for (bit, counter_bits) in counter.iter().enumerate() {
+                        count_is &= if neighbours >> bit & 1 == 1 { *counter_bits } else { !counter_bits };
+                    }
+                    if self.birth[neighbours] {
+                        next |= count_is & !alive;
+                    }
+                    if self.survival[neighbours] {
+                        next |= count_is & alive;
+                    }
+                }
+                if word as usize == self.words_per_row - 1 {
+                    next &= last_word_mask;
+                }
+                self.spare_rows[y as usize * self.words_per_row + word as usize] = next;
+            }
+        }
+        core::mem::swap(&mut self.rows, &mut self.spare_rows);
+    }
+
+    fn get_cell(&self, cell: &Point) -> u8 {
+        if !self.on_board(cell) {
+            return DEAD;
+        }
+        let (word, bit) = self.position(cell);
+        return (self.rows[word] >> bit & 1) as u8;
+    }
+
+    fn set_cell(&mut self, cell: &Point, state: u8) {
+        if !self.on_board(cell) {
+            return;
+        }
+        let (word, bit) = self.position(cell);
+        match state {
+            DEAD => self.rows[word] &= !(1 << bit),
+            _ => self.rows[word] |= 1 << bit
+        }
+    }
+
+    fn bounds(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    fn population(&self) -> usize {
+        return self.rows.iter().map(|word| word.count_ones() as usize).sum();
+    }
+
+    fn cells(&self) -> CellMap<u8> {
+        let mut cells = CellMap::new();
+        for y in 0..self.height as usize {
+            for word in 0..self.words_per_row {
+                let mut bits = self.rows[y * self.words_per_row + word];
+                while bits != 0 {
+                    let bit = bits.trailing_zeros() as usize;
+                    cells.insert(Point{x: (word * 64 + bit) as i16, y: y as i16}, ALIVE);
+                    bits &= bits - 1;
+                }
+            }
+        }
+        return cells;
+    }
+}
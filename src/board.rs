@@ -0,0 +1,997 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "random")]
+use rand::Rng;
+use crate::pattern::Pattern;
+use crate::margolus::BlockRule;
+use crate::rule::{Neighbourhood, Rule};
+use crate::rule_table::RuleTable;
+
+
+
+// cell states, dead cells aren't stored on the board at all
+// Generations rules also have "dying" states (ALIVE + 1, ALIVE + 2, ...) that cells count up through after they stop being alive
+pub const DEAD: u8 = 0;
+pub const ALIVE: u8 = 1;
+// wireworld cell states
+pub const ELECTRON_HEAD: u8 = 1;
+pub const ELECTRON_TAIL: u8 = 2;
+pub const CONDUCTOR: u8 = 3;
+// immigration cell states, which player a live cell belongs to
+pub const PLAYER_ONE: u8 = 1;
+pub const PLAYER_TWO: u8 = 2;
+
+// what the cells on a board are stored in, std's HashMap is faster but without std (on a microcontroller driving an LED matrix or something) BTreeMap is all there is
+#[cfg(feature = "std")]
+pub type CellMap<V> = std::collections::HashMap<Point, V>;
+#[cfg(not(feature = "std"))]
+pub type CellMap<V> = alloc::collections::BTreeMap<Point, V>;
+#[cfg(feature = "std")]
+pub type CellSet = std::collections::HashSet<Point>;
+#[cfg(not(feature = "std"))]
+pub type CellSet = alloc::collections::BTreeSet<Point>;
+
+
+
+// derive() will automatically derive all the traits needed to be hashable by autogenering an impl
+// PartialEq adds equal and not equal methods (for symmetric and transitive relationships)
+// Eq adds no methods but basically says "the reflexive property holds for this thing"
+// you cant just do derive(Eq) because Eq inherits PartialEq so you need those methods for Eq to hold
+// also derive Clone because I want to be able to clone Points
+// and PartialOrd/Ord so they can be the keys of a BTreeMap when there's no std
+#[derive(PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i16,
+    pub y: i16,
+}
+
+
+impl Point {
+    pub fn bound(&mut self, min_x: i16, min_y: i16, max_x: i16, max_y: i16) {
+        self.x = self.x.clamp(min_x, max_x);
+        self.y = self.y.clamp(min_y, max_y);
+    }
+}
+
+
+// the kind of cellular automaton being simulated
+#[derive(PartialEq, Clone, Copy)]
+pub enum Automaton {
+    Life, // any life-like or Generations rule (uses Board.rule)
+    Wireworld,
+    LangtonsAnt, // cells are either black (ALIVE) or white (DEAD), and the ants in Board.ants walk around flipping them
+    Margolus(BlockRule), // 2x2 blocks of cells change all at once (critters, tron)
+    Table, // a golly rule table (uses Board.table)
+    Immigration, // two player life, live cells belong to PLAYER_ONE or PLAYER_TWO and newborn cells join whoever has more of their neighbours (uses Board.rule)
+}
+
+
+#[derive(Clone)]
+pub struct Ant {
+    pub position: Point,
+    pub direction: u8, // 0 is up, 1 is right, 2 is down, 3 is left (so turning right is just adding 1)
+}
+
+
+// a tiny random number generator (splitmix64) for init_from_seed, rand's generators are allowed to change between versions so they can't be used to reproduce a board
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+}
+
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+
+// what happened in a generation, from Board::step
+pub struct StepStats {
+    pub births: usize, // cells that were DEAD and aren't any more
+    pub deaths: usize, // cells that weren't DEAD and are now
+    pub changed: Vec<Point>, // every cell with a different state than before (births and deaths included)
+}
+
+
+// from Board::iter_generations
+pub struct GenerationIter<'a> {
+    board: &'a mut Board,
+}
+
+
+impl Iterator for GenerationIter<'_> {
+    type Item = CellMap<u8>;
+
+    fn next(&mut self) -> Option<CellMap<u8>> {
+        self.board.update_cells();
+        return Some(self.board.cells.clone());
+    }
+}
+
+
+// Board::state_hash for cells and ants that aren't on a Board (the other engines give the same hash as Board for the same cells)
+pub fn hash_state(cells: &CellMap<u8>, ants: &[Ant]) -> u64 {
+    let mut cells: Vec<(&Point, &u8)> = cells.iter().collect();
+    cells.sort();
+    let mut bytes = Vec::new();
+    for (cell, &state) in cells {
+        bytes.extend_from_slice(&cell.x.to_le_bytes());
+        bytes.extend_from_slice(&cell.y.to_le_bytes());
+        bytes.push(state);
+    }
+    for ant in ants {
+        bytes.extend_from_slice(&ant.position.x.to_le_bytes());
+        bytes.extend_from_slice(&ant.position.y.to_le_bytes());
+        bytes.push(ant.direction);
+    }
+    return bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME));
+}
+
+
+#[derive(Clone)]
+pub struct Board {
+    pub width: u32,
+    pub height: u32,
+    pub cells: CellMap<u8>, // state of every cell that isn't DEAD
+    pub automaton: Automaton,
+    pub rule: Rule,
+    pub ants: Vec<Ant>, // only used by Automaton::LangtonsAnt
+    pub table: Option<RuleTable>, // only used by Automaton::Table
+    pub walls: CellSet, // cells that are always dead, so patterns on either side of a wall can't affect each other
+    pub sources: CellSet, // cells that are always alive (always electron heads in wireworld, always black for ants)
+    pub blocks_shifted: bool, // whether Automaton::Margolus's blocks are shifted down and right by one cell this generation
+    // kept empty between generations and reused every generation, so at high populations the maps don't have to be allocated (and grown) from scratch each time
+    spare_cells: CellMap<u8>, // what cells was before the last generation, the next one gets built in it
+    neighbour_counts: CellMap<u8>,
+    rival_counts: CellMap<u8>, // player two's neighbour counts in immigration, player one's go in neighbour_counts
+    checked: CellSet,
+}
+
+
+impl Board {
+    pub fn new(width: u32, height: u32, automaton: Automaton, rule: Rule) -> Board {
+        return Board {
+            width,
+            height,
+            cells: CellMap::new(),
+            automaton,
+            rule,
+            ants: Vec::new(),
+            table: None,
+            walls: CellSet::new(),
+            sources: CellSet::new(),
+            blocks_shifted: false,
+            spare_cells: CellMap::new(),
+            neighbour_counts: CellMap::new(),
+            rival_counts: CellMap::new(),
+            checked: CellSet::new()
+        };
+    }
+
+    // gets rid of everything that's alive (walls and sources stay)
+    pub fn clear(&mut self) {
+        self.cells = CellMap::new();
+        self.ants = Vec::new();
+        self.apply_obstacles();
+    }
+
+    // changes the size of the board, everything stays where it is except for whatever's past the new edges (which is gone)
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let inside = |cell: &Point| (cell.x as i64) < width as i64 && (cell.y as i64) < height as i64;
+        self.width = width;
+        self.height = height;
+        self.cells.retain(|cell, _| inside(cell));
+        self.walls.retain(|cell| inside(cell));
+        self.sources.retain(|cell| inside(cell));
+        self.ants.retain(|ant| inside(&ant.position));
+    }
+
+    // grows the board so that nothing is within margin cells of an edge, so that it acts like it goes on forever (as far as a Point can reach)
+    // growing left or up moves everything right or down (by an even number of cells so margolus blocks still line up), returns how far
+    pub fn grow_to_fit(&mut self, margin: i16) -> Point {
+        let mut positions = self.cells.keys().chain(self.ants.iter().map(|ant| &ant.position)).peekable();
+        if positions.peek().is_none() {
+            return Point{x: 0, y: 0};
+        }
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (i16::MAX, i16::MAX, i16::MIN, i16::MIN);
+        for cell in positions {
+            min_x = min_x.min(cell.x);
+            min_y = min_y.min(cell.y);
+            max_x = max_x.max(cell.x);
+            max_y = max_y.max(cell.y);
+        }
+        let grow = |low: i16, high: i16, length: u32| -> (i16, u32) { // how far to move everything and the new length, along one axis
+            let margin = margin as i32;
+            let shift = if (low as i32) < margin { (2 * margin - low as i32 + 1) / 2 * 2 } else { 0 };
+            let new_length = match high as i32 + margin >= length as i32 {
+                true => high as i32 + shift + 1 + 2 * margin,
+                false => length as i32 + shift
+            };
+            return match new_length > i16::MAX as i32 {
+                true => (0, length), // it's as big as it can get, so things fall off the edge like usual
+                false => (shift as i16, new_length as u32)
+            };
+        };
+        let (shift_x, width) = grow(min_x, max_x, self.width);
+        let (shift_y, height) = grow(min_y, max_y, self.height);
+        self.width = width;
+        self.height = height;
+        if shift_x != 0 || shift_y != 0 {
+            let moved = |cell: &Point| Point{x: cell.x + shift_x, y: cell.y + shift_y};
+            self.cells = self.cells.iter().map(|(cell, &state)| (moved(cell), state)).collect();
+            self.walls = self.walls.iter().map(moved).collect();
+            self.sources = self.sources.iter().map(moved).collect();
+            for ant in self.ants.iter_mut() {
+                ant.position = moved(&ant.position);
+            }
+        }
+        return Point{x: shift_x, y: shift_y};
+    }
+
+    // kills anything on a wall and brings sources back to life, the automata don't have to know about either
+    fn apply_obstacles(&mut self) {
+        for wall in &self.walls {
+            self.cells.remove(wall);
+        }
+        for source in &self.sources {
+            self.cells.insert(source.clone(), ALIVE);
+        }
+    }
+
+    #[cfg(feature = "random")] // rand is the only thing the engine depends on so it can be left out
+    pub fn init_randomly(&mut self) {
+        self.init_randomly_with(&mut rand::thread_rng());
+    }
+
+    // init_randomly with a generator of your own, so a seeded one gives the same board every time (with the same version of rand)
+    #[cfg(feature = "random")]
+    pub fn init_randomly_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.clear();
+        if self.automaton == Automaton::LangtonsAnt { // ants are only interesting on an empty grid so just scatter a few of them
+            for _ in 0..rng.gen_range(1..=4) {
+                self.ants.push(Ant {
+                    position: Point{
+                        x: rng.gen_range(0..self.width as i16),
+                        y: rng.gen_range(0..self.height as i16)
+                    },
+                    direction: rng.gen_range(0..4)
+                });
+            }
+            return;
+        }
+        for _ in 0..((self.width * self.height) / 4) {
+            let random_cell = Point{
+                x: rng.gen_range(0..self.width as i16),
+                y: rng.gen_range(0..self.height as i16)
+            };
+            if self.walls.contains(&random_cell) || self.sources.contains(&random_cell) {
+                continue;
+            }
+            let state = match self.automaton {
+                Automaton::Wireworld => rng.gen_range(ELECTRON_HEAD..=CONDUCTOR),
+                Automaton::Immigration => rng.gen_range(PLAYER_ONE..=PLAYER_TWO),
+                _ => ALIVE,
+            };
+            self.cells.insert(random_cell, state);
+        }
+    }
+
+    // like init_randomly but the same seed always gives the same board, on any platform and with any version of this crate's dependencies
+    // about a quarter of the cells get filled (ant boards get 1 to 4 ants instead)
+    pub fn init_from_seed(&mut self, seed: u64) {
+        self.clear();
+        let mut rng = SeededRng(seed);
+        if self.automaton == Automaton::LangtonsAnt {
+            for _ in 0..(1 + rng.next() % 4) {
+                self.ants.push(Ant {
+                    position: Point{
+                        x: (rng.next() % self.width as u64) as i16,
+                        y: (rng.next() % self.height as u64) as i16
+                    },
+                    direction: (rng.next() % 4) as u8
+                });
+            }
+            return;
+        }
+        for y in 0..self.height as i16 {
+            for x in 0..self.width as i16 {
+                let random = rng.next(); // taken for every cell (even walls) so that walls don't change where everything else goes
+                if !random.is_multiple_of(4) || self.walls.contains(&Point{x, y}) || self.sources.contains(&Point{x, y}) {
+                    continue;
+                }
+                let state = match self.automaton {
+                    Automaton::Wireworld => ELECTRON_HEAD + ((random >> 32) % 3) as u8,
+                    Automaton::Immigration => PLAYER_ONE + ((random >> 32) % 2) as u8,
+                    _ => ALIVE,
+                };
+                self.cells.insert(Point{x, y}, state);
+            }
+        }
+    }
+
+    // a hash (FNV-1a) of every cell and ant that doesn't depend on the platform, the build or the order cells are stored in (unlike std's hashers)
+    // so two runs can be checked for giving exactly the same result
+    pub fn state_hash(&self) -> u64 {
+        return hash_state(&self.cells, &self.ants);
+    }
+
+    // copies a pattern onto the board with its top left corner at top_left, any cells that don't fit get cut off
+    pub fn place_pattern(&mut self, pattern: &Pattern, top_left: &Point) {
+        for (cell, state) in &pattern.cells {
+            let x = top_left.x + cell.x;
+            let y = top_left.y + cell.y;
+            if x >= 0 && y >= 0 && x < self.width as i16 && y < self.height as i16 {
+                self.cells.insert(Point{x, y}, *state);
+            }
+        }
+    }
+
+    // number of ALIVE cells (for wireworld that's the number of electron heads, for immigration it's both players' cells)
+    pub fn population(&self) -> usize {
+        if self.automaton == Automaton::Immigration {
+            return self.cells.len();
+        }
+        return self.cells.values().filter(|&&state| state == ALIVE).count();
+    }
+
+    // how many cells each player has in immigration
+    pub fn scores(&self) -> (usize, usize) {
+        let player_one = self.cells.values().filter(|&&state| state == PLAYER_ONE).count();
+        return (player_one, self.cells.len() - player_one);
+    }
+
+    pub fn cell_state(&self, cell: &Point) -> u8 {
+        return *self.cells.get(cell).unwrap_or(&DEAD);
+    }
+
+    // the cells at each of offsets from cell that are on the board
+    fn neighbours<'a>(&'a self, cell: &'a Point, offsets: &'a [(i16, i16)]) -> impl Iterator<Item = Point> + 'a {
+        return offsets.iter().filter_map(move |&(x, y)| self.offset_cell(cell, x, y));
+    }
+
+    // the cell x, y away from cell if it's on the board (added up as i32s since cells near the edge of the biggest boards would overflow an i16)
+    fn offset_cell(&self, cell: &Point, x: i16, y: i16) -> Option<Point> {
+        let (x, y) = (cell.x as i32 + x as i32, cell.y as i32 + y as i32);
+        return match x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32 {
+            true => Some(Point{x: x as i16, y: y as i16}),
+            false => None
+        };
+    }
+
+    // the new cells take the place of the old ones, which get emptied out to build the generation after in
+    fn replace_cells(&mut self, new_cells: CellMap<u8>) {
+        self.spare_cells = mem::replace(&mut self.cells, new_cells);
+        self.spare_cells.clear();
+    }
+
+    // runs one generation, looking at every cell that could change (IncrementalBoard is the one that skips the parts of the board that have settled down)
+    pub fn update_cells(&mut self) {
+        self.advance(None);
+    }
+
+    // update_cells, but if last_changed is every cell that changed in Life's last generation then only the cells next to them get looked at (when there aren't too many of them)
+    // returns the cells that changed for Life, the other automata don't keep track
+    fn advance(&mut self, last_changed: Option<Vec<Point>>) -> Option<Vec<Point>> {
+        let changed = match self.automaton {
+            Automaton::Life => Some(match last_changed {
+                Some(last_changed) if self.few_changes(&last_changed) => self.update_life_cells_near(&last_changed),
+                _ => self.update_life_cells()
+            }),
+            Automaton::Wireworld => { self.update_wireworld_cells(); None }
+            Automaton::LangtonsAnt => { self.update_ants(); None }
+            Automaton::Margolus(block_rule) => { self.update_blocks(&block_rule); None }
+            Automaton::Table => { self.update_table_cells(); None }
+            Automaton::Immigration => { self.update_immigration_cells(); None }
+        };
+        self.apply_obstacles();
+        return changed;
+    }
+
+    // update_cells that says what changed, so it doesn't have to be worked out by comparing the cells from before and after
+    pub fn step(&mut self) -> StepStats {
+        return self.step_from(None);
+    }
+
+    // step with advance's last_changed
+    fn step_from(&mut self, last_changed: Option<Vec<Point>>) -> StepStats {
+        if self.automaton != Automaton::Life { // only life keeps track of what changed, the rest get compared with a copy of the cells from before
+            let before = self.cells.clone();
+            self.advance(None);
+            let mut stats = StepStats { births: 0, deaths: 0, changed: Vec::new() };
+            for (cell, state) in &self.cells {
+                match before.get(cell) {
+                    Some(old_state) if old_state == state => continue,
+                    Some(_) => (),
+                    None => stats.births += 1
+                }
+                stats.changed.push(cell.clone());
+            }
+            for cell in before.keys().filter(|cell| !self.cells.contains_key(cell)) {
+                stats.deaths += 1;
+                stats.changed.push(cell.clone());
+            }
+            return stats;
+        }
+        let obstacles: CellMap<u8> = self.walls.iter().chain(&self.sources).map(|cell| (cell.clone(), self.cell_state(cell))).collect();
+        let mut changed = self.advance(last_changed).unwrap_or_default();
+        // walls and sources get put back after the generation, so whether they changed is down to how they were before it rather than what update_cells did with them
+        changed.retain(|cell| !obstacles.contains_key(cell));
+        changed.extend(obstacles.iter().filter(|(cell, &state)| self.cell_state(cell) != state).map(|(cell, _)| cell.clone()));
+        let births = changed.iter()
+            .filter(|cell| match obstacles.get(cell) {
+                Some(&state) => state == DEAD && self.cells.contains_key(cell),
+                None => self.cells.get(cell) == Some(&ALIVE) // dying cells can't come back to life, so any other cell that's alive now was just born
+            })
+            .count();
+        let deaths = changed.iter().filter(|cell| !self.cells.contains_key(cell)).count();
+        return StepStats { births, deaths, changed };
+    }
+
+    // an endless iterator over the cells of every generation after this one (each one's a copy, so the board can keep going)
+    pub fn iter_generations(&mut self) -> GenerationIter<'_> {
+        return GenerationIter { board: self };
+    }
+
+    // how many live neighbours each cell has (cells with none are left out), for the automata that go by counting them and None for the rest
+    // immigration counts both players' cells and wireworld counts electron heads
+    pub fn live_neighbour_counts(&self) -> Option<CellMap<u8>> {
+        return match self.automaton {
+            Automaton::Life => Some(self.count_neighbours(ALIVE, &self.rule.neighbourhood)),
+            Automaton::Immigration => {
+                let mut counts = self.count_neighbours(PLAYER_ONE, &self.rule.neighbourhood);
+                for (cell, count) in self.count_neighbours(PLAYER_TWO, &self.rule.neighbourhood) {
+                    *counts.entry(cell).or_insert(0) += count;
+                }
+                Some(counts)
+            }
+            Automaton::Wireworld => Some(self.count_neighbours(ELECTRON_HEAD, &Neighbourhood::Moore(1))),
+            _ => None
+        };
+    }
+
+    // counts how many neighbours of each cell are in counted_state (ignoring all the cells that we know have 0 of those neighbours)
+    // a cell counts towards the cells at minus each offset in the neighbourhood (which only matters for lopsided custom neighbourhoods)
+    fn count_neighbours(&self, counted_state: u8, neighbourhood: &Neighbourhood) -> CellMap<u8> {
+        let mut neighbour_counts = CellMap::new();
+        self.count_neighbours_into(counted_state, neighbourhood, &mut neighbour_counts);
+        return neighbour_counts;
+    }
+
+    // count_neighbours into a map that's already there (and empty), so the one in Board.neighbour_counts can be used every generation
+    fn count_neighbours_into(&self, counted_state: u8, neighbourhood: &Neighbourhood, neighbour_counts: &mut CellMap<u8>) {
+        if let Neighbourhood::Moore(range @ 2..) = neighbourhood { // range 1 is quick enough either way
+            if self.cells.len() * neighbourhood.size() > self.width as usize * self.height as usize { // cheaper to go over the whole board than to visit every neighbour of every cell
+                self.count_neighbours_dense(counted_state, *range as i16, neighbour_counts);
+                return;
+            }
+        }
+        let offsets: Vec<(i16, i16)> = neighbourhood.offsets().iter().map(|(x, y)| (-x, -y)).collect();
+        for (cell, &state) in &self.cells {
+            if state != counted_state {
+                continue;
+            }
+
+            // increment each neighbouring cell's num_neighbours count by 1 (counts begin at 0 neighbours)
+            for neighbour_cell in self.neighbours(cell, &offsets) {
+                *neighbour_counts.entry(neighbour_cell).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // count_neighbours for big moore neighbourhoods, takes the same time however big the range is since it never looks at each neighbour
+    // sums[y][x] is how many counted cells are above and to the left of x,y, so any square's count only takes its 4 corners
+    fn count_neighbours_dense(&self, counted_state: u8, range: i16, neighbour_counts: &mut CellMap<u8>) {
+        let row = self.width as usize + 1; // sums has an extra row and column of 0s at the top and left
+        let mut sums = vec![0u32; row * (self.height as usize + 1)];
+        for (cell, &state) in &self.cells {
+            if state == counted_state {
+                sums[(cell.y as usize + 1) * row + cell.x as usize + 1] = 1;
+            }
+        }
+        for index in row..sums.len() {
+            if index % row != 0 {
+                sums[index] += sums[index - 1] + sums[index - row] - sums[index - row - 1];
+            }
+        }
+
+        let range = range as i32; // the window's edges are worked out as i32s so they can hang off the biggest boards without overflowing
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let (left, top) = ((x - range).max(0) as usize, (y - range).max(0) as usize);
+                let (right, bottom) = ((x + range + 1).min(self.width as i32) as usize, (y + range + 1).min(self.height as i32) as usize);
+                let mut count = sums[bottom * row + right] + sums[top * row + left] - sums[top * row + right] - sums[bottom * row + left];
+                let cell = Point{x: x as i16, y: y as i16};
+                if self.cells.get(&cell) == Some(&counted_state) {
+                    count -= 1; // a cell isn't its own neighbour
+                }
+                if count > 0 {
+                    neighbour_counts.insert(cell, count as u8);
+                }
+            }
+        }
+    }
+
+    // returns the cells that changed
+    fn update_life_cells(&mut self) -> Vec<Point> {
+
+        // first count how many live neighbours each cell has (dying cells don't count as neighbours)
+        let mut neighbour_counts = mem::take(&mut self.neighbour_counts);
+        self.count_neighbours_into(ALIVE, &self.rule.neighbourhood, &mut neighbour_counts);
+
+        // generate new cells using neighbour counts
+        let mut new_cells = mem::take(&mut self.spare_cells);
+        let mut changed = Vec::new();
+        for (cell, &state) in &self.cells {
+            if state == ALIVE {
+                let neighbours = *neighbour_counts.get(cell).unwrap_or(&0);
+                if self.rule.survival[neighbours as usize] {
+                    new_cells.insert(cell.clone(), ALIVE);
+                    continue; // the only way a cell can stay the same
+                } else if self.rule.num_states > 2 {
+                    new_cells.insert(cell.clone(), ALIVE + 1); // start dying
+                }
+            } else if state + 1 < self.rule.num_states {
+                new_cells.insert(cell.clone(), state + 1); // keep dying
+            }
+            changed.push(cell.clone());
+        }
+        for (cell, &neighbours) in &neighbour_counts {
+            if self.rule.birth[neighbours as usize] && !self.cells.contains_key(cell) && !self.walls.contains(cell) { // only completely dead cells can be born
+                new_cells.insert(cell.clone(), ALIVE);
+                changed.push(cell.clone());
+            }
+        }
+        neighbour_counts.clear(); // emptied rather than dropped so it keeps its room for next time
+        self.neighbour_counts = neighbour_counts;
+        self.replace_cells(new_cells);
+        return changed;
+    }
+
+    // whether few enough cells changed that update_life_cells_near is quicker than update_life_cells
+    fn few_changes(&self, changed: &[Point]) -> bool {
+        return changed.len() * self.rule.neighbourhood.size() < self.cells.len();
+    }
+
+    // update_life_cells for when only the cells in changed changed last generation, which leaves everything that isn't next to one of them the way it is
+    // (dying cells are always changing, so they're always in changed)
+    fn update_life_cells_near(&mut self, changed: &[Point]) -> Vec<Point> {
+        let offsets = self.rule.neighbourhood.offsets();
+        let counted_by: Vec<(i16, i16)> = offsets.iter().map(|(x, y)| (-x, -y)).collect();
+        let mut checked = mem::take(&mut self.checked);
+        for cell in changed {
+            checked.insert(cell.clone());
+            checked.extend(self.neighbours(cell, &counted_by));
+        }
+        let mut updates = Vec::new();
+        for cell in &checked {
+            let neighbours = offsets.iter()
+                .filter(|&&(x, y)| self.offset_cell(cell, x, y).is_some_and(|neighbour| self.cells.get(&neighbour) == Some(&ALIVE)))
+                .count();
+            let state = self.cell_state(cell);
+            let new_state = match state {
+                DEAD if neighbours > 0 && self.rule.birth[neighbours] && !self.walls.contains(cell) => ALIVE, // like update_life_cells, which only looks at cells with neighbours
+                DEAD => DEAD,
+                ALIVE if self.rule.survival[neighbours] => ALIVE,
+                _ if state + 1 < self.rule.num_states => state + 1, // start or keep dying
+                _ => DEAD
+            };
+            if new_state != state {
+                updates.push((cell.clone(), new_state));
+            }
+        }
+        checked.clear();
+        self.checked = checked;
+        let mut now_changed = Vec::with_capacity(updates.len());
+        for (cell, state) in updates {
+            match state {
+                DEAD => self.cells.remove(&cell),
+                _ => self.cells.insert(cell.clone(), state)
+            };
+            now_changed.push(cell);
+        }
+        return now_changed;
+    }
+
+    // life where both players' cells count as neighbours, survivors keep their owner and births go to the player with more of the neighbours
+    // (with the usual rules that's never a tie, with rules like B4 it goes to player one on checkerboard squares that match x + y being even)
+    fn update_immigration_cells(&mut self) {
+        let mut player_one_counts = mem::take(&mut self.neighbour_counts);
+        let mut player_two_counts = mem::take(&mut self.rival_counts);
+        self.count_neighbours_into(PLAYER_ONE, &self.rule.neighbourhood, &mut player_one_counts);
+        self.count_neighbours_into(PLAYER_TWO, &self.rule.neighbourhood, &mut player_two_counts);
+        let mut new_cells = mem::take(&mut self.spare_cells);
+        for (cell, &state) in &self.cells {
+            let neighbours = player_one_counts.get(cell).unwrap_or(&0) + player_two_counts.get(cell).unwrap_or(&0);
+            if self.rule.survival[neighbours as usize] {
+                new_cells.insert(cell.clone(), state);
+            }
+        }
+        let only_player_two = player_two_counts.keys().filter(|cell| !player_one_counts.contains_key(cell)); // the rest were already looked at with player one's
+        for cell in player_one_counts.keys().chain(only_player_two) {
+            let (player_one, player_two) = (*player_one_counts.get(cell).unwrap_or(&0), *player_two_counts.get(cell).unwrap_or(&0));
+            if self.rule.birth[(player_one + player_two) as usize] && !self.cells.contains_key(cell) && !self.walls.contains(cell) {
+                let owner = match player_one.cmp(&player_two) {
+                    core::cmp::Ordering::Greater => PLAYER_ONE,
+                    core::cmp::Ordering::Less => PLAYER_TWO,
+                    core::cmp::Ordering::Equal => if (cell.x + cell.y).rem_euclid(2) == 0 { PLAYER_ONE } else { PLAYER_TWO }
+                };
+                new_cells.insert(cell.clone(), owner);
+            }
+        }
+        player_one_counts.clear();
+        player_two_counts.clear();
+        self.neighbour_counts = player_one_counts;
+        self.rival_counts = player_two_counts;
+        self.replace_cells(new_cells);
+    }
+
+    fn update_wireworld_cells(&mut self) {
+        let mut head_counts = mem::take(&mut self.neighbour_counts);
+        self.count_neighbours_into(ELECTRON_HEAD, &Neighbourhood::Moore(1), &mut head_counts); // wireworld doesn't use the rule
+        let mut new_cells = mem::take(&mut self.spare_cells);
+        for (cell, &state) in &self.cells {
+            let new_state = match state {
+                ELECTRON_HEAD => ELECTRON_TAIL,
+                ELECTRON_TAIL => CONDUCTOR,
+                _ => match head_counts.get(cell) { // a conductor becomes a head if 1 or 2 of its neighbours are heads
+                    Some(1) | Some(2) => ELECTRON_HEAD,
+                    _ => CONDUCTOR
+                }
+            };
+            new_cells.insert(cell.clone(), new_state); // wireworld cells never die
+        }
+        head_counts.clear();
+        self.neighbour_counts = head_counts;
+        self.replace_cells(new_cells);
+    }
+
+    // only blocks with something alive in them can change, empty ones always stay empty (BlockRule::table makes sure of that)
+    fn update_blocks(&mut self, block_rule: &BlockRule) {
+        let table = block_rule.table(self.blocks_shifted);
+        let shift = self.blocks_shifted as i16;
+        let mut corners = mem::take(&mut self.checked);
+        corners.extend(self.cells.keys()
+            .map(|cell| Point{x: (cell.x + shift).div_euclid(2) * 2 - shift, y: (cell.y + shift).div_euclid(2) * 2 - shift})); // the top left of the block it's in
+        let block_cells = |corner: &Point| [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| Point{x: corner.x + x, y: corner.y + y}); // in the order of the block's bits
+        let mut new_cells = mem::take(&mut self.spare_cells);
+        for corner in &corners {
+            let block = block_cells(corner).iter()
+                .enumerate()
+                .filter(|(_, cell)| self.cells.contains_key(cell))
+                .fold(0, |block, (bit, _)| block | 1 << bit);
+            for (bit, cell) in block_cells(corner).iter().enumerate() {
+                let on_board = cell.x >= 0 && cell.y >= 0 && cell.x < self.width as i16 && cell.y < self.height as i16; // blocks on the edge hang off the board
+                if table[block] & 1 << bit != 0 && on_board {
+                    new_cells.insert(cell.clone(), ALIVE);
+                }
+            }
+        }
+        corners.clear();
+        self.checked = corners;
+        self.replace_cells(new_cells);
+        self.blocks_shifted = !self.blocks_shifted;
+    }
+
+    // looks up every cell that isn't empty or is next to one that isn't (empty cells with empty neighbourhoods stay empty, RuleTable::parse checks that)
+    fn update_table_cells(&mut self) {
+        let mut table = match self.table.take() { // taken out so it can cache what it looks up while the cells are being read
+            Some(table) => table,
+            None => return
+        };
+        let offsets = table.offsets().to_vec();
+        let counted_by: Vec<(i16, i16)> = offsets.iter().map(|(x, y)| (-x, -y)).collect();
+        let mut checked = mem::take(&mut self.checked);
+        for cell in self.cells.keys() {
+            checked.insert(cell.clone());
+            checked.extend(self.neighbours(cell, &counted_by));
+        }
+        let mut new_cells = mem::take(&mut self.spare_cells);
+        for cell in &checked {
+            let mut cell_and_neighbours = Vec::with_capacity(offsets.len() + 1);
+            cell_and_neighbours.push(self.cell_state(cell));
+            cell_and_neighbours.extend(offsets.iter().map(|&(x, y)| self.offset_cell(cell, x, y).map_or(DEAD, |neighbour| self.cell_state(&neighbour)))); // off the board counts as empty
+            let state = table.next_state(&cell_and_neighbours);
+            if state != DEAD {
+                new_cells.insert(cell.clone(), state);
+            }
+        }
+        checked.clear();
+        self.checked = checked;
+        self.replace_cells(new_cells);
+        self.table = Some(table);
+    }
+
+    // unlike the other automata this updates cells one ant at a time (in the order the ants were placed)
+    fn update_ants(&mut self) {
+        for ant in self.ants.iter_mut() {
+            if self.cells.remove(&ant.position).is_some() { // black cell: turn left and flip it to white
+                ant.direction = (ant.direction + 3) % 4;
+            } else { // white cell: turn right and flip it to black
+                ant.direction = (ant.direction + 1) % 4;
+                self.cells.insert(ant.position.clone(), ALIVE);
+            }
+
+            // step forward, wrapping around the edges of the board
+            let (width, height) = (self.width as i16, self.height as i16);
+            let position = &mut ant.position;
+            match ant.direction {
+                0 => position.y = (position.y - 1).rem_euclid(height), // rem_euclid instead of adding height first, which can overflow on the biggest boards
+                1 => position.x = (position.x + 1).rem_euclid(width),
+                2 => position.y = (position.y + 1).rem_euclid(height),
+                _ => position.x = (position.x - 1).rem_euclid(width),
+            }
+        }
+    }
+
+    pub fn ant_at(&self, position: &Point) -> Option<usize> {
+        return self.ants.iter().position(|ant| ant.position == *position);
+    }
+}
+
+
+// a Board that remembers which cells changed in Life's last generation, since only cells next to them can change in the next one so that's all it has to look at
+// the board can only be edited through board_mut, which forgets what changed so that the next generation looks at every cell again (other automata run like they always do)
+// it derefs to the board too, and borrowing it mutably that way goes through board_mut, so code that takes a &mut Board only costs a full generation when it actually gets one
+pub struct IncrementalBoard {
+    board: Board,
+    changed: Option<Vec<Point>>,
+}
+
+
+impl IncrementalBoard {
+    pub fn new(board: Board) -> IncrementalBoard {
+        return IncrementalBoard { board, changed: None };
+    }
+
+    pub fn board(&self) -> &Board {
+        return &self.board;
+    }
+
+    // anything could get changed with this, so the next generation has to look at every cell
+    pub fn board_mut(&mut self) -> &mut Board {
+        self.changed = None;
+        return &mut self.board;
+    }
+
+    pub fn into_board(self) -> Board {
+        return self.board;
+    }
+
+    pub fn update_cells(&mut self) {
+        self.changed = self.board.advance(self.changed.take());
+    }
+
+    // Board::grow_to_fit, which only counts as an edit if the board actually moved or grew (cells that were off the edge might come alive now)
+    pub fn grow_to_fit(&mut self, margin: i16) -> Point {
+        let size = (self.board.width, self.board.height);
+        let shift = self.board.grow_to_fit(margin);
+        if shift.x != 0 || shift.y != 0 || (self.board.width, self.board.height) != size {
+            self.changed = None;
+        }
+        return shift;
+    }
+
+    pub fn step(&mut self) -> StepStats {
+        let stats = self.board.step_from(self.changed.take());
+        if self.board.automaton == Automaton::Life {
+            self.changed = Some(stats.changed.clone()); // every cell that's different from before, which is all the next generation needs
+        }
+        return stats;
+    }
+}
+
+
+impl Deref for IncrementalBoard {
+    type Target = Board;
+
+    fn deref(&self) -> &Board {
+        return &self.board;
+    }
+}
+
+
+impl DerefMut for IncrementalBoard {
+    fn deref_mut(&mut self) -> &mut Board {
+        return self.board_mut();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: [(i16, i16); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    // 10 blocks that never change, so an incremental board has settled down after one generation
+    fn settled_board() -> Board {
+        let mut board = Board::new(80, 80, Automaton::Life, Rule::conways_life());
+        for block in 0..10 {
+            for &(x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+                board.cells.insert(Point{x: 5 + block * 7 + x, y: 70 + y}, ALIVE);
+            }
+        }
+        return board;
+    }
+
+    fn add_glider(cells: &mut CellMap<u8>) {
+        for &(x, y) in GLIDER.iter() {
+            cells.insert(Point{x: 10 + x, y: 10 + y}, ALIVE);
+        }
+    }
+
+    // what the board should be 4 generations after the glider was added, worked out on a board that's never been run
+    fn expected_hash(board: &Board) -> u64 {
+        let mut fresh = Board::new(board.width, board.height, Automaton::Life, Rule::conways_life());
+        fresh.cells = board.cells.clone();
+        for _ in 0..4 {
+            fresh.update_cells();
+        }
+        return fresh.state_hash();
+    }
+
+    fn glider_moved(board: &Board) -> bool {
+        return GLIDER.iter().all(|&(x, y)| board.cells.contains_key(&Point{x: 11 + x, y: 11 + y}));
+    }
+
+    #[test]
+    fn cells_edited_after_settling_down_still_evolve() {
+        let mut board = settled_board();
+        for _ in 0..5 {
+            board.update_cells();
+        }
+        add_glider(&mut board.cells);
+        let expected = expected_hash(&board);
+        for _ in 0..4 {
+            board.update_cells();
+        }
+        assert_eq!(board.state_hash(), expected);
+        assert!(glider_moved(&board));
+    }
+
+    #[test]
+    fn incremental_board_sees_edits_made_through_board_mut() {
+        let mut board = IncrementalBoard::new(settled_board());
+        for _ in 0..5 {
+            board.update_cells();
+        }
+        add_glider(&mut board.board_mut().cells);
+        let expected = expected_hash(board.board());
+        for _ in 0..4 {
+            board.update_cells();
+        }
+        assert_eq!(board.board().state_hash(), expected);
+        assert!(glider_moved(board.board()));
+    }
+
+    #[test]
+    fn incremental_board_sees_edits_made_through_deref_mut() {
+        let mut board = IncrementalBoard::new(settled_board());
+        for _ in 0..5 {
+            board.update_cells();
+        }
+        add_glider(&mut board.cells);
+        let expected = expected_hash(&board);
+        for _ in 0..4 {
+            board.update_cells();
+        }
+        assert_eq!(board.state_hash(), expected);
+        assert!(glider_moved(&board));
+    }
+
+    #[test]
+    fn incremental_board_looks_at_every_cell_after_growing() {
+        let mut board = IncrementalBoard::new(settled_board());
+        add_glider(&mut board.cells);
+        for _ in 0..5 {
+            board.update_cells();
+        }
+        let shift = board.grow_to_fit(20); // the blocks are closer to the edges than that, so everything moves
+        assert!(shift.x > 0 && shift.y > 0);
+        let expected = expected_hash(&board);
+        for _ in 0..4 {
+            board.update_cells();
+        }
+        assert_eq!(board.state_hash(), expected);
+    }
+
+    fn points(cells: &[(i16, i16)]) -> Vec<Point> {
+        return cells.iter().map(|&(x, y)| Point{x, y}).collect();
+    }
+
+    fn board_with(rule: &str, cells: &[(i16, i16)]) -> Board {
+        let mut board = Board::new(20, 20, Automaton::Life, Rule::parse(rule).unwrap());
+        for cell in points(cells) {
+            board.cells.insert(cell, ALIVE);
+        }
+        return board;
+    }
+
+    fn sorted(mut cells: Vec<Point>) -> Vec<Point> {
+        cells.sort();
+        return cells;
+    }
+
+    #[test]
+    fn step_counts_births_and_deaths() {
+        let mut board = board_with("B3/S23", &[(5, 5), (6, 5), (7, 5)]); // a blinker, which turns on its side
+        let stats = board.step();
+        assert_eq!((stats.births, stats.deaths), (2, 2));
+        assert!(sorted(stats.changed) == sorted(points(&[(5, 5), (7, 5), (6, 4), (6, 6)])));
+    }
+
+    #[test]
+    fn step_leaves_out_walls_and_sources_that_stay_the_same() {
+        let mut board = board_with("B3/S23", &[(5, 5), (6, 5), (7, 5), (15, 15)]);
+        board.walls.insert(Point{x: 6, y: 4}); // where the blinker would have a birth
+        board.sources.insert(Point{x: 15, y: 15}); // on its own, so it'd die without being a source
+        let stats = board.step();
+        assert_eq!((stats.births, stats.deaths), (1, 2));
+        assert!(sorted(stats.changed) == sorted(points(&[(5, 5), (7, 5), (6, 6)])));
+    }
+
+    #[test]
+    fn step_counts_walls_and_sources_that_were_just_put_down() {
+        let mut board = board_with("B3/S23", &[(5, 5), (6, 5), (7, 5)]);
+        board.walls.insert(Point{x: 5, y: 5}); // a live cell that a wall's been put on
+        board.sources.insert(Point{x: 15, y: 15}); // a source that isn't alive yet
+        let stats = board.step();
+        assert_eq!((stats.births, stats.deaths), (3, 2)); // the source is born along with the blinker's new ends, and its old ends die (the wall takes one but it'd have died anyway)
+        assert!(sorted(stats.changed) == sorted(points(&[(5, 5), (7, 5), (6, 4), (6, 6), (15, 15)])));
+    }
+
+    #[test]
+    fn step_only_counts_dying_cells_as_deaths_once_they_are_dead() {
+        let mut board = board_with("B2/S/C3", &[(5, 5), (6, 5)]); // brian's brain
+        let stats = board.step();
+        assert_eq!((stats.births, stats.deaths), (4, 0)); // both cells start dying, which isn't dead yet
+        assert!(sorted(stats.changed) == sorted(points(&[(5, 5), (6, 5), (5, 4), (6, 4), (5, 6), (6, 6)])));
+        let stats = board.step();
+        assert_eq!(stats.deaths, 2);
+        assert!(stats.changed.contains(&Point{x: 5, y: 5}) && !board.cells.contains_key(&Point{x: 5, y: 5}));
+    }
+
+    // the board flipped left to right, which a moore neighbourhood can't tell apart from the original
+    fn mirrored(board: &Board) -> Board {
+        let mut mirror = Board::new(board.width, board.height, Automaton::Life, board.rule.clone());
+        mirror.cells = board.cells.iter().map(|(cell, &state)| (Point{x: board.width as i16 - 1 - cell.x, y: cell.y}, state)).collect();
+        return mirror;
+    }
+
+    #[test]
+    fn big_neighbourhoods_reach_past_the_right_edge_of_the_widest_board() {
+        let mut soup = Board::new(i16::MAX as u32, 6, Automaton::Life, Rule::parse("R7,C0,M1,S34..58,B34..45,NM").unwrap());
+        soup.init_from_seed(1); // crowded enough to be counted by count_neighbours_dense
+        let mut sparse = Board::new(i16::MAX as u32, 20, Automaton::Life, Rule::parse("B3/S23/NM2").unwrap());
+        for &(x, y) in [(1, 5), (2, 5), (0, 6), (1, 6), (1, 7), (0, 12), (1, 12), (2, 12)].iter() { // touching the left edge, so its mirror touches the right
+            sparse.cells.insert(Point{x, y}, ALIVE);
+        }
+        for board in [soup, sparse] {
+            let mut left = IncrementalBoard::new(board); // so it gets to update_life_cells_near once it's settled down
+            let mut right = IncrementalBoard::new(mirrored(left.board()));
+            for _ in 0..6 {
+                left.update_cells();
+                right.update_cells();
+            }
+            assert_eq!(mirrored(left.board()).state_hash(), right.board().state_hash());
+        }
+    }
+
+    #[test]
+    fn ants_wrap_around_the_edges_of_the_widest_board() {
+        let mut board = Board::new(i16::MAX as u32, 4, Automaton::LangtonsAnt, Rule::conways_life());
+        board.ants.push(Ant { position: Point{x: 0, y: 0}, direction: 2 }); // turns right on its white cell to face left, off the left edge
+        board.update_cells();
+        assert!(board.ants[0].position == Point{x: i16::MAX - 1, y: 0});
+        board.update_cells(); // then right again on the far edge, to face up and off the top
+        assert!(board.ants[0].position == Point{x: i16::MAX - 1, y: 3});
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use game_of_life::workload;
+
+
+
+// a pattern that's built into the binary so it can be shown without any files
+pub struct BundledPattern {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub rle: &'static str,
+}
+
+
+// all of these are B3/S23 patterns
+pub const BUNDLED_PATTERNS: [BundledPattern; 16] = [
+    BundledPattern {
+        name: "Glider",
+        description: "The smallest spaceship, moves one cell diagonally every 4 generations",
+        rle: "bo$2bo$3o!",
+    },
+    BundledPattern {
+        name: "Lightweight spaceship",
+        description: "The smallest orthogonal spaceship, moves 2 cells every 4 generations",
+        rle: "bo2bo$o$o3bo$4o!",
+    },
+    BundledPattern {
+        name: "Copperhead",
+        description: "A slow spaceship found in 2016 that moves one cell every 10 generations",
+        rle: "b2o2b2o$3b2o$3b2o$obo2bobo$o6bo2$o6bo$b2o2b2o$2b4o2$3b2o$3b2o!",
+    },
+    BundledPattern {
+        name: "Gosper glider gun",
+        description: "The first gun ever found, it fires a glider every 30 generations",
+        rle: workload::GOSPER_GLIDER_GUN, // the bench's glider gun workload is the same one
+    },
+    BundledPattern {
+        name: "Simkin glider gun",
+        description: "The smallest known glider gun, firing one glider every 120 generations",
+        rle: "2o5b2o$2o5b2o2$4b2o$4b2o5$22b2ob2o$21bo5bo$21bo6bo2b2o$21b3o3bo3b2o$26bo4$20b2o$20bo$21b3o$23bo!",
+    },
+    BundledPattern {
+        name: "R-pentomino",
+        description: "5 cells that take 1103 generations to settle down",
+        rle: "b2o$2o$bo!",
+    },
+    BundledPattern {
+        name: "Acorn",
+        description: "7 cells that grow into 633 cells over 5206 generations",
+        rle: "bo$3bo$2o2b3o!",
+    },
+    BundledPattern {
+        name: "Diehard",
+        description: "Completely disappears after 130 generations",
+        rle: "6bo$2o$bo3b3o!",
+    },
+    BundledPattern {
+        name: "Bunnies",
+        description: "A methuselah that lasts 17332 generations",
+        rle: "o5bo$2bo3bo$2bo2bobo$bobo!",
+    },
+    BundledPattern {
+        name: "Thunderbird",
+        description: "Settles into a ring of blinkers and beehives after 243 generations",
+        rle: "3o2$bo$bo$bo!",
+    },
+    BundledPattern {
+        name: "Pi-heptomino",
+        description: "A common shape in big explosions, it stabilizes after 173 generations",
+        rle: "3o$obo$obo!",
+    },
+    BundledPattern {
+        name: "Herschel",
+        description: "Used to move signals around in big constructions, it settles after 128 generations",
+        rle: "o$3o$obo$2bo!",
+    },
+    BundledPattern {
+        name: "Pulsar",
+        description: "The most common period 3 oscillator",
+        rle: "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    },
+    BundledPattern {
+        name: "Pentadecathlon",
+        description: "An oscillator with period 15",
+        rle: "2bo4bo$2ob4ob2o$2bo4bo!",
+    },
+    BundledPattern {
+        name: "Queen bee shuttle",
+        description: "A queen bee bouncing between two blocks, with period 30",
+        rle: "9bo$7bobo$6bobo$2o3bo2bo11b2o$2o4bobo11b2o$7bobo$9bo!",
+    },
+    BundledPattern {
+        name: "Infinite growth",
+        description: "One of the smallest patterns that grows forever, it turns into a block laying switch engine",
+        rle: "6bo$4bob2o$4bobo$4bo$2bo$obo!",
+    },
+];
+
+
+// what --demo cycles through, by name (the ones that are the most fun to watch for a few seconds)
+pub const DEMO_PATTERNS: [&str; 6] = ["Gosper glider gun", "Pulsar", "Pentadecathlon", "Acorn", "Copperhead", "Queen bee shuttle"];
+
+
+pub fn find(name: &str) -> Option<&'static BundledPattern> {
+    return BUNDLED_PATTERNS.iter().find(|pattern| pattern.name == name);
+}
+
+
+// picks a pattern based on today's date (in UTC) so it's the same all day and different the next
+pub fn pattern_of_the_day() -> &'static BundledPattern {
+    let days_since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / (24 * 60 * 60))
+        .unwrap_or(0);
+    return &BUNDLED_PATTERNS[days_since_epoch as usize % BUNDLED_PATTERNS.len()];
+}
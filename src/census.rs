@@ -0,0 +1,46 @@
+use std::io::{self, BufWriter, Write};
+use std::process;
+use game_of_life::{objects, Automaton, Board};
+use game_of_life::objects::Object;
+use crate::emit::pattern_or_random_board;
+use crate::{headless_engine, Args};
+
+
+
+const USAGE: &str = "usage: game_of_life census [--load <pattern file>] [--generations <n>] [--seed <n>] [--width <n>] [--height <n>] [--engine <engine>]";
+
+
+// the object's name and what it is if it has a name, otherwise just what it is
+pub fn describe(object: &Object) -> String {
+    return match object.name {
+        Some(name) => format!("{} ({})", name, object.description()),
+        None => object.description()
+    };
+}
+
+
+// the "census" subcommand: runs a pattern (or a random board) for --generations and then lists what's left on it, most common objects first
+// one line per kind of object with how many there are, its apgcode (- if it doesn't have one) and what it is
+pub fn run_census(mut args: Args) {
+    let board = pattern_or_random_board(&mut args, USAGE);
+    if board.automaton != Automaton::Life {
+        eprintln!("the census only works in Life");
+        process::exit(1);
+    }
+    let mut finished = Board::new(board.width, board.height, Automaton::Life, board.rule.clone()); // where the cells end up once --engine has run them
+    let mut engine = headless_engine(&args, board);
+    for _ in 0..args.generations {
+        engine.step();
+    }
+    finished.cells = engine.cells();
+
+    let objects = objects::find_objects(&finished);
+    let stdout = io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    for (count, object) in objects::census(&objects) {
+        if writeln!(output, "{} {} {}", count, object.apgcode.as_deref().unwrap_or("-"), describe(object)).is_err() {
+            return; // whatever was reading the output stopped, which is fine
+        }
+    }
+    output.flush().ok();
+}
@@ -0,0 +1,292 @@
+use std::{env, fs};
+use std::io::Write;
+use std::path::Path;
+use game_of_life::{Point, Rule};
+use crate::bundled::BUNDLED_PATTERNS;
+use crate::pattern::{self, Pattern};
+use crate::presets::RULE_PRESETS;
+use crate::theme::{theme_index, THEMES};
+
+
+
+// every command the : prompt understands, with what goes after it
+pub const COMMANDS: [(&str, &str); 13] = [
+    ("rule", "<rule>"),
+    ("skip", "<generations>"),
+    ("load", "<file or pattern name>"),
+    ("save", "[file]"),
+    ("stamp", "<pattern name or apgcode>"),
+    ("theme", "<name>"),
+    ("grid", "[spacing or off]"),
+    ("goto", "<x>,<y>"),
+    ("seed", "<n>"),
+    ("resize", "<width>x<height>"),
+    ("clear", ""),
+    ("census", ""),
+    ("quit", ""),
+];
+
+const HISTORY_FILE_NAME: &str = ".game_of_life_history"; // in the home directory
+const MAX_HISTORY_LENGTH: usize = 500;
+
+
+pub enum Command {
+    Rule(Rule),
+    Skip(u32),
+    Load(String),
+    LoadBundled(usize), // index into BUNDLED_PATTERNS
+    Save(Option<String>), // None saves over the loaded file
+    Stamp(usize), // index into BUNDLED_PATTERNS
+    StampApgcode(String, Pattern),
+    Theme(usize), // index into THEMES
+    Grid(Option<u32>), // lines every this many cells, None hides them
+    ToggleGrid,
+    Goto(Point), // moves the cursor there
+    Seed(u64), // the same random board as verify --seed gives
+    Resize(u32, u32), // width and height
+    Clear,
+    Census, // lists the objects on the board
+    Quit,
+}
+
+
+// what tab did to the text before the cursor
+pub struct Completion {
+    pub text: String, // replaces the text before the cursor
+    pub options: Vec<String>, // everything that could have been meant if there was more than one, to show under the text
+}
+
+
+// commands that have been run, newest last, saved to a file in the home directory so they're still there next time
+pub struct History {
+    entries: Vec<String>,
+    position: Option<usize>, // which entry up/down has got to, None while typing a new command
+    saved: bool, // false for a history that doesn't use the file
+}
+
+
+// x,y (or x y) as a cell on the board
+fn parse_point(text: &str) -> Option<Point> {
+    let mut numbers = text.split([',', ' ']).filter(|number| !number.is_empty()).map(|number| number.parse::<i16>());
+    let point = Point{x: numbers.next()?.ok()?, y: numbers.next()?.ok()?};
+    return if numbers.next().is_none() { Some(point) } else { None };
+}
+
+
+pub fn parse(text: &str) -> Result<Command, String> {
+    let text = text.trim();
+    if let Some(point) = parse_point(text) { // a coordinate on its own goes there, like :12,34
+        return Ok(Command::Goto(point));
+    }
+    let (name, argument) = match text.split_once(' ') {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (text, "")
+    };
+    let needs_argument = |argument: &str| -> Result<(), String> {
+        let usage = COMMANDS.iter().find(|(command, _)| *command == name).unwrap().1;
+        return if argument.is_empty() { Err(format!("Usage: {} {}", name, usage)) } else { Ok(()) };
+    };
+    return match name {
+        "rule" => {
+            needs_argument(argument)?;
+            Rule::parse(argument).map(Command::Rule)
+        }
+        "skip" => match argument.parse::<u32>() {
+            Ok(total) if total > 0 => Ok(Command::Skip(total)),
+            _ => Err("Usage: skip <generations>".to_string())
+        },
+        "load" => {
+            needs_argument(argument)?;
+            let bundled = BUNDLED_PATTERNS.iter().position(|pattern| pattern.name.eq_ignore_ascii_case(argument));
+            match bundled {
+                Some(index) if !Path::new(argument).exists() => Ok(Command::LoadBundled(index)), // files win over bundled patterns with the same name
+                _ => Ok(Command::Load(argument.to_string()))
+            }
+        }
+        "save" => Ok(Command::Save(Some(argument.to_string()).filter(|path| !path.is_empty()))),
+        "stamp" => {
+            needs_argument(argument)?;
+            if pattern::is_apgcode(argument) {
+                return pattern::parse_apgcode(argument).map(|pattern| Command::StampApgcode(argument.to_string(), pattern));
+            }
+            BUNDLED_PATTERNS.iter()
+                .position(|pattern| pattern.name.eq_ignore_ascii_case(argument))
+                .map(Command::Stamp)
+                .ok_or(format!("No pattern called {}", argument))
+        }
+        "theme" => {
+            needs_argument(argument)?;
+            theme_index(argument).map(Command::Theme).ok_or(format!("No theme called {}", argument))
+        }
+        "grid" => match argument {
+            "" => Ok(Command::ToggleGrid),
+            "off" => Ok(Command::Grid(None)),
+            _ => match argument.parse::<u32>() {
+                Ok(spacing) if spacing >= 2 => Ok(Command::Grid(Some(spacing))),
+                _ => Err("Usage: grid [spacing or off] (at least 2)".to_string())
+            }
+        },
+        "goto" => parse_point(argument).map(Command::Goto).ok_or("Usage: goto <x>,<y>".to_string()),
+        "seed" => argument.parse().map(Command::Seed).map_err(|_| "Usage: seed <n>".to_string()),
+        "resize" => {
+            let size = argument.to_lowercase().split_once('x').and_then(|(width, height)| Some((width.trim().parse::<u32>().ok()?, height.trim().parse::<u32>().ok()?)));
+            match size {
+                Some((width, height)) if width > 0 && height > 0 => Ok(Command::Resize(width, height)),
+                _ => Err("Usage: resize <width>x<height>".to_string())
+            }
+        }
+        "clear" => Ok(Command::Clear),
+        "census" => Ok(Command::Census),
+        "quit" => Ok(Command::Quit),
+        "" => Err("Type a command (Tab lists them)".to_string()),
+        _ => Err(format!("Unknown command {} (Tab lists them)", name))
+    };
+}
+
+
+// completes a command name, or whatever goes after the command (a file, rule, pattern or theme)
+pub fn complete(before_cursor: &str) -> Option<Completion> {
+    let (prefix, word, options) = match before_cursor.split_once(' ') {
+        None => {
+            let names = COMMANDS.iter().map(|(name, _)| name.to_string()).collect();
+            (String::new(), before_cursor, names)
+        }
+        Some((name, argument)) => {
+            let argument = argument.trim_start();
+            let prefix = before_cursor[..before_cursor.len() - argument.len()].to_string();
+            let options = match name {
+                "load" => match complete_path(&prefix, argument) { // bundled patterns once no files match
+                    Some(completion) => return Some(completion),
+                    None => BUNDLED_PATTERNS.iter().map(|pattern| pattern.name.to_string()).collect()
+                },
+                "save" => return complete_path(&prefix, argument),
+                "rule" => RULE_PRESETS.iter().map(|preset| preset.rule.to_string()).collect(),
+                "stamp" => BUNDLED_PATTERNS.iter().map(|pattern| pattern.name.to_string()).collect(),
+                "theme" => THEMES.iter().map(|theme| theme.name.to_string()).collect(),
+                "grid" => vec!["off".to_string()],
+                _ => Vec::new()
+            };
+            (prefix, argument, options)
+        }
+    };
+    let matches: Vec<String> = options.into_iter()
+        .filter(|option| option.to_lowercase().starts_with(&word.to_lowercase()))
+        .collect();
+    let completed = common_prefix(&matches)?;
+    let mut text = prefix + if completed.len() >= word.len() { &completed } else { word };
+    if matches.len() == 1 && !before_cursor.contains(' ') {
+        text.push(' '); // straight on to the command's argument
+    }
+    return Some(Completion { text, options: if matches.len() > 1 { matches } else { Vec::new() } });
+}
+
+
+// file names in whichever directory has been typed so far, directories end with a /
+fn complete_path(prefix: &str, path: &str) -> Option<Completion> {
+    let (directory, file_start) = match path.rfind('/') {
+        Some(index) => path.split_at(index + 1),
+        None => ("", path)
+    };
+    let mut matches: Vec<String> = fs::read_dir(if directory.is_empty() { "." } else { directory }).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let mut name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(file_start) || (name.starts_with('.') && !file_start.starts_with('.')) { // hidden files only when asked for
+                return None;
+            }
+            if entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false) {
+                name.push('/');
+            }
+            return Some(name);
+        })
+        .collect();
+    matches.sort();
+    let completed = common_prefix(&matches)?;
+    return Some(Completion {
+        text: format!("{}{}{}", prefix, directory, completed),
+        options: if matches.len() > 1 { matches } else { Vec::new() }
+    });
+}
+
+
+// the longest start that all of the options share, None if there aren't any
+fn common_prefix(options: &[String]) -> Option<String> {
+    let first = options.first()?;
+    let length = options.iter().skip(1).fold(first.len(), |length, option| {
+        let shared = first.char_indices().zip(option.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((index, _), _)| index)
+            .unwrap_or_else(|| first.len().min(option.len()));
+        return length.min(shared);
+    });
+    return Some(first[..length].to_string());
+}
+
+
+fn history_path() -> Option<String> {
+    let home = env::var("HOME").ok()?;
+    return Some(Path::new(&home).join(HISTORY_FILE_NAME).to_string_lossy().to_string());
+}
+
+
+impl History {
+    // reads the history from the last time the game was played, starts empty if there isn't one
+    pub fn load() -> History {
+        let contents = history_path().and_then(|path| fs::read_to_string(path).ok()).unwrap_or_default();
+        let mut entries: Vec<String> = contents.lines().filter(|line| !line.is_empty()).map(|line| line.to_string()).collect();
+        entries.drain(..entries.len().saturating_sub(MAX_HISTORY_LENGTH));
+        return History { entries, position: None, saved: true };
+    }
+
+    // a history that starts empty and is never saved, for recordings and replays (which would go differently if up pulled in commands from the file)
+    pub fn unsaved() -> History {
+        return History { entries: Vec::new(), position: None, saved: false };
+    }
+
+    // remembers a command that was run (in memory and in the file), running the same command twice in a row only keeps it once
+    // not being able to save the history isn't worth interrupting the game over, it's still remembered for this game either way
+    pub fn add(&mut self, command: &str) -> Result<(), String> {
+        self.position = None;
+        if command.is_empty() || self.entries.last().map(|last| last.as_str()) == Some(command) {
+            return Ok(());
+        }
+        self.entries.push(command.to_string());
+        if self.entries.len() > MAX_HISTORY_LENGTH {
+            self.entries.remove(0);
+        }
+        if let (true, Some(path)) = (self.saved, history_path()) {
+            let written = match self.entries.len() { // the file only needs rewriting when old entries have to be dropped from it
+                MAX_HISTORY_LENGTH => fs::write(&path, self.entries.join("\n") + "\n"),
+                _ => fs::OpenOptions::new().create(true).append(true).open(&path).and_then(|mut file| writeln!(file, "{}", command))
+            };
+            return written.map_err(|error| format!("Couldn't save the command history to {}: {}", path, error));
+        }
+        return Ok(());
+    }
+
+    pub fn reset_position(&mut self) {
+        self.position = None;
+    }
+
+    // the command before the one that's showing, None if there aren't any older ones
+    pub fn older(&mut self) -> Option<&str> {
+        let position = match self.position {
+            Some(0) => return None,
+            Some(position) => position - 1,
+            None => self.entries.len().checked_sub(1)?
+        };
+        self.position = Some(position);
+        return Some(&self.entries[position]);
+    }
+
+    // the command after the one that's showing, or an empty line after the newest one
+    pub fn newer(&mut self) -> Option<&str> {
+        let position = self.position? + 1;
+        if position == self.entries.len() {
+            self.position = None;
+            return Some("");
+        }
+        self.position = Some(position);
+        return Some(&self.entries[position]);
+    }
+}
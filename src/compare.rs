@@ -0,0 +1,166 @@
+use std::{process, thread, time};
+use std::io::Write;
+use termion::event::Key;
+use game_of_life::pattern::{self, Pattern};
+use game_of_life::{Automaton, Board, Point, Rule};
+use crate::renderer::{every_cell, Renderer, TermionRenderer};
+use crate::theme::{ColourDepth, Rgb, THEMES};
+use crate::{check_board_size, terminal, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: compare --against <rule> [--rule <rule>] [--pattern <file> or --seed <n>]";
+
+
+// puts the --pattern in the middle of the board, or fills it from the seed if there isn't one (the same seed gives both boards the same cells)
+fn set_up(board: &mut Board, pattern: Option<&Pattern>, seed: u64) {
+    match pattern {
+        Some(pattern) => {
+            board.clear();
+            board.place_pattern(pattern, &Point{
+                x: (board.width as i16 - pattern.width as i16) / 2,
+                y: (board.height as i16 - pattern.height as i16) / 2
+            });
+        }
+        None => board.init_from_seed(seed)
+    }
+}
+
+
+// the box around both boards with each one's rule in the top of it, and a line down the middle between them
+fn draw_border(renderer: &mut dyn Renderer, boards: &[Board; 2], colour: Option<Rgb>) {
+    let width = boards[0].width as usize;
+    let titles: Vec<String> = boards.iter()
+        .map(|board| {
+            let title: String = format!(" {} ", board.rule).chars().take(width.saturating_sub(1)).collect();
+            return format!("═{}{}", title, "═".repeat(width.saturating_sub(title.chars().count() + 1)));
+        })
+        .collect();
+    renderer.draw_text(1, 1, &format!("╔{}╦{}╗", titles[0], titles[1]), colour);
+    for y in 0..boards[0].height as u16 {
+        for x in [1, width as u16 + 2, 2 * width as u16 + 3] {
+            renderer.draw_text(x, y + 2, "║", colour);
+        }
+    }
+    renderer.draw_text(1, boards[0].height as u16 + 2, &format!("╚{}╩{}╝", "═".repeat(width), "═".repeat(width)), colour);
+}
+
+
+// the "compare" subcommand: the same start run under two rules side by side, both boards going forward a generation at a time together
+// the left board uses --rule (conway's life if there isn't one) and the right one uses --against
+#[allow(unused_must_use)]
+pub fn run_compare(args: Args) {
+    let against = args.against.clone().unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    });
+    let pattern = args.pattern_path.as_ref().map(|path| pattern::load_pattern(path).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    }));
+
+    // the boards fill the terminal between the border and the status line under it
+    let (columns, rows) = terminal::terminal_size().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    let (width, height) = (columns.saturating_sub(3) / 2, rows.saturating_sub(3));
+    let width = args.board_width.map_or(width, |board_width| board_width.min(width)) as u32;
+    let height = args.board_height.map_or(height, |board_height| board_height.min(height)) as u32;
+    if width == 0 || height == 0 {
+        eprintln!("the terminal is too small to fit two boards");
+        process::exit(1);
+    }
+    check_board_size(&args, 2 * width, height, SIMULATION_BYTES_PER_CELL);
+    let rule = args.rule.clone().unwrap_or_else(Rule::conways_life);
+    let mut boards = [Board::new(width, height, Automaton::Life, rule), Board::new(width, height, Automaton::Life, against)];
+    let mut seed = args.seed.unwrap_or_else(rand::random);
+    for board in boards.iter_mut() {
+        set_up(board, pattern.as_ref(), seed);
+    }
+    let colour_depth = ColourDepth::detect();
+    let frame_delay = time::Duration::from_secs(1) / args.fps.max(1);
+
+    let key_source = terminal::key_source().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+
+    terminal::save_terminal_state();
+    terminal::install_panic_hook();
+    terminal::install_signal_handlers();
+    let _terminal_guard = terminal::TerminalGuard;
+    let mut stdout = terminal::enter_raw_mode().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    let key_input = terminal::spawn_key_reader(key_source);
+    write!(stdout, "{}", termion::cursor::Hide);
+    let mut renderer = TermionRenderer::new(&mut stdout, colour_depth, args.theme, columns);
+    renderer.clear();
+    draw_border(&mut renderer, &boards, THEMES[args.theme].border);
+
+    let mut paused = false;
+    let mut generation: u64 = 0;
+    let mut changed = [every_cell(&boards[0]), every_cell(&boards[1])];
+    let mut next_frame_time = time::Instant::now();
+    let mut terminal_error = None;
+    'frames: while !terminal::quit_requested() {
+        renderer.draw_cells(&boards[0], &changed[0], 2, 2);
+        renderer.draw_cells(&boards[1], &changed[1], width as u16 + 3, 2);
+        let same = if boards[0].cells == boards[1].cells { " (the same so far)" } else { "" };
+        let start = if pattern.is_some() { "restart" } else { "reseed" };
+        renderer.draw_status(height as u16 + 3, &format!(
+            "Generation {}   Population: {} vs {}{}   Seed: {}   Space: pause, F: step, R: {}, Q: quit",
+            generation, boards[0].population(), boards[1].population(), same, seed, start
+        ));
+        if let Err(error) = renderer.present() {
+            terminal_error = Some(error);
+            break;
+        }
+        for board_changed in changed.iter_mut() {
+            board_changed.clear();
+        }
+
+        let mut step = !paused;
+        for key in key_input.try_iter() {
+            match key {
+                Key::Char('q') | Key::Char('Q') | Key::Ctrl('c') => break 'frames,
+                Key::Char(' ') => paused = !paused,
+                Key::Char('f') | Key::Char('F') if paused => step = true,
+                Key::Char('r') | Key::Char('R') => { // a new seed unless it's a pattern, which just starts over
+                    if pattern.is_none() {
+                        seed = rand::random();
+                    }
+                    for board in boards.iter_mut() {
+                        set_up(board, pattern.as_ref(), seed);
+                    }
+                    changed = [every_cell(&boards[0]), every_cell(&boards[1])];
+                    generation = 0;
+                    step = false;
+                }
+                _ => ()
+            }
+        }
+        if step {
+            for (board, board_changed) in boards.iter_mut().zip(changed.iter_mut()) {
+                *board_changed = board.step().changed;
+            }
+            generation += 1;
+        }
+
+        next_frame_time += frame_delay;
+        let now = time::Instant::now();
+        if next_frame_time > now {
+            thread::sleep(next_frame_time - now);
+        } else {
+            next_frame_time = now;
+        }
+    }
+    write!(stdout, "{}{}{}", colour_depth.fg(None), termion::cursor::Show, termion::clear::All);
+    if let Some(error) = terminal_error {
+        drop(stdout); // leave the alternate screen first so the message stays up
+        eprintln!("couldn't draw to the terminal: {}", error);
+        process::exit(1);
+    }
+}
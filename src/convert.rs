@@ -0,0 +1,27 @@
+use std::{fs, process};
+use game_of_life::pattern::{self, PatternFormat};
+
+
+
+// the "convert" subcommand: reads a pattern file in one format and writes it out in another (picked from the output file's extension)
+pub fn run_convert(args: &[String]) {
+    let (in_path, out_path) = match args {
+        [in_path, out_path] => (in_path, out_path),
+        _ => {
+            eprintln!("usage: game_of_life convert <in.rle|in.cells|in.lif> <out.rle|out.cells|out.lif>");
+            process::exit(1);
+        }
+    };
+    let out_format = PatternFormat::from_path(out_path).unwrap_or_else(|| {
+        eprintln!("don't know what format to write {} in (expected a .rle, .cells or .lif file)", out_path);
+        process::exit(1);
+    });
+    let pattern = pattern::load_pattern(in_path).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    if let Err(error) = fs::write(out_path, pattern::write_pattern(&pattern, out_format)) {
+        eprintln!("couldn't write {}: {}", out_path, error);
+        process::exit(1);
+    }
+}
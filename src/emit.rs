@@ -0,0 +1,112 @@
+use std::io::{self, BufWriter, Write};
+use std::process;
+use game_of_life::{pattern, Board, Engine, Point, Rule, ALIVE};
+use game_of_life::pattern::Pattern;
+use crate::{check_board_size, headless_board, headless_engine, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: game_of_life --emit jsonl [--load <pattern file>] [--generations <n>] [--cells coordinates|rle] [--seed <n>] [--width <n>] [--height <n>] [--engine <engine>]";
+const DEFAULT_BOARD_SIZE: u16 = 100; // for random boards, patterns get a board that fits them
+
+
+// how the cells are written in each line
+enum CellsFormat {
+    Coordinates, // "cells": [[x, y], ...] for every live cell
+    Rle, // "rle": the whole board (including dying states and wireworld's states) with "offset" saying where its top left is
+}
+
+
+// just enough escaping for the strings that end up in the output (rle has newlines in it)
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    return escaped;
+}
+
+
+fn generation_line(engine: &dyn Engine, rule: &Rule, generation: u32, cells_format: &CellsFormat) -> String {
+    let mut line = format!("{{\"generation\": {}, \"population\": {}, ", generation, engine.population());
+    let board_cells = engine.cells();
+    match cells_format {
+        CellsFormat::Coordinates => {
+            let mut cells: Vec<&Point> = board_cells.iter().filter(|(_, &state)| state == ALIVE).map(|(cell, _)| cell).collect();
+            cells.sort_by_key(|cell| (cell.y, cell.x)); // the same board always gives the same line
+            let cells: Vec<String> = cells.iter().map(|cell| format!("[{}, {}]", cell.x, cell.y)).collect();
+            line.push_str(&format!("\"cells\": [{}]}}", cells.join(", ")));
+        }
+        CellsFormat::Rle => {
+            let cells: Vec<(Point, u8)> = board_cells.into_iter().collect();
+            let offset = Point{
+                x: cells.iter().map(|(cell, _)| cell.x).min().unwrap_or(0),
+                y: cells.iter().map(|(cell, _)| cell.y).min().unwrap_or(0)
+            };
+            let rle = pattern::to_rle(&Pattern::from_cells(cells, Some(rule.to_string())));
+            line.push_str(&format!("\"offset\": [{}, {}], \"rle\": {}}}", offset.x, offset.y, json_string(&rle)));
+        }
+    }
+    return line;
+}
+
+
+// the --load (or --pattern) pattern if there is one, otherwise a random board (the same one every time with --seed)
+pub fn pattern_or_random_board(args: &mut Args, usage: &str) -> Board {
+    args.pattern_path = args.pattern_path.take().or(args.load_path.clone()); // --load is what the game itself uses
+    if args.pattern_path.is_some() {
+        return headless_board(args, usage);
+    }
+    let width = args.board_width.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    let height = args.board_height.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    check_board_size(args, width, height, SIMULATION_BYTES_PER_CELL);
+    let mut board = Board::new(width, height, args.automaton, args.rule.clone().unwrap_or_else(Rule::conways_life));
+    board.table = args.rule_table.clone();
+    match args.seed {
+        Some(seed) => board.init_from_seed(seed),
+        None => board.init_randomly()
+    }
+    return board;
+}
+
+
+// --emit jsonl: runs without the UI and writes a line of json to stdout for every generation, for piping into other tools
+// starts with the --load pattern, or a random board (the same one every time with --seed), and runs on --engine
+pub fn run_emit(mut args: Args) {
+    if args.emit.as_deref() != Some("jsonl") {
+        eprintln!("unknown output format \"{}\" (expected jsonl)\n{}", args.emit.as_deref().unwrap_or_default(), USAGE);
+        process::exit(1);
+    }
+    let cells_format = match args.cells_format.as_deref() {
+        None | Some("coordinates") => CellsFormat::Coordinates,
+        Some("rle") => CellsFormat::Rle,
+        Some(other) => {
+            eprintln!("unknown cells format \"{}\" (expected coordinates or rle)\n{}", other, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let board = pattern_or_random_board(&mut args, USAGE);
+    let rule = board.rule.clone();
+    let mut engine = headless_engine(&args, board);
+
+    let stdout = io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    for generation in 0..=args.generations {
+        if generation > 0 {
+            engine.step();
+        }
+        if writeln!(output, "{}", generation_line(engine.as_ref(), &rule, generation, &cells_format)).is_err() {
+            return; // whatever was reading the output stopped (like head does), which is fine
+        }
+    }
+    output.flush().ok();
+}
@@ -0,0 +1,259 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use crate::bitboard::BitBoard;
+use crate::board::{hash_state, Ant, Automaton, Board, CellMap, IncrementalBoard, Point, ALIVE, DEAD};
+use crate::hashlife::HashLife;
+use crate::rule::{Neighbourhood, Rule};
+
+
+
+// something that can run a board's generations, so the things that only need to step, read and edit cells don't have to care how it's done
+// Board is the sparse one (and the only one that does every automaton, along with IncrementalBoard), BitBoard is a dense bitboard and HashLife is hashlife
+// the other two only do 2 state rules with the usual 8 neighbours, but all of them give exactly the same results for those
+pub trait Engine {
+    fn step(&mut self); // one generation
+    fn get_cell(&self, cell: &Point) -> u8;
+    fn set_cell(&mut self, cell: &Point, state: u8); // cells off the board are ignored
+    fn bounds(&self) -> (u32, u32); // width and height, everything past the edges is always dead
+    fn population(&self) -> usize;
+
+    // every cell that isn't DEAD (engines that can do better than looking at every cell on the board should)
+    fn cells(&self) -> CellMap<u8> {
+        let (width, height) = self.bounds();
+        let mut cells = CellMap::new();
+        for y in 0..height as i16 {
+            for x in 0..width as i16 {
+                let cell = Point{x, y};
+                let state = self.get_cell(&cell);
+                if state != DEAD {
+                    cells.insert(cell, state);
+                }
+            }
+        }
+        return cells;
+    }
+
+    // Board::state_hash, which is the same for every engine with the same cells
+    fn state_hash(&self) -> u64 {
+        return hash_state(&self.cells(), &[]);
+    }
+
+    // langton's ants, which only the engines that do every automaton have
+    fn ants(&self) -> &[Ant] {
+        return &[];
+    }
+}
+
+
+// which engine to run a board on, for picking one with --engine
+#[derive(Clone, Copy, PartialEq)]
+pub enum EngineKind {
+    Sparse, // Board looking at every live cell and its neighbours every generation
+    Incremental, // IncrementalBoard, which only looks at the cells next to the last generation's changes once the board settles down
+    BitBoard,
+    HashLife,
+}
+
+
+pub const ENGINE_KINDS: [(EngineKind, &str); 4] = [
+    (EngineKind::Sparse, "sparse"),
+    (EngineKind::Incremental, "incremental"),
+    (EngineKind::BitBoard, "bitboard"),
+    (EngineKind::HashLife, "hashlife"),
+];
+
+
+impl EngineKind {
+    pub fn parse(name: &str) -> Result<EngineKind, String> {
+        return ENGINE_KINDS.iter()
+            .find(|(_, kind_name)| kind_name.eq_ignore_ascii_case(name.trim()))
+            .map(|&(kind, _)| kind)
+            .ok_or_else(|| format!("unknown engine \"{}\" (expected sparse, incremental, bitboard or hashlife)", name));
+    }
+
+    pub fn name(&self) -> &'static str {
+        return ENGINE_KINDS.iter().find(|(kind, _)| kind == self).unwrap().1;
+    }
+
+    // the board on this kind of engine, the bitboard and hashlife engines only take life-like boards without walls or sources
+    pub fn engine_for(&self, board: Board) -> Result<Box<dyn Engine>, String> {
+        if matches!(self, EngineKind::BitBoard | EngineKind::HashLife) {
+            if board.automaton != Automaton::Life {
+                return Err(format!("the {} engine only runs life", self.name()));
+            }
+            if !board.walls.is_empty() || !board.sources.is_empty() {
+                return Err(format!("the {} engine can't do walls or sources", self.name()));
+            }
+        }
+        let mut engine: Box<dyn Engine> = match self {
+            EngineKind::Sparse => return Ok(Box::new(board)),
+            EngineKind::Incremental => return Ok(Box::new(IncrementalBoard::new(board))),
+            EngineKind::BitBoard => Box::new(BitBoard::new(board.width, board.height, &board.rule)?),
+            EngineKind::HashLife => Box::new(HashLife::new(board.width, board.height, &board.rule)?)
+        };
+        for cell in board.cells.keys() {
+            engine.set_cell(cell, ALIVE);
+        }
+        return Ok(engine);
+    }
+}
+
+
+impl Engine for IncrementalBoard {
+    fn step(&mut self) {
+        self.update_cells();
+    }
+
+    fn get_cell(&self, cell: &Point) -> u8 {
+        return self.board().cell_state(cell);
+    }
+
+    fn set_cell(&mut self, cell: &Point, state: u8) {
+        self.board_mut().set_cell(cell, state);
+    }
+
+    fn bounds(&self) -> (u32, u32) {
+        return self.board().bounds();
+    }
+
+    fn population(&self) -> usize {
+        return self.board().population();
+    }
+
+    fn cells(&self) -> CellMap<u8> {
+        return self.board().cells.clone();
+    }
+
+    fn state_hash(&self) -> u64 {
+        return self.board().state_hash();
+    }
+
+    fn ants(&self) -> &[Ant] {
+        return &self.board().ants;
+    }
+}
+
+
+impl Engine for Board {
+    fn step(&mut self) {
+        self.update_cells();
+    }
+
+    fn get_cell(&self, cell: &Point) -> u8 {
+        return self.cell_state(cell);
+    }
+
+    fn set_cell(&mut self, cell: &Point, state: u8) {
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.width as i16 || cell.y >= self.height as i16 {
+            return;
+        }
+        match state {
+            DEAD => self.cells.remove(cell),
+            _ => self.cells.insert(cell.clone(), state)
+        };
+    }
+
+    fn bounds(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    fn population(&self) -> usize {
+        return Board::population(self);
+    }
+
+    fn cells(&self) -> CellMap<u8> {
+        return self.cells.clone();
+    }
+
+    fn state_hash(&self) -> u64 {
+        return Board::state_hash(self);
+    }
+
+    fn ants(&self) -> &[Ant] {
+        return &self.ants;
+    }
+}
+
+
+// the rules the bitboard and hashlife engines can run: any life-like B/S rule, but not Generations or other neighbourhoods
+pub fn check_life_like(rule: &Rule, engine: &str) -> Result<(), String> {
+    if rule.num_states != 2 || rule.neighbourhood != Neighbourhood::Moore(1) {
+        return Err(format!("the {} engine only runs 2 state rules with the usual 8 neighbours, not {}", engine, rule));
+    }
+    return Ok(());
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // boards either side of the bitboard's 64 cell words, each with a rule
+    const BOARDS: [(u32, u32, &str); 4] = [(65, 3, "B3/S23"), (130, 67, "B36/S23"), (64, 64, "B3/S23"), (127, 200, "B36/S23")];
+
+    fn seeded_board(width: u32, height: u32, rule: &str, seed: u64) -> Board {
+        let mut board = Board::new(width, height, Automaton::Life, Rule::parse(rule).unwrap());
+        board.init_from_seed(seed);
+        return board;
+    }
+
+    #[test]
+    fn every_engine_matches_the_sparse_one() {
+        for (seed, &(width, height, rule)) in BOARDS.iter().enumerate() {
+            let mut sparse = seeded_board(width, height, rule, seed as u64);
+            let mut engines: Vec<Box<dyn Engine>> = ENGINE_KINDS.iter()
+                .map(|(kind, _)| kind.engine_for(seeded_board(width, height, rule, seed as u64)).unwrap())
+                .collect();
+            for generation in 0..40 {
+                if generation == 20 { // edits part way through, along the edges and across a word boundary
+                    for &(x, y) in [(0, 0), (63, 1), (64, 1), (65, 1), (width as i16 - 1, height as i16 - 1)].iter() {
+                        let cell = Point{x, y};
+                        let state = if sparse.get_cell(&cell) == DEAD { ALIVE } else { DEAD };
+                        sparse.set_cell(&cell, state);
+                        for engine in engines.iter_mut() {
+                            engine.set_cell(&cell, state);
+                        }
+                    }
+                }
+                sparse.update_cells();
+                for (engine, (_, name)) in engines.iter_mut().zip(ENGINE_KINDS.iter()) {
+                    engine.step();
+                    if generation % 5 != 4 {
+                        continue; // hashing every engine every generation makes this slow in debug builds
+                    }
+                    assert_eq!(engine.population(), sparse.population(), "{} on {}x{} {} at generation {}", name, width, height, rule, generation);
+                    assert_eq!(engine.state_hash(), sparse.state_hash(), "{} on {}x{} {} at generation {}", name, width, height, rule, generation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hashlife_matches_the_sparse_engine_after_collecting_garbage() {
+        let mut sparse = seeded_board(130, 67, "B3/S23", 7);
+        let mut hashlife = HashLife::new(sparse.width, sparse.height, &sparse.rule).unwrap();
+        for cell in sparse.cells.keys() {
+            hashlife.set_cell(cell, ALIVE);
+        }
+        for generation in 0..40 {
+            if generation % 10 == 0 {
+                hashlife.collect_garbage();
+            }
+            sparse.update_cells();
+            hashlife.step();
+            assert_eq!(hashlife.state_hash(), sparse.state_hash(), "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn only_life_like_rules_go_on_the_bitboard_and_hashlife_engines() {
+        for &(kind, _) in ENGINE_KINDS.iter() {
+            let generations = seeded_board(20, 20, "B2/S/3", 1);
+            let range_2 = seeded_board(20, 20, "B3/S23/NM2", 1);
+            let accepted = !matches!(kind, EngineKind::BitBoard | EngineKind::HashLife);
+            assert_eq!(kind.engine_for(generations).is_ok(), accepted);
+            assert_eq!(kind.engine_for(range_2).is_ok(), accepted);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fs, process, thread, time};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use game_of_life::{Board, Rule};
+use crate::render::render_frame;
+use crate::{check_board_size, headless_board, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: game_of_life framebuffer [<pattern file>] [--device <framebuffer>] [--fps <n>] [--scale <pixels per cell>]";
+const DEFAULT_DEVICE: &str = "/dev/fb0";
+
+
+// the size and pixel format of a linux framebuffer device
+struct Framebuffer {
+    file: fs::File,
+    width: u32, // in pixels
+    height: u32,
+    bytes_per_pixel: u32,
+    stride: u32, // bytes per row, can be more than width * bytes_per_pixel
+}
+
+
+// reads one of the files the kernel puts in /sys/class/graphics/fbN/ for each framebuffer
+fn read_sysfs(device_name: &str, file_name: &str) -> Result<String, String> {
+    let path = format!("/sys/class/graphics/{}/{}", device_name, file_name);
+    return fs::read_to_string(&path)
+        .map(|contents| contents.trim().to_string())
+        .map_err(|error| format!("couldn't read {}: {}", path, error));
+}
+
+
+fn open_framebuffer(device: &str) -> Result<Framebuffer, String> {
+    let device_name = Path::new(device).file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let size = read_sysfs(&device_name, "virtual_size")?; // "width,height"
+    let (width, height) = size.split_once(',')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .ok_or(format!("couldn't understand the size of {} (\"{}\")", device, size))?;
+    let bits_per_pixel: u32 = read_sysfs(&device_name, "bits_per_pixel")?.parse().map_err(|_| format!("couldn't read the pixel format of {}", device))?;
+    if ![16, 24, 32].contains(&bits_per_pixel) {
+        return Err(format!("{} uses {} bits per pixel, only 16, 24 and 32 are supported", device, bits_per_pixel));
+    }
+    let bytes_per_pixel = bits_per_pixel / 8;
+    let stride = read_sysfs(&device_name, "stride").ok().and_then(|stride| stride.parse().ok()).unwrap_or(width * bytes_per_pixel);
+    let file = fs::OpenOptions::new().write(true).open(device).map_err(|error| format!("couldn't open {}: {}", device, error))?;
+    return Ok(Framebuffer { file, width, height, bytes_per_pixel, stride });
+}
+
+
+impl Framebuffer {
+    // copies an rgb frame to the top left of the screen, converting it to the framebuffer's pixel format
+    // 32 and 24 bit framebuffers are almost always BGR(X) and 16 bit ones are RGB565
+    fn draw(&mut self, frame: &[u8], frame_width: u32, frame_height: u32) -> std::io::Result<()> {
+        let mut screen = vec![0; (self.stride * self.height) as usize];
+        for y in 0..frame_height.min(self.height) {
+            for x in 0..frame_width.min(self.width) {
+                let rgb = &frame[((y * frame_width + x) * 3) as usize..][..3];
+                let index = (y * self.stride + x * self.bytes_per_pixel) as usize;
+                match self.bytes_per_pixel {
+                    2 => {
+                        let pixel = ((rgb[0] as u16 >> 3) << 11) | ((rgb[1] as u16 >> 2) << 5) | (rgb[2] as u16 >> 3);
+                        screen[index..index + 2].copy_from_slice(&pixel.to_le_bytes());
+                    }
+                    _ => screen[index..index + 3].copy_from_slice(&[rgb[2], rgb[1], rgb[0]]),
+                }
+            }
+        }
+        self.file.seek(SeekFrom::Start(0))?;
+        return self.file.write_all(&screen);
+    }
+}
+
+
+// the "framebuffer" subcommand: runs the simulation on a linux framebuffer (like a raspberry pi's screen or an LED matrix with a framebuffer driver) until it's killed
+// the board fills the screen unless a size is given, and starts with the pattern on it or randomly if there's no pattern
+pub fn run_framebuffer(mut args: Args) {
+    let device = args.device.clone().unwrap_or_else(|| DEFAULT_DEVICE.to_string());
+    let mut framebuffer = open_framebuffer(&device).unwrap_or_else(|message| {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    });
+    let scale = args.scale.max(1);
+    args.board_width = args.board_width.or(Some((framebuffer.width / scale).min(u16::MAX as u32) as u16));
+    args.board_height = args.board_height.or(Some((framebuffer.height / scale).min(u16::MAX as u32) as u16));
+
+    check_board_size(&args, args.board_width.unwrap() as u32, args.board_height.unwrap() as u32, SIMULATION_BYTES_PER_CELL + 3 * scale as u64 * scale as u64);
+    let mut board = if args.pattern_path.is_some() {
+        headless_board(&args, USAGE)
+    } else {
+        let mut board = Board::new(
+            args.board_width.unwrap() as u32,
+            args.board_height.unwrap() as u32,
+            args.automaton,
+            args.rule.clone().unwrap_or_else(Rule::conways_life)
+        );
+        board.table = args.rule_table.clone();
+        board.init_randomly();
+        board
+    };
+
+    let frame_delay = time::Duration::from_secs(1) / args.fps.max(1);
+    let mut next_frame_time = time::Instant::now();
+    loop {
+        let frame = render_frame(&board, scale);
+        if let Err(error) = framebuffer.draw(&frame, board.width * scale, board.height * scale) {
+            eprintln!("couldn't write to {}: {}", device, error);
+            process::exit(1);
+        }
+        board.update_cells();
+
+        next_frame_time += frame_delay;
+        let now = time::Instant::now();
+        if next_frame_time > now {
+            thread::sleep(next_frame_time - now);
+        } else {
+            next_frame_time = now;
+        }
+    }
+}
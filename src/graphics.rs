@@ -0,0 +1,175 @@
+use std::{env, io};
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use termion::raw::IntoRawMode;
+use crate::{png, sixel};
+
+
+
+pub const PIXELS_PER_CELL: u32 = 3; // how big cells are drawn on the screen, the image itself is one pixel per cell and the terminal scales it up (except for sixels, which get scaled before they're sent)
+const KITTY_CHUNK_SIZE: usize = 4096; // the most base64 the kitty protocol allows in one escape code
+const DEVICE_ATTRIBUTES_TIMEOUT_MS: i32 = 200; // how long to wait for the terminal to say whether it supports sixels
+const BASE64_CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+
+// the ways that terminals can be sent actual images instead of characters
+#[derive(Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Kitty, // kitty's graphics protocol (also supported by wezterm, ghostty and konsole)
+    Iterm2, // iTerm2's inline images
+    Sixel, // DEC's old raster graphics, supported by xterm (with -ti vt340), mlterm, foot, contour and others
+}
+
+
+// a terminal that can draw images, and how big its characters are so the board can be lined up with them
+#[derive(Clone, Copy)]
+pub struct Graphics {
+    pub protocol: Protocol,
+    pub char_width: u32, // in pixels
+    pub char_height: u32,
+}
+
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let value = (group[0] as u32) << 16 | (*group.get(1).unwrap_or(&0) as u32) << 8 | *group.get(2).unwrap_or(&0) as u32;
+        for index in 0..4 {
+            if index <= group.len() {
+                encoded.push(BASE64_CHARS[(value >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    return encoded;
+}
+
+
+// asks the terminal for its primary device attributes, a 4 in the reply means it can draw sixels
+// there's no environment variable to go by for sixels, and the reply has to be read before the key reader starts (or it would turn up as key presses)
+// the reply is read from the terminal itself rather than stdin, which might have had a pattern piped into it
+fn terminal_supports_sixel() -> bool {
+    if !termion::is_tty(&io::stdout()) {
+        return false;
+    }
+    let tty = match termion::get_tty() {
+        Ok(tty) => tty,
+        Err(_) => return false
+    };
+    let raw_terminal = match io::stdout().into_raw_mode() { // so the reply doesn't wait for enter or get echoed
+        Ok(raw_terminal) => raw_terminal,
+        Err(_) => return false
+    };
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[c").ok();
+    stdout.flush().ok();
+    let mut reply = Vec::new();
+    let mut input = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+    while !reply.ends_with(b"c") && unsafe { libc::poll(&mut input, 1, DEVICE_ATTRIBUTES_TIMEOUT_MS) } > 0 {
+        let mut byte = 0u8;
+        if unsafe { libc::read(tty.as_raw_fd(), &mut byte as *mut u8 as *mut libc::c_void, 1) } != 1 {
+            break;
+        }
+        reply.push(byte);
+    }
+    drop(raw_terminal);
+    let reply = String::from_utf8_lossy(&reply); // something like "\x1b[?62;4;22c"
+    return reply.trim_start_matches("\x1b[?").trim_end_matches('c').split(';').any(|attribute| attribute == "4");
+}
+
+
+impl Graphics {
+    // goes by the environment variables that kitty-like terminals and iTerm2 set, and only asks the terminal about sixels when ask_terminal is true
+    // also needs the terminal to say how many pixels it is so the image can be sized
+    pub fn detect(ask_terminal: bool) -> Option<Graphics> {
+        let term = env::var("TERM").unwrap_or_default();
+        let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+        let protocol = if env::var("KITTY_WINDOW_ID").is_ok() || term.contains("kitty") || term_program == "WezTerm" || term_program == "ghostty" {
+            Protocol::Kitty
+        } else if term_program == "iTerm.app" {
+            Protocol::Iterm2
+        } else if ask_terminal && terminal_supports_sixel() {
+            Protocol::Sixel
+        } else {
+            return None;
+        };
+        let (columns, rows) = termion::terminal_size().ok()?;
+        let (pixel_width, pixel_height) = termion::terminal_size_pixels().ok()?;
+        if columns == 0 || rows == 0 || pixel_width == 0 || pixel_height == 0 { // some terminals don't fill in the pixel size
+            return None;
+        }
+        return Some(Graphics {
+            protocol,
+            char_width: (pixel_width / columns) as u32,
+            char_height: (pixel_height / rows) as u32,
+        });
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self.protocol {
+            Protocol::Kitty => "kitty graphics",
+            Protocol::Iterm2 => "iTerm2 images",
+            Protocol::Sixel => "sixels",
+        };
+    }
+
+    // how many characters wide and tall a board this big is on the screen
+    pub fn screen_size(&self, board_width: u32, board_height: u32) -> (u16, u16) {
+        return (
+            (board_width * PIXELS_PER_CELL).div_ceil(self.char_width) as u16,
+            (board_height * PIXELS_PER_CELL).div_ceil(self.char_height) as u16
+        );
+    }
+
+    // how many cells fit in this many characters
+    pub fn cells_in(&self, columns: u16, rows: u16) -> (u16, u16) {
+        return (
+            (columns as u32 * self.char_width / PIXELS_PER_CELL).min(u16::MAX as u32) as u16,
+            (rows as u32 * self.char_height / PIXELS_PER_CELL).min(u16::MAX as u32) as u16
+        );
+    }
+
+    // the escape codes that draw an rgba image at the cursor, stretched over columns x rows characters (or PIXELS_PER_CELL times bigger for sixels)
+    // kitty keeps images around by their id, so drawing one replaces the last image with the same id (the others just get drawn over)
+    pub fn image(&self, rgba: &[u8], width: u32, height: u32, columns: u16, rows: u16, id: u32) -> String {
+        return match self.protocol {
+            Protocol::Kitty => {
+                // the old image gets deleted first, then the new one goes under the text (z=-1) so the cursor and prompts still show up on top of it
+                // q=2 stops the terminal replying, since the replies would turn up as key presses
+                let mut escape = format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", id);
+                let encoded = base64(rgba);
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                for (index, chunk) in chunks.iter().enumerate() {
+                    let more = (index + 1 < chunks.len()) as u8;
+                    let chunk = std::str::from_utf8(chunk).unwrap(); // base64 is all ascii
+                    if index == 0 {
+                        escape.push_str(&format!(
+                            "\x1b_Ga=T,f=32,s={},v={},c={},r={},i={},z=-1,C=1,q=2,m={};{}\x1b\\",
+                            width, height, columns, rows, id, more, chunk
+                        ));
+                    } else {
+                        escape.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                    }
+                }
+                escape
+            }
+            Protocol::Iterm2 => {
+                let image = png::encode(rgba, width, height, png::ColourType::Rgba);
+                format!(
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+                    image.len(), columns, rows, base64(&image)
+                )
+            }
+            Protocol::Sixel => sixel::encode(rgba, width, height, PIXELS_PER_CELL),
+        };
+    }
+
+    // gets rid of every image, like when leaving pixel mode (iTerm2 images and sixels are just overwritten by whatever gets printed over them)
+    pub fn clear(&self) -> String {
+        return match self.protocol {
+            Protocol::Kitty => "\x1b_Ga=d,d=A,q=2\x1b\\".to_string(),
+            Protocol::Iterm2 | Protocol::Sixel => String::new(),
+        };
+    }
+}
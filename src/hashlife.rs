@@ -0,0 +1,294 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::board::{CellMap, Point, ALIVE, DEAD};
+use crate::engine::{check_life_like, Engine};
+use crate::rule::Rule;
+
+
+
+type NodeId = u32; // where a node is in HashLife.nodes
+// finds nodes by their children, HashMap without std like CellMap
+#[cfg(feature = "std")]
+type NodeMap = std::collections::HashMap<[NodeId; 4], NodeId>;
+#[cfg(not(feature = "std"))]
+type NodeMap = alloc::collections::BTreeMap<[NodeId; 4], NodeId>;
+
+const EMPTY_CELL: NodeId = 0; // the two level 0 nodes are single cells
+const LIVE_CELL: NodeId = 1;
+const MAX_NODES: usize = 1 << 20; // past this many nodes the ones that aren't part of the board any more get thrown away
+
+
+// a square of 2^level cells made of 4 squares half its size, every different square is only stored once
+// (so a board that's mostly empty or repeats itself takes very little memory, and a square's next generation only has to be worked out once)
+#[derive(Clone)]
+struct Node {
+    level: u8,
+    children: [NodeId; 4], // top left, top right, bottom left, bottom right (unused for single cells)
+    population: usize,
+    next: Option<NodeId>, // the middle half of this square one generation later, once it's been worked out
+}
+
+
+// a hashlife engine, which stores the board as a quadtree of shared squares and remembers what every square turns into
+// each generation is still stepped one at a time (so that it's clipped to the board like the other engines), it's quickest on boards that repeat a lot
+pub struct HashLife {
+    width: u32,
+    height: u32,
+    nodes: Vec<Node>,
+    node_ids: NodeMap, // finds the node with those children, so the same square is never stored twice
+    empty: Vec<NodeId>, // the empty square of each level
+    root: NodeId, // centred on 0, 0 and big enough to hold the whole board with room to spare
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+
+impl HashLife {
+    pub fn new(width: u32, height: u32, rule: &Rule) -> Result<HashLife, String> {
+        check_life_like(rule, "hashlife")?;
+        let (mut birth, mut survival) = ([false; 9], [false; 9]);
+        birth.copy_from_slice(&rule.birth);
+        survival.copy_from_slice(&rule.survival);
+        birth[0] = false; // like Board, which only ever looks at cells with live neighbours
+        let cell = |population| Node { level: 0, children: [0; 4], population, next: None };
+        let mut hashlife = HashLife {
+            width,
+            height,
+            nodes: vec![cell(0), cell(1)],
+            node_ids: NodeMap::new(),
+            empty: vec![EMPTY_CELL],
+            root: EMPTY_CELL,
+            birth,
+            survival,
+        };
+        let mut level = 3; // the smallest square that can be stepped
+        while 1_i64 << (level - 1) <= width.max(height) as i64 {
+            level += 1;
+        }
+        hashlife.root = hashlife.empty_node(level);
+        return Ok(hashlife);
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        return &self.nodes[id as usize];
+    }
+
+    // the node with these children, made if it doesn't exist yet
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.node_ids.get(&children) {
+            return id;
+        }
+        let level = self.node(children[0]).level + 1;
+        let population = children.iter().map(|&child| self.node(child).population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { level, children, population, next: None });
+        self.node_ids.insert(children, id);
+        return id;
+    }
+
+    fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let smaller = *self.empty.last().unwrap();
+            let empty = self.join([smaller; 4]);
+            self.empty.push(empty);
+        }
+        return self.empty[level as usize];
+    }
+
+    // the square half the size in the middle of this one
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [top_left, top_right, bottom_left, bottom_right] = self.node(id).children.map(|child| self.node(child).children);
+        return self.join([top_left[3], top_right[2], bottom_left[1], bottom_right[0]]);
+    }
+
+    // the same square in the middle of one twice its size with nothing else in it
+    fn expand(&mut self, id: NodeId) -> NodeId {
+        let [top_left, top_right, bottom_left, bottom_right] = self.node(id).children;
+        let empty = self.empty_node(self.node(id).level - 1);
+        let top_left = self.join([empty, empty, empty, top_left]);
+        let top_right = self.join([empty, empty, top_right, empty]);
+        let bottom_left = self.join([empty, bottom_left, empty, empty]);
+        let bottom_right = self.join([bottom_right, empty, empty, empty]);
+        return self.join([top_left, top_right, bottom_left, bottom_right]);
+    }
+
+    // the middle half of a square (level 2 or more) one generation later
+    fn next(&mut self, id: NodeId) -> NodeId {
+        if let Some(next) = self.node(id).next {
+            return next;
+        }
+        let node = self.node(id).clone();
+        let next = if node.population == 0 {
+            self.empty_node(node.level - 1)
+        } else if node.level == 2 {
+            self.next_of_4x4(id)
+        } else {
+            // the 9 squares half the size that overlap each other by half, from the top left
+            let [top_left, top_right, bottom_left, bottom_right] = node.children;
+            let [a, b, c, d] = [top_left, top_right, bottom_left, bottom_right].map(|child| self.node(child).children);
+            let top = self.join([a[1], b[0], a[3], b[2]]);
+            let left = self.join([a[2], a[3], c[0], c[1]]);
+            let middle = self.join([a[3], b[2], c[1], d[0]]);
+            let right = self.join([b[2], b[3], d[0], d[1]]);
+            let bottom = self.join([c[1], d[0], c[3], d[2]]);
+            let squares = [top_left, top, top_right, left, middle, right, bottom_left, bottom, bottom_right];
+            let centres = squares.map(|square| self.centre(square));
+            // each quarter of the answer is the next generation of the middle of the 4 squares around it
+            let mut quarters = [EMPTY_CELL; 4];
+            for (quarter, &(x, y)) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter().enumerate() {
+                let around = self.join([centres[y * 3 + x], centres[y * 3 + x + 1], centres[y * 3 + x + 3], centres[y * 3 + x + 4]]);
+                quarters[quarter] = self.next(around);
+            }
+            self.join(quarters)
+        };
+        self.nodes[id as usize].next = Some(next);
+        return next;
+    }
+
+    // next for the smallest squares, by counting neighbours
+    fn next_of_4x4(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (quarter, &child) in self.node(id).children.iter().enumerate() {
+            for (cell, &grandchild) in self.node(child).children.iter().enumerate() {
+                cells[quarter / 2 * 2 + cell / 2][quarter % 2 * 2 + cell % 2] = grandchild == LIVE_CELL;
+            }
+        }
+        let mut next = [EMPTY_CELL; 4];
+        for (index, &(x, y)) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter().enumerate() {
+            let neighbours = (y - 1..=y + 1)
+                .flat_map(|neighbour_y| (x - 1..=x + 1).map(move |neighbour_x| (neighbour_x, neighbour_y)))
+                .filter(|&(neighbour_x, neighbour_y)| (neighbour_x, neighbour_y) != (x, y) && cells[neighbour_y][neighbour_x])
+                .count();
+            let alive = match cells[y][x] {
+                true => self.survival[neighbours],
+                false => self.birth[neighbours]
+            };
+            next[index] = if alive { LIVE_CELL } else { EMPTY_CELL };
+        }
+        return self.join(next);
+    }
+
+    // the same square with every cell that's off the board taken out, left and top are where the square's top left corner is
+    fn clip(&mut self, id: NodeId, left: i64, top: i64) -> NodeId {
+        let node = self.node(id).clone();
+        let size = 1_i64 << node.level;
+        if node.population == 0 || (left >= 0 && top >= 0 && left + size <= self.width as i64 && top + size <= self.height as i64) {
+            return id;
+        }
+        if left + size <= 0 || top + size <= 0 || left >= self.width as i64 || top >= self.height as i64 {
+            return self.empty_node(node.level);
+        }
+        let half = size / 2;
+        let mut children = node.children;
+        for (index, child) in children.iter_mut().enumerate() {
+            *child = self.clip(*child, left + (index % 2) as i64 * half, top + (index / 2) as i64 * half);
+        }
+        return self.join(children);
+    }
+
+    // the same square with one cell changed
+    fn set(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.node(id).clone();
+        if node.level == 0 {
+            return if alive { LIVE_CELL } else { EMPTY_CELL };
+        }
+        let half = 1_i64 << (node.level - 1);
+        let index = (y >= half) as usize * 2 + (x >= half) as usize;
+        let mut children = node.children;
+        children[index] = self.set(children[index], x % half, y % half, alive);
+        return self.join(children);
+    }
+
+    // where the root's top left corner is
+    fn root_corner(&self) -> i64 {
+        return -(1_i64 << (self.node(self.root).level - 1));
+    }
+
+    // starts the nodes over with only the ones that are part of the board, once there are too many of them
+    pub(crate) fn collect_garbage(&mut self) {
+        let cells = vec![self.nodes[EMPTY_CELL as usize].clone(), self.nodes[LIVE_CELL as usize].clone()];
+        let old_nodes = core::mem::replace(&mut self.nodes, cells);
+        self.node_ids = NodeMap::new();
+        self.empty = vec![EMPTY_CELL];
+        let mut new_ids: Vec<Option<NodeId>> = vec![None; old_nodes.len()];
+        new_ids[EMPTY_CELL as usize] = Some(EMPTY_CELL);
+        new_ids[LIVE_CELL as usize] = Some(LIVE_CELL);
+        self.root = self.copy_node(&old_nodes, &mut new_ids, self.root);
+    }
+
+    fn copy_node(&mut self, old_nodes: &[Node], new_ids: &mut [Option<NodeId>], id: NodeId) -> NodeId {
+        if let Some(new_id) = new_ids[id as usize] {
+            return new_id;
+        }
+        let children = old_nodes[id as usize].children.map(|child| self.copy_node(old_nodes, new_ids, child));
+        let new_id = self.join(children);
+        new_ids[id as usize] = Some(new_id);
+        return new_id;
+    }
+}
+
+
+impl Engine for HashLife {
+    fn step(&mut self) {
+        let expanded = self.expand(self.root); // so the next generation of its middle half is the whole board again
+        let next = self.next(expanded);
+        let corner = self.root_corner();
+        self.root = self.clip(next, corner, corner);
+        if self.nodes.len() > MAX_NODES {
+            self.collect_garbage();
+        }
+    }
+
+    fn get_cell(&self, cell: &Point) -> u8 {
+        if cell.x < 0 || cell.y < 0 || cell.x as u32 >= self.width || cell.y as u32 >= self.height {
+            return DEAD;
+        }
+        let corner = self.root_corner();
+        let (mut x, mut y, mut id) = (cell.x as i64 - corner, cell.y as i64 - corner, self.root);
+        while self.node(id).level > 0 {
+            let half = 1_i64 << (self.node(id).level - 1);
+            id = self.node(id).children[(y >= half) as usize * 2 + (x >= half) as usize];
+            x %= half;
+            y %= half;
+        }
+        return if id == LIVE_CELL { ALIVE } else { DEAD };
+    }
+
+    fn set_cell(&mut self, cell: &Point, state: u8) {
+        if cell.x < 0 || cell.y < 0 || cell.x as u32 >= self.width || cell.y as u32 >= self.height {
+            return;
+        }
+        let corner = self.root_corner();
+        self.root = self.set(self.root, cell.x as i64 - corner, cell.y as i64 - corner, state != DEAD);
+    }
+
+    fn bounds(&self) -> (u32, u32) {
+        return (self.width, self.height);
+    }
+
+    fn population(&self) -> usize {
+        return self.node(self.root).population;
+    }
+
+    fn cells(&self) -> CellMap<u8> {
+        let mut cells = CellMap::new();
+        let corner = self.root_corner();
+        let mut squares = vec![(self.root, corner, corner)]; // left to look at, with their top left corners
+        while let Some((id, left, top)) = squares.pop() {
+            let node = self.node(id);
+            if node.population == 0 {
+                continue;
+            }
+            if node.level == 0 {
+                cells.insert(Point{x: left as i16, y: top as i16}, ALIVE);
+                continue;
+            }
+            let half = 1_i64 << (node.level - 1);
+            for (index, &child) in node.children.iter().enumerate() {
+                squares.push((child, left + (index % 2) as i64 * half, top + (index / 2) as i64 * half));
+            }
+        }
+        return cells;
+    }
+}
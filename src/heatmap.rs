@@ -0,0 +1,54 @@
+use std::collections::VecDeque;
+use game_of_life::{Board, Point, ALIVE};
+use game_of_life::board::CellMap;
+
+
+
+const WINDOW: usize = 100; // generations that the heatmap covers
+
+
+
+// how often each cell has been alive over the last few generations, to show where the activity is
+pub struct Heatmap {
+    history: VecDeque<Vec<Point>>, // the alive cells in each generation of the window, oldest first
+    counts: CellMap<u32>, // how many generations in the window each cell was alive in (cells that weren't alive in any of them aren't stored)
+}
+
+
+impl Heatmap {
+    pub fn new() -> Heatmap {
+        return Heatmap { history: VecDeque::new(), counts: CellMap::new() };
+    }
+
+    // adds the board's current generation to the window (and drops the oldest one if the window is full), should be called after every generation
+    pub fn record(&mut self, board: &Board) {
+        let alive: Vec<Point> = board.cells.iter()
+            .filter(|(_, &state)| state == ALIVE)
+            .map(|(cell, _)| cell.clone())
+            .collect();
+        for cell in &alive {
+            *self.counts.entry(cell.clone()).or_insert(0) += 1;
+        }
+        self.history.push_back(alive);
+        if self.history.len() > WINDOW {
+            for cell in self.history.pop_front().unwrap() {
+                let count = self.counts.get_mut(&cell).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&cell);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.counts.clear();
+    }
+
+    // every cell that's been alive in the window, with the fraction of the window it was alive for
+    pub fn heat(&self) -> impl Iterator<Item = (&Point, f64)> {
+        let generations = self.history.len() as f64;
+        return self.counts.iter().map(move |(cell, &count)| (cell, count as f64 / generations));
+    }
+}
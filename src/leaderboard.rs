@@ -0,0 +1,106 @@
+use std::{env, fs};
+use std::io::{self, Write};
+use std::path::Path;
+
+
+
+const LEADERBOARD_FILE_NAME: &str = ".game_of_life_leaderboard"; // in the home directory
+pub const LEADERBOARD_LENGTH: usize = 20;
+
+
+// a soup that took a long time to settle down (a methuselah)
+#[derive(Clone)]
+pub struct Methuselah {
+    pub lifespan: u32, // generations until its population started repeating
+    pub peak_population: usize,
+    pub seed: u64,
+    pub size: (u16, u16),
+    pub rule: String,
+}
+
+
+// the longest lived soups the search subcommand has ever found, longest first, kept in a file in the home directory
+pub struct Leaderboard {
+    pub entries: Vec<Methuselah>,
+}
+
+
+fn leaderboard_path() -> Option<String> {
+    let home = env::var("HOME").ok()?;
+    return Some(Path::new(&home).join(LEADERBOARD_FILE_NAME).to_string_lossy().to_string());
+}
+
+
+// a line of the file: lifespan, peak population, seed, size and rule
+fn parse_entry(line: &str) -> Option<Methuselah> {
+    let mut parts = line.split_whitespace();
+    let lifespan = parts.next()?.parse().ok()?;
+    let peak_population = parts.next()?.parse().ok()?;
+    let seed = parts.next()?.parse().ok()?;
+    let (width, height) = parts.next()?.split_once('x')?;
+    let size = (width.parse().ok()?, height.parse().ok()?);
+    let rule = parts.next()?.to_string();
+    return Some(Methuselah { lifespan, peak_population, seed, size, rule });
+}
+
+
+impl Leaderboard {
+    // starts empty if there isn't a file yet (lines that can't be read are skipped)
+    pub fn load() -> Leaderboard {
+        let contents = leaderboard_path().and_then(|path| fs::read_to_string(path).ok()).unwrap_or_default();
+        return Leaderboard { entries: contents.lines().filter_map(parse_entry).collect() };
+    }
+
+    // puts a soup in its place on the leaderboard if it lasted long enough to get on it, returns its place (counting from 1)
+    pub fn add(&mut self, methuselah: Methuselah) -> Option<usize> {
+        let already_on = self.entries.iter().any(|entry| entry.seed == methuselah.seed && entry.size == methuselah.size && entry.rule == methuselah.rule);
+        if already_on {
+            return None; // the same search run again
+        }
+        let place = self.entries.iter().position(|entry| methuselah.lifespan > entry.lifespan).unwrap_or(self.entries.len());
+        if place >= LEADERBOARD_LENGTH {
+            return None;
+        }
+        self.entries.insert(place, methuselah);
+        self.entries.truncate(LEADERBOARD_LENGTH);
+        return Some(place + 1);
+    }
+
+    pub fn save(&self) -> Result<String, String> {
+        let path = leaderboard_path().ok_or("couldn't save the leaderboard, HOME isn't set")?;
+        let lines: Vec<String> = self.entries.iter()
+            .map(|entry| format!("{} {} {} {}x{} {}", entry.lifespan, entry.peak_population, entry.seed, entry.size.0, entry.size.1, entry.rule))
+            .collect();
+        fs::write(&path, lines.join("\n") + "\n").map_err(|error| format!("couldn't save the leaderboard to {}: {}", path, error))?;
+        return Ok(path);
+    }
+
+    // a table of the entries, with the command that runs each one again
+    pub fn to_lines(entries: &[Methuselah]) -> Vec<String> {
+        let mut lines = vec![format!("{:>5} {:>10} {:>8}  run it again with", "place", "lifespan", "peak")];
+        for (index, entry) in entries.iter().enumerate() {
+            lines.push(format!(
+                "{:>5} {:>10} {:>8}  search --soups 1 --seed {} --size {}x{} --rule {}",
+                index + 1, entry.lifespan, entry.peak_population, entry.seed, entry.size.0, entry.size.1, entry.rule
+            ));
+        }
+        return lines;
+    }
+}
+
+
+// the "leaderboard" subcommand: prints the longest lived soups that have been found so far
+pub fn print_leaderboard() {
+    let leaderboard = Leaderboard::load();
+    if leaderboard.entries.is_empty() {
+        println!("No soups on the leaderboard yet, run the search subcommand to find some");
+        return;
+    }
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    for line in Leaderboard::to_lines(&leaderboard.entries) {
+        if writeln!(output, "{}", line).is_err() {
+            return; // whatever was reading the output stopped, which is fine
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// the simulation side of the game (boards, rules and pattern files) with nothing that needs a terminal
+// the game_of_life binary is built on top of this with the "tui" feature, but the engine on its own can be used with
+//     default-features = false
+// which leaves it with no dependencies and no std, just alloc (so it can run on embedded things like LED matrices)
+// the "std" feature adds back manifests and loading pattern files, and "random" adds Board::init_randomly (and init_randomly_with)
+
+#![allow(clippy::needless_return)] // I like explicit returns
+#![cfg_attr(not(feature = "std"), no_std)] // only needs alloc without std, manifests and loading files are left out
+
+extern crate alloc;
+
+pub mod bitboard;
+pub mod board;
+pub mod engine;
+#[cfg(feature = "std")]
+pub mod manifest;
+pub mod hashlife;
+pub mod margolus;
+pub mod objects;
+pub mod pattern;
+pub mod rule;
+pub mod rule_table;
+pub mod workload;
+
+pub use bitboard::BitBoard;
+pub use board::{Ant, Automaton, Board, GenerationIter, IncrementalBoard, Point, StepStats, ALIVE, CONDUCTOR, DEAD, ELECTRON_HEAD, ELECTRON_TAIL, PLAYER_ONE, PLAYER_TWO};
+pub use engine::{Engine, EngineKind};
+pub use hashlife::HashLife;
+pub use margolus::BlockRule;
+pub use rule::{Neighbourhood, Rule};
+pub use rule_table::RuleTable;
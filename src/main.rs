@@ -7,429 +7,3701 @@ Might be more efficient to only print the cell diff every frame instead of the w
     depends on how efficient termion::Gotos are
 */
 
-use std::{iter, thread, time, process, env};
-use std::collections::{HashSet, HashMap};
-use rand::Rng;
+#![allow(clippy::needless_return)] // I like explicit returns
+
+mod bench;
+mod bundled;
+mod census;
+mod command;
+mod compare;
+mod convert;
+mod emit;
+#[cfg(feature = "framebuffer")]
+mod framebuffer;
+mod graphics;
+mod heatmap;
+mod leaderboard;
+mod petri;
+mod png;
+mod presets;
+mod puzzle;
+mod render;
+mod renderer;
+mod screensaver;
+mod search;
+mod replay;
+mod sixel;
+mod stats;
+mod terminal;
+mod theme;
+mod verify;
+
+use std::{iter, mem, panic, thread, time, process, env, fs};
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use game_of_life::{manifest, objects, pattern, Ant, Automaton, Board, BlockRule, Engine, EngineKind, IncrementalBoard, Neighbourhood, Point, Rule, RuleTable, ALIVE, CONDUCTOR, ELECTRON_HEAD, ELECTRON_TAIL, PLAYER_ONE, PLAYER_TWO};
+use bundled::{BundledPattern, BUNDLED_PATTERNS, DEMO_PATTERNS};
+use command::{Command, History};
+use graphics::Graphics;
+use heatmap::Heatmap;
+use petri::PetriDish;
+use puzzle::Puzzle;
+use renderer::Renderer;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use replay::{Event, Recorder, Replay};
+use theme::{heat_colour, ColourDepth, Rgb, Theme, THEMES};
+use game_of_life::board::{CellMap, CellSet};
+use game_of_life::manifest::Manifest;
+use game_of_life::pattern::{Pattern, PatternFormat};
 use termion::{
     self, 
-    input::TermRead, // for Stdin::keys method
-    raw::IntoRawMode, // for Stdout::into_raw_mode method
     event::Key
 };
 use std::io::{
     self,
+    Read, // for Stdin::read_to_string
     Write, // for RawTerminal::write_fmt (RawTerminal's impl for Write trait) (called by write!)
 };
 
 
 
-const INSTRUCTIONS: &str = "\
-    ║ Spacebar:   Play/Pause       ║\r\n\
-    ║ Arrow keys: Move cursor      ║\r\n\
-    ║ C:          Clear            ║\r\n\
-    ║ A:          Create/Kill cell ║\r\n\
-    ║ F:          Advance 1 frame  ║\r\n\
-    ║ R:          Randomize        ║\r\n\
-    ║ H:          Show/Hide cursor ║\r\n\
-    ║ U:          Toggle unicode   ║\r\n\
-    ║ -/+:        Adjust framerate ║\r\n\
-    ║ Q:          Quit             ║\r\n\
-    ╚══════════════════════════════╝\r\n\
-                                    \
-"; // extra empty line at end needed to print frame delay
-const INSTRUCTIONS_WIDTH: u16 = 32;
-const INSTRUCTIONS_HEIGHT: u16 = 12;
+// one line of the help, each key is only listed in the modes where it does what the line says
+struct KeyHelp {
+    keys: &'static str,
+    description: &'static str,
+    shown: fn(&Board, &GameState) -> bool,
+}
+
+const KEY_HELP: [KeyHelp; 56] = [
+    KeyHelp { keys: "Spacebar", description: "Play/Pause", shown: |_, _| true },
+    KeyHelp { keys: "Arrow keys", description: "Move cursor", shown: |_, game_state| !game_state.pasting && !game_state.vim_keys },
+    KeyHelp { keys: "hjkl/gg/G", description: "Move/jump corner", shown: |_, game_state| !game_state.pasting && game_state.vim_keys },
+    KeyHelp { keys: "Arrow keys", description: "Move preview", shown: |_, game_state| game_state.pasting },
+    KeyHelp { keys: "C", description: "Clear", shown: |_, _| true },
+    KeyHelp { keys: "A", description: "Create/Kill cell", shown: |board, _| num_paintable_states(board) == 1 },
+    KeyHelp { keys: "A", description: "Draw/erase state", shown: |board, _| num_paintable_states(board) > 1 },
+    KeyHelp { keys: "F", description: "Advance 1 frame", shown: |_, game_state| game_state.paused },
+    KeyHelp { keys: "R", description: "Randomize", shown: |_, game_state| !game_state.pasting && game_state.petri_dish.is_none() },
+    KeyHelp { keys: "R", description: "Reseed dishes", shown: |_, game_state| !game_state.pasting && game_state.petri_dish.is_some() },
+    KeyHelp { keys: "R", description: "Rotate preview", shown: |_, game_state| game_state.pasting },
+    KeyHelp { keys: "H", description: "Show/Hide cursor", shown: |_, game_state| !game_state.pasting },
+    KeyHelp { keys: "H/V", description: "Flip preview", shown: |_, game_state| game_state.pasting },
+    KeyHelp { keys: "U", description: "Toggle unicode", shown: |_, _| true },
+    KeyHelp { keys: "O", description: "Colour theme", shown: |_, _| true },
+    KeyHelp { keys: "E", description: "Edit rule", shown: |board, _| uses_rule(board.automaton) },
+    KeyHelp { keys: "[/]", description: "Named rules", shown: |board, _| uses_rule(board.automaton) },
+    KeyHelp { keys: "E", description: "Electron head", shown: |board, _| board.automaton == Automaton::Wireworld },
+    KeyHelp { keys: "T", description: "Electron tail", shown: |board, _| board.automaton == Automaton::Wireworld },
+    KeyHelp { keys: "T", description: "Toggle trails", shown: |board, _| board.automaton != Automaton::Wireworld },
+    KeyHelp { keys: "G", description: "Toggle heatmap", shown: |_, game_state| !game_state.vim_keys },
+    KeyHelp { keys: "Ctrl+G", description: "Toggle heatmap", shown: |_, game_state| game_state.vim_keys },
+    KeyHelp { keys: "N", description: "Place/turn ant", shown: |board, _| board.automaton == Automaton::LangtonsAnt },
+    KeyHelp { keys: "{/}", description: "Pick paint state", shown: |board, _| num_paintable_states(board) > 1 },
+    KeyHelp { keys: "S", description: "Skip ahead", shown: |_, game_state| game_state.skip.is_none() },
+    KeyHelp { keys: "Esc", description: "Stop skipping", shown: |_, game_state| game_state.skip.is_some() && game_state.shape_start.is_none() && !game_state.pasting },
+    KeyHelp { keys: "M", description: "Symmetry mode", shown: |_, _| true },
+    KeyHelp { keys: "B", description: "Brush size/shape", shown: |_, _| true },
+    KeyHelp { keys: "X", description: "Erase brush", shown: |_, _| true },
+    KeyHelp { keys: "K", description: "Fixed alive/dead", shown: |_, _| true },
+    KeyHelp { keys: "D", description: "Line/rectangle", shown: |_, _| true },
+    KeyHelp { keys: "V", description: "Mark/draw shape", shown: |_, game_state| !game_state.pasting },
+    KeyHelp { keys: "Esc", description: "Cancel shape", shown: |_, game_state| game_state.shape_start.is_some() && !game_state.pasting },
+    KeyHelp { keys: "P", description: "Petri dish mode", shown: |board, _| board.automaton == Automaton::Life },
+    KeyHelp { keys: "Y", description: "Copy marked area", shown: |_, game_state| !game_state.pasting },
+    KeyHelp { keys: "I", description: "Paste", shown: |_, game_state| !game_state.pasting },
+    KeyHelp { keys: "L", description: "Pattern library", shown: |board, game_state| board.automaton == Automaton::Life && !game_state.pasting },
+    KeyHelp { keys: "L", description: "Next pattern", shown: |_, game_state| game_state.pasting && game_state.library_index.is_some() },
+    KeyHelp { keys: "Enter", description: "Paste", shown: |_, game_state| game_state.pasting },
+    KeyHelp { keys: "Esc", description: "Cancel paste", shown: |_, game_state| game_state.pasting },
+    KeyHelp { keys: "W", description: "Save loaded file", shown: |_, game_state| game_state.loaded_file.is_some() },
+    KeyHelp { keys: "Z", description: "Save screenshot", shown: |_, _| true },
+    KeyHelp { keys: "J", description: "Move/hide panel", shown: |_, _| true },
+    KeyHelp { keys: "Ctrl+F", description: "Follow the action", shown: |_, _| true },
+    KeyHelp { keys: "Ctrl+N", description: "Neighbour counts", shown: |board, _| counts_neighbours(board.automaton) },
+    KeyHelp { keys: "Ctrl+P", description: "Preview next gen", shown: |_, _| true },
+    KeyHelp { keys: "Ctrl+T", description: "New tab", shown: |_, game_state| tabs_allowed(game_state) },
+    KeyHelp { keys: "(Shift+)Tab", description: "Switch tab", shown: |_, game_state| !game_state.tabs.is_empty() },
+    KeyHelp { keys: "Ctrl+W", description: "Close tab", shown: |_, game_state| !game_state.tabs.is_empty() },
+    KeyHelp { keys: "-/+ or 1-9", description: "Adjust framerate", shown: |_, game_state| !game_state.paused },
+    KeyHelp { keys: "-/+", description: "Adjust framerate", shown: |_, game_state| game_state.paused },
+    KeyHelp { keys: "</>", description: "Gens per frame", shown: |_, _| true },
+    KeyHelp { keys: "Shift+1-9", description: "Save bookmark", shown: |_, _| true },
+    KeyHelp { keys: "1-9", description: "Load bookmark", shown: |_, game_state| game_state.paused },
+    KeyHelp { keys: ":", description: "Command line", shown: |_, _| true },
+    KeyHelp { keys: "Q", description: "Quit", shown: |_, _| true },
+];
+const PANEL_WIDTH: u16 = 30; // the narrowest the board gets drawn, so that the bookmarks fit under it
+const PANEL_HEIGHT: u16 = 16; // lines under the board for the side panel, bookmarks, population and frame delay
+const SIDE_PANEL_WIDTH: u16 = 32; // how wide the panel is when it's next to the board instead
+const HELP_COLUMN_WIDTH: usize = 31; // each key in the help, with a gap after it
 
 const CELL_CHAR_UNICODE: char = '⬤';//'◯';//'◉';//'▨';
 const CELL_CHAR_ASCII: char = '#';
+const DYING_CELL_CHAR_UNICODE: char = '◯';
+const DYING_CELL_CHAR_ASCII: char = 'o';
+const CONDUCTOR_CHAR_UNICODE: char = '▨';
+const CONDUCTOR_CHAR_ASCII: char = '+';
+const PLAYER_TWO_CHAR_UNICODE: char = '◆';
+const PLAYER_TWO_CHAR_ASCII: char = 'X';
+const GHOST_CHAR_UNICODE: char = '◌'; // cells that will be pasted
+const GHOST_CHAR_ASCII: char = '%';
+const TRAIL_CHAR_UNICODE: char = '•'; // cells that died recently
+const TRAIL_CHAR_ASCII: char = '.';
+const BRAILLE_BLANK: char = '\u{2800}'; // the other braille characters are this plus whichever of BRAILLE_DOTS are filled in
+const BRAILLE_DOTS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]]; // indexed by [y][x] within each 2x4 block
+const MINIMAP_CHARS_UNICODE: [char; 5] = [' ', '░', '▒', '▓', '█']; // emptiest to fullest
+const MINIMAP_CHARS_ASCII: [char; 5] = [' ', '.', ':', '+', '#'];
+const MINIMAP_MAX_WIDTH: u32 = 24; // in characters, not counting its border
+const MINIMAP_MAX_HEIGHT: u32 = 8;
+const WALL_CHAR_UNICODE: char = '░';
+const WALL_CHAR_ASCII: char = ':';
+const SOURCE_CHAR_UNICODE: char = '■'; // cells that are always alive
+const SOURCE_CHAR_ASCII: char = '@';
+const PIXEL_SOURCE_COLOUR: Rgb = Rgb(255, 215, 0); // for themes without a source colour, so sources still stand out from other cells in pixel mode
+const ANT_CHARS_UNICODE: [char; 4] = ['▲', '▶', '▼', '◀']; // indexed by Ant.direction
+const ANT_CHARS_ASCII: [char; 4] = ['^', '>', 'v', '<'];
+
+
+const BOOKMARK_KEYS: [char; 9] = ['!', '@', '#', '$', '%', '^', '&', '*', '(']; // shift+1-9 (on a US keyboard), terminals don't send anything useful for most ctrl+number combos
+const EMPTY_BOOKMARK_CHAR_UNICODE: char = '·';
+const EMPTY_BOOKMARK_CHAR_ASCII: char = '.';
+
+const SPINNER_CHARS_UNICODE: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_CHARS_ASCII: [char; 4] = ['|', '/', '-', '\\'];
+const SPARKLINE_CHARS_UNICODE: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARKLINE_CHARS_ASCII: [char; 8] = ['_', '.', '-', '~', '=', '+', '*', '#'];
+const POPULATION_HISTORY_LENGTH: usize = 500; // more generations than any sparkline will be wide enough to show
+
+const TRAIL_LENGTH: u8 = 8; // generations that a dead cell's trail lasts for
+const DEMO_GENERATIONS: u32 = 300; // how long --demo shows each pattern for (9 seconds at the default speed)
+const AI_SEED_INTERVAL: u32 = 40; // generations between each patch of cells the immigration AI drops
+const AI_SEED_SIZE: i16 = 5; // width and height of the patch
+const AI_SEED_DISTANCE: i16 = 6; // how far from the player one cell it picks the patch can be
+const TRAIL_BRIGHTEST: u8 = 14; // out of 23 (the terminal's grayscale colours), trails fade out from this as they get older
+
+const DEFAULT_GRID_SPACING: u32 = 10; // for :grid without a spacing
+const GRID_CHAR_UNICODE: char = '·';
+const GRID_CHAR_ASCII: char = '.';
+const INFINITE_MARGIN: i16 = 16; // with --infinite the board grows whenever something gets this close to an edge (more than anything can travel in a generation)
+const LOAD_PADDING: u32 = 10; // empty cells that the board gets grown to leave around a --load pattern if it doesn't fit
+const STDIN_PATH: &str = "-"; // loading this reads the pattern from stdin instead of a file
+const RECOVERY_FILE_NAME: &str = ".game_of_life_recovery.rle"; // in the home directory
+const PATTERN_PADDING: u32 = 100; // empty cells on each side of a pattern run without the UI when no board size is given, so things can move around a bit before hitting the edges
+
+const SKIP_TIME_PER_FRAME: time::Duration = time::Duration::from_millis(50); // how long to spend computing generations between progress updates while skipping ahead
+
+// rough worst case for how much memory each cell of a board can use, the board's HashMap, the neighbour counts and the next generation's HashMap can all end up holding (almost) every cell
+const SIMULATION_BYTES_PER_CELL: u64 = 24;
+const PRINTED_BYTES_PER_CELL: u64 = 4; // the string the board gets printed from (unicode cells are 3 bytes)
+const PIXEL_BYTES_PER_CELL: u64 = 16; // the image the board gets drawn as in pixel mode (4 bytes per cell, then a bit more than that again for each of the base64 and png copies)
+const GHOST_COLOUR: Rgb = Rgb(127, 127, 127); // dimmed so it's obvious it isn't on the board yet
+const PIXEL_DEFAULT_COLOUR: Rgb = Rgb(229, 229, 229); // what None (the terminal's normal text colour) turns into in pixel mode, since the image can't use the real one
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 2048;
+
+const INPUT_TIMEOUT: time::Duration = time::Duration::from_millis(250); // longest to wait for a key before checking if we've been asked to quit
+const SPINNER_FRAME_TIME: time::Duration = time::Duration::from_millis(100);
+const TOAST_TIME: time::Duration = time::Duration::from_secs(4); // how long notifications stay up, they fade out over the last third of it
+const MAX_TOASTS: usize = 3; // older ones get pushed out
 
 const MIN_FRAME_DELAY: i16 = 1; // can't go to 0 ms or else moving the cursor while paused gets really glitchy (almost certainly just the terminal's fault and not mine)
-const MAX_FRAME_DELAY: i16 = 100; // if we go much higher than 100 ms it gets hard to lower the framerate because key inputs are received so slowly
+const MAX_FRAME_DELAY: i16 = 250; // keys only get handled once per frame so much slower than this and the game starts to feel unresponsive
+const MAX_GENERATIONS_PER_FRAME: u32 = 1024; // much more than this and big boards take longer than a frame to compute anyway
+const FRAME_DELAY_PRESETS: [i16; 9] = [1, 5, 10, 20, 30, 50, 100, 175, 250]; // frame delays for the 1-9 keys
 
 
 
-// derive() will automatically derive all the traits needed to be hashable by autogenering an impl
-// PartialEq adds equal and not equal methods (for symmetric and transitive relationships)
-// Eq adds no methods but basically says "the reflexive property holds for this thing"
-// you cant just do derive(Eq) because Eq inherits PartialEq so you need those methods for Eq to hold
-// also derive Clone because I want to be able to clone Points
-#[derive(PartialEq, Eq, Hash, Clone)]
-struct Point {
-    x: i16,
-    y: i16,
+
+
+fn ant_char(direction: u8, unicode: bool) -> char {
+    if unicode {
+        return ANT_CHARS_UNICODE[direction as usize];
+    }
+    return ANT_CHARS_ASCII[direction as usize];
 }
 
 
-impl Point {
-    fn bound(&mut self, min_x: i16, min_y: i16, max_x: i16, max_y: i16) {
-        if self.x < min_x { self.x = min_x; } 
-        else if self.x > max_x { self.x = max_x; }
-        if self.y < min_y { self.y = min_y; } 
-        else if self.y > max_y { self.y = max_y; }
+fn cell_char(automaton: Automaton, state: u8, unicode: bool) -> char {
+    if automaton == Automaton::Table && state != ALIVE { // rule tables can have lots of states, so they're shown as their number (then letters)
+        return char::from_digit(state as u32, 36).map_or('#', |c| c.to_ascii_uppercase());
+    }
+    if automaton == Automaton::Immigration && state == PLAYER_TWO {
+        return if unicode { PLAYER_TWO_CHAR_UNICODE } else { PLAYER_TWO_CHAR_ASCII };
     }
+    if automaton == Automaton::Wireworld {
+        return match (state, unicode) {
+            (ELECTRON_HEAD, true) => CELL_CHAR_UNICODE,
+            (ELECTRON_HEAD, false) => CELL_CHAR_ASCII,
+            (ELECTRON_TAIL, true) => DYING_CELL_CHAR_UNICODE,
+            (ELECTRON_TAIL, false) => DYING_CELL_CHAR_ASCII,
+            (_, true) => CONDUCTOR_CHAR_UNICODE,
+            (_, false) => CONDUCTOR_CHAR_ASCII,
+        };
+    }
+    return match (state, unicode) {
+        (ALIVE, true) => CELL_CHAR_UNICODE,
+        (ALIVE, false) => CELL_CHAR_ASCII,
+        (_, true) => DYING_CELL_CHAR_UNICODE,
+        (_, false) => DYING_CELL_CHAR_ASCII,
+    };
 }
 
 
-struct Board {
-    width: u32,
-    height: u32,
-    occupied_cells: HashSet<Point>,
+// which of the theme's colours a cell gets drawn in
+fn cell_colour(automaton: Automaton, state: u8, theme: &Theme) -> Option<Rgb> {
+    return match (automaton, state) {
+        (Automaton::Wireworld, ELECTRON_HEAD) => theme.cell,
+        (Automaton::Wireworld, ELECTRON_TAIL) => theme.dying,
+        (Automaton::Wireworld, _) => theme.conductor,
+        (Automaton::Immigration, PLAYER_TWO) => theme.rival,
+        (_, ALIVE) => theme.cell,
+        _ => theme.dying,
+    };
 }
 
 
-impl Board {
-    fn new(width: u32, height: u32) -> Board {
-        return Board {
-            width: width,
-            height: height,
-            occupied_cells: HashSet::new()
-        };
-    }
+// the part of the board in the viewport (width x height cells with viewport at the top left) as rows of characters, each with the colour it should be drawn in
+// hexagonal rules get a space between every cell and each row shifted half a cell (one character) to the left of the one above
+// with :grid the empty cells on every spacing-th row and column get a faint dot, and each dotted row starts with its y coordinate (hex boards don't get a grid)
+fn board_to_chars(board: &Board, game_state: &GameState, width: u32, height: u32) -> Vec<Vec<(char, Option<Rgb>)>> {
+    let (unicode, viewport) = (game_state.unicode_enabled, &game_state.viewport);
+    let theme = &THEMES[game_state.theme];
 
-    fn init_randomly(&mut self) {
-        self.occupied_cells = HashSet::new(); // empty occupied_cells
-        let mut rng = rand::thread_rng();
-        for _ in 0..((self.width * self.height) / 4) {
-            let random_cell = Point{
-                x: rng.gen_range(0..self.width as i16),
-                y: rng.gen_range(0..self.height as i16)
-            };
-            self.occupied_cells.insert(random_cell);
+    // build empty board, every character has the colour it should be drawn in next to it
+    let hex = hexagonal(board);
+    let row_length = if hex { hex_row_length(width, height) } else { width as u16 };
+    let grid_char = if unicode { GRID_CHAR_UNICODE } else { GRID_CHAR_ASCII };
+    let mut board_chars = Vec::new();
+    for row in 0..height {
+        let mut cell_row: Vec<(char, Option<Rgb>)> = vec![('║', theme.border)];
+        match game_state.grid.filter(|_| !hex) {
+            Some(spacing) if (viewport.y as u32 + row).is_multiple_of(spacing) => {
+                let label = (viewport.y as u32 + row).to_string();
+                let label = if label.len() < width as usize { label } else { String::new() };
+                cell_row.extend(label.chars().map(|character| (character, theme.grid)));
+                cell_row.extend(iter::repeat_n((grid_char, theme.grid), width as usize - label.len()));
+            }
+            Some(spacing) => cell_row.extend((0..width).map(|column| match (viewport.x as u32 + column).is_multiple_of(spacing) {
+                true => (grid_char, theme.grid),
+                false => (' ', None)
+            })),
+            None => cell_row.extend(iter::repeat_n((' ', None), row_length as usize))
         }
+        cell_row.push(('║', theme.border));
+        board_chars.push(cell_row);
     }
+    let mut set = |point: &Point, character: (char, Option<Rgb>)| {
+        let (x, y) = (point.x - viewport.x, point.y - viewport.y);
+        if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+            let column = if hex { hex_column(x, y, height) } else { x as u16 };
+            board_chars[y as usize][column as usize + 1] = character; // +1 because the first character of every row is a '║'
+        }
+    };
 
-    fn update_cells(&mut self) {
+    let wall_char = if unicode { WALL_CHAR_UNICODE } else { WALL_CHAR_ASCII };
+    for wall in &board.walls {
+        set(wall, (wall_char, theme.wall));
+    }
 
-        // first count how many neighbours each cell has (ignoring all the cells that we know have 0 neighbours)
-        let mut neighbour_counts: HashMap<Point, u8> = HashMap::new();
-        for cell in &self.occupied_cells {
+    // add filled cells
+    for (point, &state) in &board.cells {
+        set(point, (cell_char(board.automaton, state, unicode), cell_colour(board.automaton, state, theme)));
+    }
 
-            let on_top_edge = cell.y == 0;
-            let on_right_edge = cell.x == (self.width as i16 - 1);
-            let on_bottom_edge = cell.y == (self.height as i16 - 1);
-            let on_left_edge = cell.x == 0;
+    let source_char = if unicode { SOURCE_CHAR_UNICODE } else { SOURCE_CHAR_ASCII };
+    for source in &board.sources {
+        set(source, (source_char, theme.source.or(theme.cell)));
+    }
 
-            // find all valid neighbours
-            let mut neighbours: Vec<Point> = Vec::with_capacity(8);
-            if !on_top_edge {
-                neighbours.push(Point{x: cell.x, y: cell.y - 1});
-            }
-            if !on_right_edge {
-                neighbours.push(Point{x: cell.x + 1, y: cell.y});
-            }
-            if !on_bottom_edge {
-                neighbours.push(Point{x: cell.x, y: cell.y + 1});
-            }
-            if !on_left_edge {
-                neighbours.push(Point{x: cell.x - 1, y: cell.y});
-            }
-            if !on_top_edge && !on_left_edge {
-                neighbours.push(Point{x: cell.x - 1, y: cell.y - 1});
-            }
-            if !on_top_edge && !on_right_edge {
-                neighbours.push(Point{x: cell.x + 1, y: cell.y - 1});
-            }
-            if !on_bottom_edge && !on_left_edge {
-                neighbours.push(Point{x: cell.x - 1, y: cell.y + 1});
-            }
-            if !on_bottom_edge && !on_right_edge {
-                neighbours.push(Point{x: cell.x + 1, y: cell.y + 1});
-            }
+    // ants get drawn on top of the cells they're standing on
+    for ant in &board.ants {
+        set(&ant.position, (ant_char(ant.direction, unicode), theme.ant));
+    }
 
-            // increment each neighbouring cell's num_neighbours count by 1
-            for neighbour_cell in neighbours.into_iter() {
-                let num_neighbours = *neighbour_counts // dereference so that I don't have a borrowed value (could dereference it later but compiler will complain if I mutate neighbour_counts while having an immutable borrow of it out)
-                    .get(&neighbour_cell)
-                    .unwrap_or(&0); // count begins at 0 neighbours by default
-                neighbour_counts.insert(neighbour_cell, num_neighbours + 1);
-            }
-        }
+    return board_chars;
+}
 
-        // generate new occupied cells using neighbour counts
-        let mut new_occupied_cells = HashSet::new();
-        for (cell, neighbours) in neighbour_counts {
-            let is_alive = self.occupied_cells.contains(&cell);
-            if is_alive && (neighbours == 2 || neighbours == 3) {
-                new_occupied_cells.insert(cell);
-            }
-            else if !is_alive && neighbours == 3 {
-                new_occupied_cells.insert(cell);
-            }
+
+// draws rows of characters with their top left at left, top, a run of characters in the same colour at a time
+fn draw_chars(renderer: &mut dyn Renderer, left: u16, top: u16, rows: &[Vec<(char, Option<Rgb>)>]) {
+    for (row_num, row) in rows.iter().enumerate() {
+        let mut column = left;
+        for run in row.chunk_by(|(_, colour), (_, next_colour)| colour == next_colour) {
+            let text: String = run.iter().map(|&(character, _)| character).collect();
+            renderer.draw_text(column, top + row_num as u16, &text, run[0].1);
+            column += run.len() as u16;
         }
-        self.occupied_cells = new_occupied_cells;
     }
 }
 
 
-fn board_to_string(board: &Board, cell_char: char) -> String {
+// the automata that run on Board.rule, which can be changed while playing
+fn uses_rule(automaton: Automaton) -> bool {
+    return matches!(automaton, Automaton::Life | Automaton::Immigration);
+}
 
-    // build empty board string
-    let mut board_string = Vec::new();
-    for _ in 0..board.height {
-        let mut cell_row: Vec<char> = vec!['║'];
-        cell_row.extend(iter::repeat(' ').take(board.width as usize));
-        cell_row.push('║');
-        cell_row.push('\r'); // in raw mode terminals a newline just moves the cursor down, we need a carriage return so that the cursor also moves to the beginning of the line
-        cell_row.push('\n');
-        board_string.push(cell_row);
-    }
 
-    // add filled cells
-    for point in &board.occupied_cells {
-        board_string[point.y as usize][point.x as usize + 1] = cell_char; // x+1 because the first character of every row is a '║'
-    }
+fn hexagonal(board: &Board) -> bool {
+    return match (board.automaton, &board.table) {
+        (Automaton::Life | Automaton::Immigration, _) => board.rule.neighbourhood == Neighbourhood::Hexagonal,
+        (Automaton::Table, Some(table)) => table.neighbourhood == Neighbourhood::Hexagonal,
+        _ => false
+    };
+}
+
 
-    return board_string.iter().flatten().collect();
+// whether the board gets drawn as a hex grid, which only the modes with a character per cell can do
+fn hex_layout(board: &Board, game_state: &GameState) -> bool {
+    let pixels = game_state.pixels && game_state.graphics.is_some();
+    return hexagonal(board) && !game_state.braille && !pixels;
 }
 
 
-// prints parts of screen that wont change
-#[allow(unused_must_use)] // so I dont have to type .ok() after every write! call
-fn print_static_text<W: Write>(stdout: &mut W, board: &Board) {
+// how many characters wide a hex grid is, each cell takes up 2 characters and the rows lean over by one character each
+fn hex_row_length(width: u32, height: u32) -> u16 {
+    return (2 * width + height).saturating_sub(2) as u16;
+}
 
-    // print top and bottom of board
-    write!(stdout, "{}", termion::clear::All); // .ok() to convert Result into an Option and throw away the possible Error (because not handling the error is a compiler warning)
-    write!(stdout, "{}╔", termion::cursor::Goto(1, 1));
-    let long_pipe: String = iter::repeat('═')
-        .take(board.width as usize)
-        .collect();
-    write!(stdout, "{}", long_pipe);
-    write!(stdout, "╗");
-    write!(
-        stdout, "{}╠", 
-        termion::cursor::Goto(1, board.height as u16 + 2)
-    );
-    write!(stdout, "{}", long_pipe);
-    write!(stdout, "╝");
 
-    // print instructions
-    write!(
-        stdout, "{}╦", 
-        termion::cursor::Goto(INSTRUCTIONS_WIDTH, board.height as u16 + 2)
-    );
-    write!(stdout, "\r\n{}", INSTRUCTIONS);
+// which character of its row a cell is in on a hex grid (x and y are relative to the viewport)
+fn hex_column(x: i16, y: i16, height: u32) -> u16 {
+    return (2 * x as i32 + height as i32 - 1 - y as i32) as u16;
+}
+
 
-    stdout.flush();
+// how many cells of the board fit on the screen at once, boards bigger than that get scrolled around to follow the cursor
+fn viewport_size(board: &Board, game_state: &GameState) -> (u32, u32) {
+    let (max_width, max_height) = game_state.max_screen_size;
+    let (width, height) = match (game_state.pixels, &game_state.graphics) {
+        (true, Some(graphics)) => {
+            let (width, height) = graphics.cells_in(max_width, max_height);
+            (width as u32, height as u32)
+        }
+        _ if game_state.braille => (max_width as u32 * 2, max_height as u32 * 4),
+        _ if hex_layout(board, game_state) => {
+            let height = (max_height as u32).min(board.height);
+            (((max_width as u32 + 2).saturating_sub(height) / 2).max(1), height)
+        }
+        _ => (max_width as u32, max_height as u32)
+    };
+    return (width.min(board.width), height.min(board.height));
 }
 
 
-struct GameState {
-    paused: bool,
-    game_running: bool,
-    cursor_position: Point,
-    cursor_visible: bool,
-    cell_char: char,
-    frame_delay: i16, // signed so we can check when it goes below 0 more easily
-    is_first_frame: bool, // for any setup that only occurs on the first frame
+// how many characters wide and tall the board gets drawn (not counting its border)
+// braille packs 2x4 cells into each character, and pixel mode fits as many as the terminal's character size allows, but neither gets narrower than the panel
+fn screen_size(board: &Board, game_state: &GameState) -> (u16, u16) {
+    let (width, height) = viewport_size(board, game_state);
+    if let (true, Some(graphics)) = (game_state.pixels, &game_state.graphics) {
+        let (width, height) = graphics.screen_size(width, height);
+        return (width.max(PANEL_WIDTH), height);
+    }
+    if game_state.braille {
+        return ((width.div_ceil(2) as u16).max(PANEL_WIDTH), height.div_ceil(4) as u16);
+    }
+    if hex_layout(board, game_state) {
+        return (hex_row_length(width, height), height as u16);
+    }
+    return (width as u16, height as u16);
 }
 
 
-// stored seperately from GameState because these variables must be reset to defaults (false) every frame
-struct FrameState {
-    board_updated: bool,
-    frame_delay_updated: bool,
+// where a cell is on the screen when every cell is a character, None if it's scrolled out of view
+fn screen_position(board: &Board, game_state: &GameState, cell: &Point) -> Option<(u16, u16)> {
+    let (width, height) = viewport_size(board, game_state);
+    let (x, y) = (cell.x - game_state.viewport.x, cell.y - game_state.viewport.y);
+    if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+        return None;
+    }
+    let column = if hex_layout(board, game_state) { hex_column(x, y, height) } else { x as u16 };
+    return Some((column + 2, y as u16 + 2));
 }
 
 
-fn handle_key_press(key: Key, board: &mut Board, game_state: &mut GameState, frame_state: &mut FrameState) {
-    match key {
-        Key::Char('q') | Key::Char('Q') => game_state.game_running = false,
-        Key::Char(' ') => game_state.paused = !game_state.paused,
-        Key::Char('r') | Key::Char('R') => { // initialize randomly
-            board.init_randomly(); 
-            frame_state.board_updated = true;
-        },
-        Key::Char('c') | Key::Char('C') => { // clear board
-            board.occupied_cells = HashSet::new();
-            frame_state.board_updated = true;
+// Ctrl+F: moves the viewport so that the middle of the population (the average position of its cells) is in the middle of the screen
+// the cursor moves along with it so that it stays in the same place on the screen
+// only called when the board's getting redrawn anyway
+fn follow_population(board: &Board, game_state: &mut GameState) {
+    if board.cells.is_empty() {
+        return;
+    }
+    let (width, height) = viewport_size(board, game_state);
+    let (sum_x, sum_y) = board.cells.keys().fold((0i64, 0i64), |(x, y), cell| (x + cell.x as i64, y + cell.y as i64));
+    let centre = |sum: i64, length: u32, board_length: u32| -> i16 {
+        let start = sum / board.cells.len() as i64 - length as i64 / 2;
+        return start.clamp(0, (board_length - length) as i64) as i16;
+    };
+    let viewport = Point{x: centre(sum_x, width, board.width), y: centre(sum_y, height, board.height)};
+    game_state.cursor_position.x += viewport.x - game_state.viewport.x;
+    game_state.cursor_position.y += viewport.y - game_state.viewport.y;
+    game_state.viewport = viewport;
+}
+
+
+// moves the viewport so that the cursor's in it without going past the edges of the board, returns whether it moved
+fn scroll_to_cursor(board: &Board, game_state: &mut GameState) -> bool {
+    let (width, height) = viewport_size(board, game_state);
+    let scroll = |start: i16, cursor: i16, length: u32, board_length: u32| -> i16 {
+        let start = start.min(cursor).max(cursor - length as i16 + 1);
+        return start.clamp(0, (board_length - length) as i16);
+    };
+    let viewport = Point{
+        x: scroll(game_state.viewport.x, game_state.cursor_position.x, width, board.width),
+        y: scroll(game_state.viewport.y, game_state.cursor_position.y, height, board.height)
+    };
+    let moved = viewport != game_state.viewport;
+    game_state.viewport = viewport;
+    return moved;
+}
+
+
+// like board_to_chars but each character is a braille pattern with a dot for every cell (or ant) in a 2x4 block
+fn board_to_braille(board: &Board, theme: &Theme, viewport: &Point, screen_width: u16, screen_height: u16) -> Vec<Vec<(char, Option<Rgb>)>> {
+    let mut dots = vec![vec![0u8; screen_width as usize]; screen_height as usize];
+    let positions = board.cells.keys().chain(board.ants.iter().map(|ant| &ant.position));
+    for cell in positions {
+        let (x, y) = (cell.x - viewport.x, cell.y - viewport.y);
+        if x >= 0 && y >= 0 && (x as usize) < screen_width as usize * 2 && (y as usize) < screen_height as usize * 4 {
+            let (x, y) = (x as usize, y as usize);
+            dots[y / 4][x / 2] |= BRAILLE_DOTS[y % 4][x % 2];
         }
-        Key::Char('f') | Key::Char('F') => { // move forward one frame
-            if game_state.paused {
-                board.update_cells();
-                frame_state.board_updated = true;
-            }
+    }
+    return dots.iter()
+        .map(|row| {
+            let mut characters = vec![('║', theme.border)];
+            characters.extend(row.iter().map(|&dots| (char::from_u32(BRAILLE_BLANK as u32 + dots as u32).unwrap(), theme.cell)));
+            characters.push(('║', theme.border));
+            return characters;
+        })
+        .collect();
+}
+
+
+// the part of the board in the viewport as an rgba image with one pixel per cell for pixel mode, empty cells are see-through
+// the overlays get drawn into the image too since there aren't any characters to put them on
+fn board_to_pixels(board: &Board, game_state: &GameState, width: u32, height: u32) -> Vec<u8> {
+    let theme = &THEMES[game_state.theme];
+    let mut image = vec![0; width as usize * height as usize * 4];
+    let mut fill = |cell: &Point, Rgb(r, g, b): Rgb| {
+        let (x, y) = (cell.x - game_state.viewport.x, cell.y - game_state.viewport.y);
+        if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+            let index = (y as usize * width as usize + x as usize) * 4;
+            image[index..index + 4].copy_from_slice(&[r, g, b, 255]);
         }
-        Key::Right => game_state.cursor_position.x += 1,
-        Key::Down => game_state.cursor_position.y += 1,
-        Key::Left => game_state.cursor_position.x -= 1,
-        Key::Up => game_state.cursor_position.y -= 1,
-        Key::Char('h') | Key::Char('H') => { // hide cursor
-            game_state.cursor_visible = !game_state.cursor_visible;
+    };
+    if let Some(heatmap) = &game_state.heatmap {
+        for (cell, heat) in heatmap.heat() {
+            fill(cell, heat_colour(heat));
         }
-        Key::Char('a') | Key::Char('A') => { // create/kill a cell
-            if board.occupied_cells.contains(&game_state.cursor_position) {
-                board.occupied_cells.remove(&game_state.cursor_position);
-            } else {
-                board.occupied_cells.insert(game_state.cursor_position.clone());
-            }
-            frame_state.board_updated = true;
+    }
+    if let Some(trails) = &game_state.trails {
+        for (cell, &age) in trails {
+            fill(cell, trail_colour(age));
         }
-        Key::Char('u') | Key::Char('U') => {
-            if game_state.cell_char == CELL_CHAR_UNICODE {
-                game_state.cell_char = CELL_CHAR_ASCII;
-            } else {
-                game_state.cell_char = CELL_CHAR_UNICODE;
-            }
-            frame_state.board_updated = true;
+    }
+    for wall in &board.walls {
+        fill(wall, theme.wall.unwrap_or(Rgb(100, 100, 100)));
+    }
+    for (cell, &state) in &board.cells {
+        fill(cell, cell_colour(board.automaton, state, theme).unwrap_or(PIXEL_DEFAULT_COLOUR));
+    }
+    for source in &board.sources {
+        fill(source, theme.source.unwrap_or(PIXEL_SOURCE_COLOUR));
+    }
+    for ant in &board.ants {
+        fill(&ant.position, theme.ant.unwrap_or(Rgb(255, 40, 40)));
+    }
+    if let (true, Some(clipboard)) = (game_state.pasting, &game_state.clipboard) {
+        for (cell, _) in &clipboard.cells {
+            fill(&Point{x: game_state.cursor_position.x + cell.x, y: game_state.cursor_position.y + cell.y}, GHOST_COLOUR);
         }
-        Key::Char('-') | Key::Char('_') | Key::Char('=') | Key::Char('+') => {
-            match key {
-                Key::Char('-') | Key::Char('_') => game_state.frame_delay -= 1,
-                _ => game_state.frame_delay += 1
-            }
-            if game_state.frame_delay < MIN_FRAME_DELAY { 
-                game_state.frame_delay = MIN_FRAME_DELAY; 
-            }
-            if game_state.frame_delay > MAX_FRAME_DELAY { 
-                game_state.frame_delay = MAX_FRAME_DELAY; 
-            }
-            frame_state.frame_delay_updated = true;
+    }
+    return image;
+}
+
+
+// prints parts of screen that wont change
+fn print_static_text(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let theme = &THEMES[game_state.theme];
+    let (screen_width, screen_height) = screen_size(board, game_state);
+
+    // print top and bottom of board
+    renderer.clear();
+    let long_pipe: String = "═".repeat(screen_width as usize);
+    renderer.draw_text(1, 1, &format!("╔{}╗", long_pipe), theme.border);
+    renderer.draw_text(1, screen_height + 2, &format!("╚{}╝", long_pipe), theme.border);
+
+    // in pixel mode the image only covers the inside of the box so the sides have to be printed here
+    if game_state.pixels {
+        for y in 2..(screen_height + 2) {
+            renderer.draw_text(1, y, &format!("║{}║", " ".repeat(screen_width as usize)), theme.border);
         }
-        _ => ()
-    };
+    }
 }
 
 
-fn play_game<W: io::Write, R: io::Read>(board: &mut Board, key_input: &mut termion::input::Keys<R>, stdout: &mut W) {
-    let mut game_state = GameState {
-        paused: false,
-        game_running: true,
-        cursor_position: Point{x:0, y:0}, // we will consider the top left of the board to be 0,0 to conform with board.occupied_cells Points
-        cursor_visible: true,
-        cell_char: CELL_CHAR_UNICODE,
-        frame_delay: 30,
-        is_first_frame: true
+// the ? overlay, covering the whole game: the keys that do something right now (which changes with the automaton, whether the game is paused, whether something's being pasted, etc)
+// they go down in as many columns as fit across the board, anything past the bottom gets cut off
+fn print_help(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let theme = &THEMES[game_state.theme];
+    let (screen_width, _) = screen_size(board, game_state);
+    let width = match game_state.panel { // inside the border
+        Panel::Side => (screen_width + SIDE_PANEL_WIDTH + 1) as usize,
+        _ => screen_width as usize
     };
+    let height = (status_line(board, game_state) - 3) as usize;
+    let shown_keys: Vec<String> = KEY_HELP.iter()
+        .filter(|key_help| (key_help.shown)(board, game_state))
+        .map(|key_help| format!("{:<12}{:<17}", format!("{}:", key_help.keys), key_help.description))
+        .collect();
+    let columns = ((width - 1) / HELP_COLUMN_WIDTH).max(1);
+    let rows = shown_keys.len().div_ceil(columns);
+    let title = " Keys (any key to close) ";
+    renderer.clear();
+    renderer.draw_text(1, 1, &format!("╔═{}{}╗", title, "═".repeat(width.saturating_sub(title.len() + 1))), theme.border);
+    for row in 0..height {
+        let line: String = (0..columns)
+            .filter_map(|column| shown_keys.get(column * rows + row).filter(|_| row < rows))
+            .map(|key| format!("{:<width$}", key, width = HELP_COLUMN_WIDTH))
+            .collect();
+        let line: String = format!(" {}", line).chars().take(width).collect();
+        renderer.draw_text(1, row as u16 + 2, "║", theme.border);
+        renderer.draw_text(2, row as u16 + 2, &format!("{:<width$}", line, width = width), theme.text);
+        renderer.draw_text(width as u16 + 2, row as u16 + 2, "║", theme.border);
+    }
+    renderer.draw_text(1, height as u16 + 2, &format!("╚{}╝", "═".repeat(width)), theme.border);
+}
 
-    while game_state.game_running {
-
-        let mut frame_state = FrameState {
-            board_updated: false,
-            frame_delay_updated: false
-        };
 
-        // update_cells before we handle key presses so that if a keypress causes a cell to be born or die we will see that effect directly on the next frame (if we were to call update_cells after handling input (but before printing the frame) then we would never see the direct result of the user input because update_cells would be called because the user input has a chance to be printed to the screen)
-        // the downside of doing it this way is that that a user keypress actually effects the state of the next cell update, and not the current cell update (the one that the user is currently looking at), although this is only noticable at low framerates
-        if !game_state.paused {
-            board.update_cells();
-            frame_state.board_updated = true;
+// what each cell character means for automata with more than one kind of cell, with a > next to the state that A draws
+fn legend_lines(board: &Board, game_state: &GameState) -> Vec<String> {
+    let unicode = game_state.unicode_enabled;
+    let paint_state = drawn_state(board, game_state);
+    let marker = |state: u8| if state == paint_state { '>' } else { ' ' };
+    return match board.automaton {
+        Automaton::Life if board.rule.num_states <= 2 => Vec::new(),
+        Automaton::Margolus(_) => Vec::new(),
+        Automaton::Table => {
+            let mut lines = vec![format!("{}:", board.table.as_ref().map_or("", |table| &table.name))];
+            for state in 1..=num_paintable_states(board) {
+                lines.push(format!("{}{} {} State {}", marker(state), state, cell_char(board.automaton, state, unicode), state));
+            }
+            lines
         }
+        Automaton::Life => {
+            let mut lines = vec!["Generations:".to_string()];
+            for state in 1..=num_paintable_states(board) {
+                let name = if state == ALIVE { "Alive".to_string() } else { format!("Dying {}", state - ALIVE) };
+                lines.push(format!("{}{} {} {}", marker(state), state, cell_char(board.automaton, state, unicode), name));
+            }
+            lines
+        }
+        Automaton::Wireworld => vec![
+            "Wireworld:".to_string(),
+            format!("{}1 {} Electron head (E)", marker(ELECTRON_HEAD), cell_char(board.automaton, ELECTRON_HEAD, unicode)),
+            format!("{}2 {} Electron tail (T)", marker(ELECTRON_TAIL), cell_char(board.automaton, ELECTRON_TAIL, unicode)),
+            format!("{}3 {} Conductor", marker(CONDUCTOR), cell_char(board.automaton, CONDUCTOR, unicode)),
+        ],
+        Automaton::Immigration => vec![
+            "Immigration:".to_string(),
+            format!("{}1 {} Player 1", marker(PLAYER_ONE), cell_char(board.automaton, PLAYER_ONE, unicode)),
+            format!("{}2 {} Player 2", marker(PLAYER_TWO), cell_char(board.automaton, PLAYER_TWO, unicode)),
+        ],
+        Automaton::LangtonsAnt => vec![
+            "Langton's Ant:".to_string(),
+            format!(" {} Ant (N to place/turn/remove)", ant_char(0, unicode)),
+            format!(" {} Black cell (A)", cell_char(board.automaton, ALIVE, unicode)),
+        ],
+    };
+}
 
-        // handle key presses
-        // this only handles one key per frame but key_input has a buffer so if more than one key is pressed in one frame duration then each key press will still get handled on subsequent frames 
-        match key_input.next() {
-            Some(input) => {
-                handle_key_press(input.unwrap(), board, &mut game_state, &mut frame_state); // kinda yucky that handle_key_press can mutate any of its input, would be more clear if it returned a BoardState and FrameState but then rust gets angry about borrows and moves and fixing it ends up being even worse than this
-            },
-            None => () // a key wasn't pressed
+
+// the name and (word wrapped) description of a bundled pattern
+fn pattern_info_lines(pattern: &BundledPattern, heading: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![format!("{}: {}", heading, pattern.name)];
+    let mut line = String::new();
+    for word in pattern.description.split(' ') {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(line);
+            line = String::new();
         }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    return lines;
+}
+
 
-        // print board
-        if frame_state.board_updated {
-            let board_string = board_to_string(board, game_state.cell_char);
-            write!(stdout, "{}{}", termion::cursor::Goto(1, 2), board_string).ok();
+// everything under the board: the legend, then the puzzle, the petri dish report, the demo's pattern or the pattern of the day
+// lines that don't fit go in another column next to them (if there's room), and lines that aren't used get blanked out so that nothing is left behind when something gets shorter or goes away
+fn print_side_panel(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let (left, top, width) = match panel_position(board, game_state) {
+        Some(position) => position,
+        None => return
+    };
+    let mut lines = legend_lines(board, game_state);
+    let rest = match (&game_state.puzzle, &game_state.petri_dish, game_state.pattern_of_the_day) {
+        (Some(puzzle), _, _) => match game_state.unicode_enabled {
+            true => puzzle.report(board, CELL_CHAR_UNICODE, EMPTY_BOOKMARK_CHAR_UNICODE),
+            false => puzzle.report(board, CELL_CHAR_ASCII, EMPTY_BOOKMARK_CHAR_ASCII)
+        },
+        (None, Some(petri_dish), _) => petri_dish.report(),
+        (None, None, _) if game_state.demo.is_some() => {
+            let demo = game_state.demo.as_ref().unwrap();
+            let heading = format!("Demo {}/{}", demo.index + 1, DEMO_PATTERNS.len());
+            pattern_info_lines(demo_pattern(demo.index), &heading, width)
         }
+        (None, None, Some(pattern)) => pattern_info_lines(pattern, "Pattern of the day", width),
+        (None, None, None) => Vec::new(),
+    };
+    if !lines.is_empty() && !rest.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(rest);
+    let height = (PANEL_HEIGHT - 5) as usize; // leave room for the bookmarks and population
+    let column_width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) + 3;
+    for line_num in 0..height {
+        let line: String = lines.iter().skip(line_num).step_by(height)
+            .map(|line| format!("{:<width$}", line, width = column_width))
+            .collect::<String>()
+            .chars().take(width).collect();
+        let padding = " ".repeat(width - line.chars().count());
+        renderer.draw_text(left, top + line_num as u16, &format!("{}{}", line, padding), THEMES[game_state.theme].text);
+    }
+}
 
-        // write frame delay
-        if frame_state.frame_delay_updated || game_state.is_first_frame {
-            let last_line = board.height as u16 + INSTRUCTIONS_HEIGHT + 2;
-            write!(
-                stdout, 
-                "{}Sleep per frame: {} ms     ", // extra spaces to eliminate old trailing zeros
-                termion::cursor::Goto(0, last_line),
-                game_state.frame_delay
-            ).ok();
+
+// draws a pattern over the board with its top left at top_left, without actually putting it on the board
+fn print_ghost(renderer: &mut dyn Renderer, board: &Board, pattern: &Pattern, top_left: &Point, game_state: &GameState) {
+    let ghost_char = if game_state.unicode_enabled { GHOST_CHAR_UNICODE } else { GHOST_CHAR_ASCII };
+    for (cell, _) in &pattern.cells {
+        if let Some((left, top)) = screen_position(board, game_state, &Point{x: top_left.x + cell.x, y: top_left.y + cell.y}) {
+            renderer.draw_text(left, top, &ghost_char.to_string(), Some(GHOST_COLOUR));
         }
+    }
+}
 
-        // ensure cursor is at correct location
-        game_state.cursor_position.bound(
-            0, 0, 
-            board.width as i16 - 1, board.height as i16 - 1
-        );
-        write!(stdout, "{}", termion::cursor::Goto(
-            game_state.cursor_position.x as u16 + 2, 
-            game_state.cursor_position.y as u16 + 2
-        )).ok();
-
-        // set cursor visibility
-        if game_state.cursor_visible {
-            write!(stdout, "{}", termion::cursor::Show).ok();
+
+// writes the x coordinate of every grid column that has room for it into the top border, which has to be redone whenever the view moves
+fn print_grid_labels(renderer: &mut dyn Renderer, game_state: &GameState, spacing: u32, width: u32) {
+    let theme = &THEMES[game_state.theme];
+    let mut border = String::new();
+    for column in 0..width {
+        if border.chars().count() > column as usize { // the label before this one is still going
+            continue;
+        }
+        let x = game_state.viewport.x as u32 + column;
+        let label = x.to_string();
+        if x.is_multiple_of(spacing) && (column as usize + label.len()) <= width as usize {
+            border.push_str(&label);
         } else {
-            write!(stdout, "{}", termion::cursor::Hide).ok();
+            border.push('═');
         }
+    }
+    renderer.draw_text(2, 1, &border, theme.border);
+}
 
-        game_state.is_first_frame = false;
 
-        stdout.flush().ok(); // ensure all writes are printed to the screen
-        thread::sleep(time::Duration::from_millis(game_state.frame_delay as u64)); // sleep for duration of one frame
+// the gray a trail gets drawn in, darker the longer ago the cell died (the same grays as the terminal's grayscale colours)
+fn trail_colour(age: u8) -> Rgb {
+    let shade = 8 + 10 * (TRAIL_BRIGHTEST - age * TRAIL_BRIGHTEST / TRAIL_LENGTH);
+    return Rgb(shade, shade, shade);
+}
+
+
+// draws recently dead cells in gray over the board, getting darker the longer ago they died
+fn print_trails(renderer: &mut dyn Renderer, board: &Board, trails: &CellMap<u8>, game_state: &GameState) {
+    let trail_char = if game_state.unicode_enabled { TRAIL_CHAR_UNICODE } else { TRAIL_CHAR_ASCII };
+    for (cell, &age) in trails {
+        if board.cells.contains_key(cell) || board.walls.contains(cell) { // something's been drawn there since
+            continue;
+        }
+        if let Some((left, top)) = screen_position(board, game_state, cell) {
+            renderer.draw_text(left, top, &trail_char.to_string(), Some(trail_colour(age)));
+        }
     }
 }
 
 
-fn default_board_dimensions() -> (u16, u16) {
-    let (terminal_width, terminal_height) = termion::terminal_size().unwrap();
-    let min_board_height = 1;
-    let min_board_width = INSTRUCTIONS_WIDTH - 2; // -2 because theres 2 borders on either side of the instructions
-    let max_board_width = terminal_width - 2; // again, -2 because borders
-    let max_board_height = terminal_height - INSTRUCTIONS_HEIGHT - 2;
-    if max_board_height < min_board_height || max_board_width < min_board_width {
-        println!("your terminal is too small to play :(");
-        process::exit(1);
+// the automata that Board::live_neighbour_counts works for
+fn counts_neighbours(automaton: Automaton) -> bool {
+    return matches!(automaton, Automaton::Life | Automaton::Immigration | Automaton::Wireworld);
+}
+
+
+// the states that count as live neighbours, see Board::live_neighbour_counts
+fn counted_as_live(automaton: Automaton, state: u8) -> bool {
+    return match automaton {
+        Automaton::Immigration => true, // both players
+        Automaton::Wireworld => state == ELECTRON_HEAD,
+        _ => state == ALIVE
+    };
+}
+
+
+// Ctrl+P: cells that will be born next generation show up as ghosts and cells that will die turn red
+fn print_preview(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let mut next = board.clone();
+    next.update_cells();
+    let live = |cells: &CellMap<u8>, cell: &Point| cells.get(cell).is_some_and(|&state| counted_as_live(board.automaton, state));
+    let ghost_char = if game_state.unicode_enabled { GHOST_CHAR_UNICODE } else { GHOST_CHAR_ASCII };
+    let changing: CellSet = board.cells.keys().chain(next.cells.keys())
+        .filter(|cell| live(&board.cells, cell) != live(&next.cells, cell))
+        .cloned()
+        .collect();
+    for cell in &changing {
+        let (left, top) = match screen_position(board, game_state, cell) {
+            Some(position) => position,
+            None => continue
+        };
+        let (character, colour) = match board.cells.get(cell) {
+            Some(&state) if live(&board.cells, cell) => (cell_char(board.automaton, state, game_state.unicode_enabled), theme::DEATH_COLOUR),
+            _ => (ghost_char, theme::BIRTH_COLOUR)
+        };
+        renderer.draw_text(left, top, &character.to_string(), Some(colour));
     }
-    return (max_board_width, max_board_height);
 }
 
 
-fn parse_args(args: Vec<String>, mut board_width: u16, mut board_height: u16) -> (u16, u16) {
-    let mut last_arg: String = "".to_string(); // convert to String since string literals are of type &str
-    for arg in args {
-        match arg.parse::<u16>() {
-            Ok(val) => {
-                match last_arg.as_str() {
-                    "--height" => board_height = val,
-                    "--width" => board_width = val,
-                    _ => ()
-                }
+// Ctrl+N: writes how many live neighbours each cell has over it (up to 9, + for more), coloured by what happens to the cell next generation
+// empty cells with no live neighbours are left alone
+fn print_neighbour_counts(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let counts = match board.live_neighbour_counts() {
+        Some(counts) => counts,
+        None => return
+    };
+    let mut next = board.clone();
+    next.update_cells();
+    let live = |cells: &CellMap<u8>, cell: &Point| cells.get(cell).is_some_and(|&state| counted_as_live(board.automaton, state));
+    let shown: CellSet = counts.keys()
+        .chain(board.cells.keys().filter(|cell| live(&board.cells, cell)))
+        .cloned()
+        .collect();
+    for cell in &shown {
+        let (left, top) = match screen_position(board, game_state, cell) {
+            Some(position) if !board.walls.contains(cell) => position,
+            _ => continue
+        };
+        let count = *counts.get(cell).unwrap_or(&0) as u32;
+        let colour = match (live(&board.cells, cell), live(&next.cells, cell)) {
+            (false, true) => theme::BIRTH_COLOUR,
+            (true, true) => theme::SURVIVAL_COLOUR,
+            (true, false) => theme::DEATH_COLOUR,
+            (false, false) => theme::STILL_EMPTY_COLOUR
+        };
+        renderer.draw_text(left, top, &char::from_digit(count, 10).unwrap_or('+').to_string(), Some(colour));
+    }
+}
+
+
+// colours the background of every cell by how often it's been alive lately, from blue for hardly ever to red for always
+fn print_heatmap(renderer: &mut dyn Renderer, board: &Board, heatmap: &Heatmap, game_state: &GameState) {
+    for (cell, heat) in heatmap.heat() {
+        let (left, top) = match screen_position(board, game_state, cell) {
+            Some(position) if !board.walls.contains(cell) => position,
+            _ => continue
+        };
+        let character = board.cells.get(cell).map_or(' ', |&state| cell_char(board.automaton, state, game_state.unicode_enabled));
+        renderer.draw_highlighted(left, top, &character.to_string(), Some(theme::heat_colour(heat)));
+    }
+}
+
+
+// the toasts in the bottom right corner of the board (newest at the bottom), fading out towards the end of TOAST_TIME
+fn print_toasts(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let (screen_width, screen_height) = screen_size(board, game_state);
+    for (index, toast) in game_state.toasts.iter().rev().enumerate().take(screen_height as usize) {
+        let text: String = format!(" {} ", toast.text).chars().take(screen_width as usize).collect();
+        let left = screen_width + 2 - text.chars().count() as u16;
+        let fade = ((TOAST_TIME.as_secs_f32() - toast.shown.elapsed().as_secs_f32()) * 3.0 / TOAST_TIME.as_secs_f32()).clamp(0.0, 1.0);
+        let colour = THEMES[game_state.theme].status.map(|Rgb(r, g, b)| Rgb((r as f32 * fade) as u8, (g as f32 * fade) as u8, (b as f32 * fade) as u8));
+        renderer.draw_highlighted(left, screen_height + 1 - index as u16, &text, colour); // the colour is the background so it stands out from the cells
+    }
+}
+
+
+// a little map of the whole board in the top right corner when it doesn't all fit on the screen
+// each character is shaded by how full that part of the board is, and the part that's on the screen is highlighted
+fn print_minimap(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let (viewport_width, viewport_height) = viewport_size(board, game_state);
+    let (screen_width, screen_height) = screen_size(board, game_state);
+    let max_width = MINIMAP_MAX_WIDTH.min(screen_width.saturating_sub(2) as u32);
+    let max_height = MINIMAP_MAX_HEIGHT.min(screen_height.saturating_sub(2) as u32);
+    if (viewport_width == board.width && viewport_height == board.height) || max_width == 0 || max_height == 0 {
+        return;
+    }
+    // each character covers a cells_per_char wide and twice as tall part of the board, since characters are about twice as tall as they are wide
+    let cells_per_char = board.width.div_ceil(max_width).max(board.height.div_ceil(2 * max_height));
+    let (map_width, map_height) = (board.width.div_ceil(cells_per_char), board.height.div_ceil(2 * cells_per_char));
+    let mut counts = vec![vec![0u32; map_width as usize]; map_height as usize];
+    for cell in board.cells.keys() {
+        counts[cell.y as usize / (2 * cells_per_char as usize)][cell.x as usize / cells_per_char as usize] += 1;
+    }
+    let shades = if game_state.unicode_enabled { MINIMAP_CHARS_UNICODE } else { MINIMAP_CHARS_ASCII };
+    let area = 2 * cells_per_char * cells_per_char;
+    let in_viewport = |x: u32, y: u32| {
+        let (viewport_x, viewport_y) = (game_state.viewport.x as u32, game_state.viewport.y as u32);
+        return (viewport_x / cells_per_char..=(viewport_x + viewport_width - 1) / cells_per_char).contains(&x)
+            && (viewport_y / (2 * cells_per_char)..=(viewport_y + viewport_height - 1) / (2 * cells_per_char)).contains(&y);
+    };
+
+    let theme = &THEMES[game_state.theme];
+    let left = screen_width - map_width as u16; // so the right of the box lines up with the right of the board
+    renderer.draw_text(left, 2, &format!("╔{}╗", "═".repeat(map_width as usize)), theme.border);
+    for (y, row) in counts.iter().enumerate() {
+        let top = 3 + y as u16;
+        renderer.draw_text(left, top, "║", theme.border);
+        let shaded: Vec<(usize, bool)> = row.iter().enumerate()
+            .map(|(x, &count)| {
+                let shade = if count == 0 { 0 } else { 1 + (count * 6 / area).min(3) as usize }; // anything over half full gets the fullest shade
+                return (shade, in_viewport(x as u32, y as u32));
+            })
+            .collect();
+        let mut column = left + 1;
+        for run in shaded.chunk_by(|(_, highlighted), (_, next_highlighted)| highlighted == next_highlighted) {
+            let text: String = run.iter().map(|&(shade, _)| shades[shade]).collect();
+            match run[0].1 {
+                true => renderer.draw_highlighted(column, top, &text, theme.cell),
+                false => renderer.draw_text(column, top, &text, theme.cell)
             }
-            Err(_) => ()
+            column += run.len() as u16;
         }
-        last_arg = arg;
+        renderer.draw_text(column, top, "║", theme.border);
     }
-    return (board_width, board_height)
+    renderer.draw_text(left, 3 + map_height as u16, &format!("╚{}╝", "═".repeat(map_width as usize)), theme.border);
 }
 
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+// shows which bookmark slots have something in them (above the population)
+fn print_bookmarks(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let (left, top, _) = match panel_position(board, game_state) {
+        Some(position) => position,
+        None => return
+    };
+    let empty_char = if game_state.unicode_enabled { EMPTY_BOOKMARK_CHAR_UNICODE } else { EMPTY_BOOKMARK_CHAR_ASCII };
+    let slots: Vec<String> = game_state.bookmarks.iter().enumerate()
+        .map(|(slot, bookmark)| match bookmark {
+            Some(_) => (slot + 1).to_string(),
+            None => empty_char.to_string()
+        })
+        .collect();
+    renderer.draw_text(left, top + PANEL_HEIGHT - 4, &format!("Bookmarks: {}", slots.join(" ")), THEMES[game_state.theme].text);
+}
 
-    let (defualt_board_width, default_board_height) = default_board_dimensions();
-    let (board_width, board_height) = parse_args(
-        args, defualt_board_width, default_board_height
-    );
-    let mut board = Board::new(
-        board_width as u32, 
-        board_height as u32
-    );
-    board.init_randomly();
 
-    // switch to alternate screen buffer and enter raw mode
-    let mut stdout = termion::screen::AlternateScreen::from(
-        io::stdout().into_raw_mode().unwrap() // into_raw_mode enters raw mode (don't echo every key we press, don't move the cursor when we press keys, etc)
-    );
+// prints the population and a sparkline of how it's changed over the last few generations (at the bottom of the panel)
+fn print_population(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState) {
+    let (left, top, sparkline_width) = match panel_position(board, game_state) {
+        Some(position) => position,
+        None => return
+    };
+    let bottom = top + PANEL_HEIGHT - 2;
+    let colour = THEMES[game_state.theme].text;
+    let scores = match board.automaton {
+        Automaton::Immigration => {
+            let (player_one, player_two) = board.scores();
+            format!("   Player 1: {}   Player 2: {}", player_one, player_two)
+        }
+        _ => String::new()
+    };
+    let population = format!("Population: {}{}", board.population(), scores);
+    let padding = " ".repeat(sparkline_width.saturating_sub(population.chars().count())); // get rid of old digits
+    renderer.draw_text(left, bottom - 1, &format!("{}{}", population, padding), colour);
+
+    let history: Vec<usize> = game_state.population_history.iter()
+        .skip(game_state.population_history.len().saturating_sub(sparkline_width))
+        .cloned()
+        .collect();
+    let sparkline_chars = if game_state.unicode_enabled { SPARKLINE_CHARS_UNICODE } else { SPARKLINE_CHARS_ASCII };
+    let min = history.iter().cloned().min().unwrap_or(0);
+    let max = history.iter().cloned().max().unwrap_or(0);
+    let sparkline: String = history.iter()
+        .map(|&population| {
+            if max == min {
+                return sparkline_chars[0];
+            }
+            let level = (population - min) * (sparkline_chars.len() - 1) / (max - min); // scaled so the lowest population in the window gets the shortest bar
+            return sparkline_chars[level];
+        })
+        .collect();
+    renderer.draw_text(left, bottom, &sparkline, colour);
+}
 
-    // create object to read keyboard inputs from (use async_stdin instead of io::stdin so that calls to key_input.next are nonblocking)
-    let mut key_input = termion::async_stdin().keys();
 
-    print_static_text(&mut stdout, &board);
+// draws a little box in the middle of the board with the text being typed and any error with it (or what tab could complete it to)
+// returns where the cursor goes, at the end of the typed text
+fn print_prompt(renderer: &mut dyn Renderer, board: &Board, game_state: &GameState, prompt: &Prompt) -> (u16, u16) {
+    let (screen_width, screen_height) = screen_size(board, game_state);
+    let box_width = screen_width.saturating_sub(2).clamp(20, 40) as usize; // inner width
+    let left = 2 + (screen_width as usize).saturating_sub(box_width + 2) as u16 / 2;
+    let top = 2 + screen_height.saturating_sub(4) / 2;
+    let fit = |text: &str| -> String { // truncate/pad text to exactly fill the inside of the box
+        let mut fitted: String = text.chars().take(box_width).collect();
+        fitted.push_str(&" ".repeat(box_width - fitted.chars().count()));
+        return fitted;
+    };
+    let (title, help) = match prompt.kind {
+        PromptKind::Rule => ("Rule", "Enter: apply, Esc: cancel"),
+        PromptKind::Skip => ("Generations to skip", "Enter: apply, Esc: cancel"),
+        PromptKind::Command => ("Command", "Tab: complete, Up/Down: history"),
+        PromptKind::Confirm(_) => ("Unsaved edits", "Y: yes, anything else: no"),
+    };
+    let message = prompt.message.as_deref().unwrap_or(help);
+    let scroll = (prompt.cursor + 1).saturating_sub(box_width); // text longer than the box scrolls to keep the cursor in it
+    let shown_text: String = prompt.text.chars().skip(scroll).collect();
+    renderer.draw_text(left, top, &format!("╔═ {} {}╗", title, "═".repeat(box_width - 3 - title.len())), None);
+    renderer.draw_text(left, top + 1, &format!("║{}║", fit(&shown_text)), None);
+    renderer.draw_text(left, top + 2, &format!("║{}║", fit(message)), None);
+    renderer.draw_text(left, top + 3, &format!("╚{}╝", "═".repeat(box_width)), None);
+    return (left + 1 + (prompt.cursor - scroll) as u16, top + 1);
+}
 
-    play_game(&mut board, &mut key_input, &mut stdout);
 
-    // reset terminal to exit
-    write!(stdout, 
-        "{}{}{}", 
-        termion::cursor::Show, // make cursor visible again
-        termion::cursor::Goto(0,0), // move cursor back to a reasonable place (useful because some terminals won't exit the alternate screen buffer properly (maybe they only have 1 buffer?))
-        termion::clear::All // also for screens that don't exit the alternate screen properly
-    ).ok();
-    stdout.flush().ok();
+struct GameState {
+    paused: bool,
+    game_running: bool,
+    cursor_position: Point,
+    cursor_visible: bool,
+    viewport: Point, // the cell in the top left of the screen, boards bigger than the screen get scrolled around
+    max_screen_size: (u16, u16), // how many characters of board fit in the terminal
+    panel: Panel,
+    unicode_enabled: bool,
+    braille: bool, // draw the board with a braille dot for each cell instead of a character for each cell
+    graphics: Option<Graphics>, // Some if the terminal can draw images
+    pixels: bool, // draw the board as an image with a pixel for each cell (only if graphics is Some)
+    theme: usize, // index into THEMES
+    frame_delay: i16, // signed so we can check when it goes below 0 more easily
+    generations_per_frame: u32, // how many generations to advance between frames (for fast forwarding)
+    is_first_frame: bool, // for any setup that only occurs on the first frame
+    prompt: Option<Prompt>, // Some while a prompt is open over the board
+    help_shown: bool, // the ? overlay with all the keys
+    command_history: History, // commands run from the : prompt, this session and earlier ones
+    skip: Option<Skip>, // Some while skipping ahead
+    bookmarks: [Option<Bookmark>; 9], // saved with shift+1-9 and loaded with 1-9
+    symmetry: Symmetry, // which cells get edited along with the one under the cursor
+    brush: Brush,
+    shape: Shape, // what gets drawn from shape_start to the cursor
+    shape_start: Option<Point>, // Some after V has been pressed once
+    petri_dish: Option<PetriDish>,
+    puzzle: Option<Puzzle>, // Some with --puzzle
+    demo: Option<Demo>, // Some with --demo
+    rival_ai: Option<u32>, // Some when the AI plays player two in immigration, with how many generations until it next seeds some cells
+    vim_keys: bool, // --vim, h/j/k/l move the cursor and gg/G jump to the corners
+    count: String, // digits typed before a vim motion, like the 10 in 10l
+    pending_g: bool, // the first g of gg has been typed
+    pattern_of_the_day: Option<&'static BundledPattern>, // shown under the board if the game started with it
+    paint_state: Option<u8>, // picked with { and } in automata with more than 2 states, None to draw the usual state
+    clipboard: Option<Pattern>, // copied with Y
+    pasting: bool, // true while the clipboard is being moved around before it gets pasted
+    heatmap: Option<Heatmap>, // Some while the heatmap is shown
+    trails: Option<CellMap<u8>>, // Some while trails are turned on, has how many generations ago each recently dead cell died
+    library_index: Option<usize>, // which bundled pattern is in the clipboard, if it came from the pattern library
+    population_history: VecDeque<usize>, // population after each of the last POPULATION_HISTORY_LENGTH generations
+    loaded_file: Option<LoadedFile>,
+    screenshot_scale: u32, // pixels per cell in screenshots
+    rng: StdRng, // everything random comes from this so that replays get the same random boards
+    replaying: bool, // files don't get written while playing back a replay (it might have come from someone else)
+    message: String, // shown next to the frame time (for whatever's going on, like skipping or pasting)
+    toasts: VecDeque<Toast>, // notifications for things that just happened (like saving), oldest first
+    generations: u64, // how many generations have been run since the game started, skips included
+    peak_population: usize, // the highest population after any of those generations (or at the start)
+    infinite: bool, // --infinite, the board grows before anything reaches its edges
+    grid: Option<u32>, // Some while grid lines are drawn, every this many cells
+    follow: bool, // the view keeps the middle of the population in the middle of the screen
+    neighbour_counts: bool, // while paused every cell shows how many live neighbours it has
+    preview: bool, // while paused the cells that are about to be born or die are highlighted
+    memory_limit: Option<u64>, // in bytes, :resize won't make a board that could need more than this (None with --force)
+    edited: bool, // cells have been edited by hand since the board was last saved, cleared, randomized or loaded
+    confirm: bool, // ask before Q, R, C or Ctrl+W throw those edits away (turned off with --no-confirm)
+    tabs: Vec<Tab>, // the boards in the other tabs, in order without the one being shown
+    tab: usize, // which tab is being shown, counting all of them
+}
+
+
+// a board in a tab that isn't being shown, with everything that goes with it (the rest of GameState is shared by every tab)
+struct Tab {
+    board: IncrementalBoard, // each tab keeps track of its own changes, so switching back to one doesn't cost a full generation
+    cursor_position: Point,
+    viewport: Point,
+    loaded_file: Option<LoadedFile>,
+    petri_dish: Option<PetriDish>,
+    bookmarks: [Option<Bookmark>; 9],
+    population_history: VecDeque<usize>,
+    generations: u64,
+    peak_population: usize,
+    edited: bool,
+}
+
+
+// the file given with --load, kept around so that edits can be saved back to it
+struct LoadedFile {
+    path: String,
+    manifest: Option<Manifest>, // Some if the file was a manifest
+    offset: Point, // where the file's 0,0 is on the board
+}
+
+
+// a box over the board for typing something in
+struct Prompt {
+    kind: PromptKind,
+    text: String,
+    cursor: usize, // in characters from the start of the text
+    message: Option<String>, // why the last thing that was entered couldn't be used, or the options tab found
+}
+
+
+// what a prompt is asking for
+#[derive(PartialEq)]
+enum PromptKind {
+    Rule,
+    Skip, // a number of generations to skip ahead
+    Command, // one of command::COMMANDS, opened with :
+    Confirm(Key), // y/n before a key that would throw away hand edits, the key gets pressed again if it's y
+}
+
+
+impl Prompt {
+    fn new(kind: PromptKind, text: String) -> Prompt {
+        let cursor = text.chars().count();
+        return Prompt { kind, text, cursor, message: None };
+    }
+
+    // whether a character can be typed into this kind of prompt
+    fn accepts(&self, c: char) -> bool {
+        return match self.kind {
+            PromptKind::Rule => c.is_ascii_alphanumeric() || "/,-;.".contains(c), // the rest are for bigger neighbourhoods
+            PromptKind::Skip => c.is_ascii_digit(),
+            PromptKind::Command => !c.is_control(),
+            PromptKind::Confirm(_) => false, // it's answered by the first key
+        };
+    }
+
+    fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.text.insert(index, c);
+    }
+
+    // deletes the characters in range (counted in characters, not bytes)
+    fn remove(&mut self, range: std::ops::Range<usize>) {
+        let (start, end) = (self.byte_index(range.start), self.byte_index(range.end));
+        self.text.replace_range(start..end, "");
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        return self.text.char_indices().nth(char_index).map(|(index, _)| index).unwrap_or(self.text.len());
+    }
+}
+
+
+// mirrors edits around the middle of the board
+#[derive(Clone, Copy)]
+enum Symmetry {
+    Off,
+    MirrorX, // left to right
+    MirrorY, // top to bottom
+    FourFold, // both mirrors at once
+    Rotational, // half a turn around the middle
+}
+
+
+impl Symmetry {
+    // the order M cycles through them in
+    fn next(self) -> Symmetry {
+        return match self {
+            Symmetry::Off => Symmetry::MirrorX,
+            Symmetry::MirrorX => Symmetry::MirrorY,
+            Symmetry::MirrorY => Symmetry::FourFold,
+            Symmetry::FourFold => Symmetry::Rotational,
+            Symmetry::Rotational => Symmetry::Off,
+        };
+    }
+
+    fn name(self) -> &'static str {
+        return match self {
+            Symmetry::Off => "off",
+            Symmetry::MirrorX => "mirror left/right",
+            Symmetry::MirrorY => "mirror top/bottom",
+            Symmetry::FourFold => "4-fold",
+            Symmetry::Rotational => "rotational",
+        };
+    }
+
+    // cell and all of its mirror images (without any duplicates for cells on a mirror line)
+    fn symmetric_cells(self, cell: &Point, board: &Board) -> Vec<Point> {
+        let mirrored_x = board.width as i16 - 1 - cell.x;
+        let mirrored_y = board.height as i16 - 1 - cell.y;
+        let mut cells = vec![cell.clone()];
+        match self {
+            Symmetry::Off => (),
+            Symmetry::MirrorX => cells.push(Point{x: mirrored_x, y: cell.y}),
+            Symmetry::MirrorY => cells.push(Point{x: cell.x, y: mirrored_y}),
+            Symmetry::FourFold => cells.extend([
+                Point{x: mirrored_x, y: cell.y},
+                Point{x: cell.x, y: mirrored_y},
+                Point{x: mirrored_x, y: mirrored_y}
+            ]),
+            Symmetry::Rotational => cells.push(Point{x: mirrored_x, y: mirrored_y}),
+        }
+        cells.sort();
+        cells.dedup();
+        return cells;
+    }
+}
+
+
+// the shape of cells around the cursor that get edited
+#[derive(Clone, Copy)]
+enum Brush {
+    Single,
+    Square3, // 3x3
+    Square5, // 5x5
+    Circle, // radius 3
+}
+
+
+impl Brush {
+    fn next(self) -> Brush {
+        return match self {
+            Brush::Single => Brush::Square3,
+            Brush::Square3 => Brush::Square5,
+            Brush::Square5 => Brush::Circle,
+            Brush::Circle => Brush::Single,
+        };
+    }
+
+    fn name(self) -> &'static str {
+        return match self {
+            Brush::Single => "1x1",
+            Brush::Square3 => "3x3",
+            Brush::Square5 => "5x5",
+            Brush::Circle => "circle",
+        };
+    }
+
+    // where the brush's cells are relative to the cursor
+    fn offsets(self) -> Vec<(i16, i16)> {
+        let (radius, round) = match self {
+            Brush::Single => (0, false),
+            Brush::Square3 => (1, false),
+            Brush::Square5 => (2, false),
+            Brush::Circle => (3, true),
+        };
+        let mut offsets = Vec::new();
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if !round || x * x + y * y <= radius * radius + 1 { // +1 so the circle isn't all pointy at the top/bottom/sides
+                    offsets.push((x, y));
+                }
+            }
+        }
+        return offsets;
+    }
+}
+
+
+// what the line/rectangle tool can draw
+#[derive(Clone, Copy)]
+enum Shape {
+    Line,
+    Rectangle, // just the outline
+    FilledRectangle,
+}
+
+
+impl Shape {
+    fn next(self) -> Shape {
+        return match self {
+            Shape::Line => Shape::Rectangle,
+            Shape::Rectangle => Shape::FilledRectangle,
+            Shape::FilledRectangle => Shape::Line,
+        };
+    }
+
+    fn name(self) -> &'static str {
+        return match self {
+            Shape::Line => "line",
+            Shape::Rectangle => "rectangle",
+            Shape::FilledRectangle => "filled rectangle",
+        };
+    }
+
+    // all the cells in the shape going from start to end (both corners of a rectangle)
+    fn cells(self, start: &Point, end: &Point) -> Vec<Point> {
+        let mut cells = Vec::new();
+        match self {
+            Shape::Line => { // bresenham's line algorithm
+                let (dx, dy) = ((end.x - start.x).abs(), -(end.y - start.y).abs());
+                let (step_x, step_y) = ((end.x - start.x).signum(), (end.y - start.y).signum());
+                let mut error = dx + dy;
+                let mut cell = start.clone();
+                loop {
+                    cells.push(cell.clone());
+                    if cell == *end {
+                        break;
+                    }
+                    if 2 * error >= dy {
+                        error += dy;
+                        cell.x += step_x;
+                    }
+                    if 2 * error <= dx {
+                        error += dx;
+                        cell.y += step_y;
+                    }
+                }
+            }
+            Shape::Rectangle | Shape::FilledRectangle => {
+                let (left, right) = (start.x.min(end.x), start.x.max(end.x));
+                let (top, bottom) = (start.y.min(end.y), start.y.max(end.y));
+                for y in top..=bottom {
+                    for x in left..=right {
+                        let on_edge = x == left || x == right || y == top || y == bottom;
+                        if on_edge || matches!(self, Shape::FilledRectangle) {
+                            cells.push(Point{x, y});
+                        }
+                    }
+                }
+            }
+        }
+        return cells;
+    }
+}
+
+
+// where the legend, bookmarks and population go, the frame delay and messages always go on the line under everything else
+#[derive(PartialEq, Clone, Copy)]
+enum Panel {
+    Bottom,
+    Side, // to the right of the board, for wide terminals
+    Hidden,
+}
+
+
+impl Panel {
+    fn next(self) -> Panel {
+        return match self {
+            Panel::Bottom => Panel::Side,
+            Panel::Side => Panel::Hidden,
+            Panel::Hidden => Panel::Bottom,
+        };
+    }
+
+    fn name(self) -> &'static str {
+        return match self {
+            Panel::Bottom => "bottom",
+            Panel::Side => "side",
+            Panel::Hidden => "hidden",
+        };
+    }
+
+    fn parse(name: &str) -> Option<Panel> {
+        return [Panel::Bottom, Panel::Side, Panel::Hidden].iter().copied().find(|panel| panel.name() == name);
+    }
+
+    // how many columns and rows of the terminal aren't left for the board (its border, the panel and the status line)
+    fn reserved_size(self) -> (u16, u16) {
+        return match self {
+            Panel::Bottom => (2, PANEL_HEIGHT + 2),
+            Panel::Side => (SIDE_PANEL_WIDTH + 3, 3),
+            Panel::Hidden => (2, 3),
+        };
+    }
+}
+
+
+// how many characters of board fit in the terminal around the panel
+fn max_screen_size(panel: Panel) -> (u16, u16) {
+    let (reserved_width, reserved_height) = panel.reserved_size();
+    return terminal::terminal_size()
+        .map(|(width, height)| (width.saturating_sub(reserved_width), height.saturating_sub(reserved_height)))
+        .unwrap_or((u16::MAX, u16::MAX));
+}
+
+
+// the top left of the panel and how wide it is, None while it's hidden
+fn panel_position(board: &Board, game_state: &GameState) -> Option<(u16, u16, usize)> {
+    let (screen_width, screen_height) = screen_size(board, game_state);
+    return match game_state.panel {
+        Panel::Bottom => Some((2, screen_height + 3, screen_width as usize)),
+        Panel::Side => Some((screen_width + 4, 2, SIDE_PANEL_WIDTH as usize)),
+        Panel::Hidden => None,
+    };
+}
+
+
+// the line with the frame delay and messages on it, under the board and the panel
+fn status_line(board: &Board, game_state: &GameState) -> u16 {
+    let (_, screen_height) = screen_size(board, game_state);
+    return match game_state.panel {
+        Panel::Bottom => screen_height + PANEL_HEIGHT + 2,
+        Panel::Side => (screen_height + 3).max(PANEL_HEIGHT + 1), // the panel can be taller than the board
+        Panel::Hidden => screen_height + 3,
+    };
+}
+
+
+// copies every cell in the rectangle with corners at start and end
+fn copy_area(board: &Board, start: &Point, end: &Point) -> Pattern {
+    let (left, top) = (start.x.min(end.x), start.y.min(end.y));
+    let (right, bottom) = (start.x.max(end.x), start.y.max(end.y));
+    let cells = board.cells.iter()
+        .filter(|(cell, _)| cell.x >= left && cell.x <= right && cell.y >= top && cell.y <= bottom)
+        .map(|(cell, &state)| (Point{x: cell.x - left, y: cell.y - top}, state))
+        .collect();
+    return Pattern {
+        width: (right - left + 1) as u32,
+        height: (bottom - top + 1) as u32,
+        cells,
+        rule: None
+    };
+}
+
+
+// keys that mean something different while pasting, returns false if the key should be handled normally instead
+fn handle_paste_key_press(key: Key, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) -> bool {
+    let clipboard = game_state.clipboard.as_mut().unwrap();
+    match key {
+        Key::Char('r') | Key::Char('R') => *clipboard = clipboard.rotated_clockwise(),
+        Key::Char('h') | Key::Char('H') => *clipboard = clipboard.flipped_horizontally(),
+        Key::Char('v') | Key::Char('V') => *clipboard = clipboard.flipped_vertically(),
+        Key::Char('l') | Key::Char('L') if game_state.library_index.is_some() => { // on to the next pattern in the library
+            let index = (game_state.library_index.unwrap() + 1) % BUNDLED_PATTERNS.len();
+            take_from_library(index, game_state);
+            frame_state.message_updated = true;
+        }
+        Key::Char('\n') => {
+            board.place_pattern(clipboard, &game_state.cursor_position);
+            game_state.pasting = false;
+            game_state.edited = true;
+            game_state.message = String::new();
+            frame_state.message_updated = true;
+        }
+        Key::Esc => {
+            game_state.pasting = false;
+            game_state.message = String::new();
+            frame_state.message_updated = true;
+        }
+        _ => return false // including the arrow keys, they move the cursor (and the ghost with it) like normal
+    }
+    frame_state.board_updated = true;
+    return true;
+}
+
+
+// how many states can be picked with { and } (no more than 9 so the legend still fits)
+fn num_paintable_states(board: &Board) -> u8 {
+    return match board.automaton {
+        Automaton::Life => (board.rule.num_states - 1).min(9),
+        Automaton::Wireworld => CONDUCTOR,
+        Automaton::LangtonsAnt | Automaton::Margolus(_) => 1,
+        Automaton::Immigration => PLAYER_TWO,
+        Automaton::Table => board.table.as_ref().map_or(1, |table| (table.num_states - 1).min(9) as u8),
+    };
+}
+
+
+// puts one of the bundled patterns in the clipboard and starts pasting it
+fn take_from_library(index: usize, game_state: &mut GameState) {
+    let bundled_pattern = &BUNDLED_PATTERNS[index];
+    game_state.clipboard = Some(pattern::parse_rle(bundled_pattern.rle).unwrap()); // bundled patterns are always valid
+    game_state.library_index = Some(index);
+    game_state.pasting = true;
+    game_state.message = format!("Stamping {} ({}/{}): L next, R rotate, H/V flip, Enter stamp, Esc cancel", bundled_pattern.name, index + 1, BUNDLED_PATTERNS.len());
+}
+
+
+// the state that A and the shape tool put down
+fn drawn_state(board: &Board, game_state: &GameState) -> u8 {
+    return match game_state.paint_state {
+        Some(state) if state <= num_paintable_states(board) => state, // the rule could have changed to one with fewer states since it was picked
+        _ => match board.automaton {
+            Automaton::Wireworld => CONDUCTOR,
+            _ => ALIVE, // dying cells get revived
+        }
+    };
+}
+
+
+fn state_name(board: &Board, state: u8) -> String {
+    return match (board.automaton, state) {
+        (Automaton::Wireworld, ELECTRON_HEAD) => "Electron head".to_string(),
+        (Automaton::Wireworld, ELECTRON_TAIL) => "Electron tail".to_string(),
+        (Automaton::Wireworld, _) => "Conductor".to_string(),
+        (Automaton::Table, state) => format!("State {}", state),
+        (Automaton::Immigration, state) => format!("Player {}", state),
+        (_, ALIVE) => "Alive".to_string(),
+        (_, state) => format!("Dying {}", state - ALIVE),
+    };
+}
+
+
+// every cell on the board under the brush (and its mirror images if symmetry is on)
+fn brushed_cells(board: &Board, game_state: &GameState) -> Vec<Point> {
+    let cursor = &game_state.cursor_position;
+    let mut cells: Vec<Point> = game_state.brush.offsets().into_iter()
+        .map(|(x, y)| Point{x: cursor.x + x, y: cursor.y + y})
+        .filter(|cell| cell.x >= 0 && cell.y >= 0 && cell.x < board.width as i16 && cell.y < board.height as i16)
+        .flat_map(|cell| game_state.symmetry.symmetric_cells(&cell, board))
+        .collect();
+    cells.sort();
+    cells.dedup();
+    return cells;
+}
+
+
+// a notification in the corner of the board
+struct Toast {
+    text: String,
+    shown: time::Instant,
+}
+
+
+// a copy of the board that can be gone back to
+struct Bookmark {
+    cells: CellMap<u8>,
+    ants: Vec<Ant>,
+}
+
+
+// --demo shows each of DEMO_PATTERNS for DEMO_GENERATIONS generations, then moves on to the next one
+struct Demo {
+    index: usize, // into DEMO_PATTERNS
+    generations: u32, // how long the current one has been running
+}
+
+
+// generations are computed in chunks between frames while skipping so that the progress can be shown (and so it can be cancelled)
+struct Skip {
+    done: u32,
+    total: u32,
+    started: time::Instant, // for the spinner, which goes by the clock so it keeps the same speed however long each chunk takes
+}
+
+
+// stored seperately from GameState because these variables must be reset to defaults (false) every frame
+struct FrameState {
+    board_updated: bool,
+    frame_delay_updated: bool, // also covers generations_per_frame and the paint state since they're printed together
+    legend_updated: bool,
+    message_updated: bool,
+    bookmarks_updated: bool,
+    layout_updated: bool, // everything gets reprinted (in the new colours or for the new board size)
+    petri_dish_toggled: bool, // so the side panel gets redrawn without the report after petri dish mode is turned off
+}
+
+
+// while a prompt is open every key goes to it instead of the game
+// the text can be edited like a shell's (arrows, home/end, ctrl+a/e/u/k/w) and the command prompt also has history and tab completion
+fn handle_prompt_key_press(key: Key, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) {
+    let prompt = game_state.prompt.as_mut().unwrap();
+    if let PromptKind::Confirm(confirmed_key) = prompt.kind { // any key answers it
+        game_state.prompt = None;
+        frame_state.board_updated = true; // get rid of the overlay
+        if matches!(key, Key::Char('y') | Key::Char('Y')) {
+            game_state.edited = false; // they're being thrown away on purpose, so the key won't ask again
+            if matches!(confirmed_key, Key::Char('q') | Key::Char('Q')) { // along with the ones in every other tab
+                for tab in &mut game_state.tabs {
+                    tab.edited = false;
+                }
+            }
+            handle_key_press(confirmed_key, board, game_state, frame_state);
+        }
+        return;
+    }
+    let length = prompt.text.chars().count();
+    match key {
+        Key::Char('\n') => { // apply whatever was typed
+            let applied = match prompt.kind {
+                PromptKind::Rule => Rule::parse(&prompt.text).map(|rule| board.rule = rule),
+                PromptKind::Skip => match prompt.text.parse::<u32>() {
+                    Ok(total) if total > 0 => {
+                        game_state.skip = Some(Skip { done: 0, total, started: time::Instant::now() });
+                        Ok(())
+                    }
+                    _ => Err("Enter a number of generations".to_string())
+                },
+                PromptKind::Command => {
+                    let text = prompt.text.trim().to_string();
+                    if let Err(message) = game_state.command_history.add(&text) { // even if it fails, so it can be fixed up from the history
+                        notify(message, game_state, frame_state);
+                    }
+                    command::parse(&text).and_then(|command| run_command(command, board, game_state, frame_state))
+                }
+                PromptKind::Confirm(_) => Ok(()) // answered by whatever key is pressed first, so enter never gets here
+            };
+            match applied {
+                Ok(()) => {
+                    if game_state.prompt.as_ref().is_some_and(|prompt| prompt.kind == PromptKind::Rule) {
+                        notify(format!("Rule set to {}", board.rule), game_state, frame_state);
+                    }
+                    game_state.prompt = None;
+                    frame_state.board_updated = true; // get rid of the overlay
+                    frame_state.legend_updated = true; // the number of states might have changed
+                    frame_state.frame_delay_updated = true;
+                }
+                Err(message) => game_state.prompt.as_mut().unwrap().message = Some(message)
+            }
+            return;
+        }
+        Key::Esc => {
+            game_state.prompt = None;
+            frame_state.board_updated = true;
+            return;
+        }
+        Key::Left | Key::Ctrl('b') => prompt.cursor = prompt.cursor.saturating_sub(1),
+        Key::Right | Key::Ctrl('f') => prompt.cursor = (prompt.cursor + 1).min(length),
+        Key::Home | Key::Ctrl('a') => prompt.cursor = 0,
+        Key::End | Key::Ctrl('e') => prompt.cursor = length,
+        Key::Backspace if prompt.cursor > 0 => {
+            prompt.cursor -= 1;
+            prompt.remove(prompt.cursor..prompt.cursor + 1);
+        }
+        Key::Delete | Key::Ctrl('d') if prompt.cursor < length => prompt.remove(prompt.cursor..prompt.cursor + 1),
+        Key::Ctrl('u') => { // delete everything before the cursor
+            prompt.remove(0..prompt.cursor);
+            prompt.cursor = 0;
+        }
+        Key::Ctrl('k') => prompt.remove(prompt.cursor..length), // and everything after it
+        Key::Ctrl('w') => { // the word before the cursor
+            let before: Vec<char> = prompt.text.chars().take(prompt.cursor).collect();
+            let spaces = before.iter().rev().take_while(|c| **c == ' ').count();
+            let word = before.iter().rev().skip(spaces).take_while(|c| **c != ' ').count();
+            let word_start = prompt.cursor - spaces - word;
+            prompt.remove(word_start..prompt.cursor);
+            prompt.cursor = word_start;
+        }
+        Key::Up | Key::Down if prompt.kind == PromptKind::Command => {
+            let entry = match key {
+                Key::Up => game_state.command_history.older(),
+                _ => game_state.command_history.newer()
+            };
+            if let Some(entry) = entry {
+                prompt.text = entry.to_string();
+                prompt.cursor = prompt.text.chars().count();
+            }
+        }
+        Key::Char('\t') if prompt.kind == PromptKind::Command => {
+            let before_cursor: String = prompt.text.chars().take(prompt.cursor).collect();
+            let after_cursor: String = prompt.text.chars().skip(prompt.cursor).collect();
+            prompt.message = match command::complete(&before_cursor) {
+                Some(completion) => {
+                    prompt.cursor = completion.text.chars().count();
+                    prompt.text = completion.text + &after_cursor;
+                    Some(completion.options.join("  ")).filter(|options| !options.is_empty())
+                }
+                None => Some("Nothing to complete".to_string())
+            };
+            return;
+        }
+        Key::Char(c) if prompt.accepts(c) => {
+            prompt.insert(c);
+            prompt.cursor += 1;
+        }
+        _ => return
+    }
+    prompt.message = None; // the error (or the list of completions) was about the old text
+}
+
+
+// does what was typed after :, the prompt stays open with the error if it fails
+// for loading from the command line, swaps the board for a new one with the pattern in it
+fn replace_board(path: String, pattern: &Pattern, manifest: Option<Manifest>, board: &mut Board, game_state: &mut GameState, frame_state: &mut FrameState) -> Result<LoadedFile, String> {
+    let mut loaded_board = Board::new(board.width, board.height, board.automaton, board.rule.clone()); // so nothing changes if the rule is bad
+    loaded_board.table = board.table.clone();
+    let loaded_file = load_file(&mut loaded_board, path, pattern, manifest, false)?;
+    *board = loaded_board; // the new board doesn't have any petri dish walls
+    frame_state.petri_dish_toggled |= game_state.petri_dish.take().is_some();
+    game_state.edited = false;
+    clear_history(game_state);
+    return Ok(loaded_file);
+}
+
+
+// how much memory each cell could need the way the board's being drawn, for checking :resize against --memory-limit
+fn bytes_per_cell(game_state: &GameState) -> u64 {
+    return SIMULATION_BYTES_PER_CELL + if game_state.pixels { PIXEL_BYTES_PER_CELL } else { PRINTED_BYTES_PER_CELL };
+}
+
+
+fn run_command(command: Command, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) -> Result<(), String> {
+    match command {
+        Command::Rule(rule) if uses_rule(board.automaton) => {
+            notify(format!("Rule set to {}", rule), game_state, frame_state);
+            board.rule = rule;
+        }
+        Command::Rule(_) => return Err("Rules only work in Life".to_string()),
+        Command::Skip(total) => game_state.skip = Some(Skip { done: 0, total, started: time::Instant::now() }),
+        Command::Load(path) => {
+            let (pattern, manifest) = read_load_file(&path)?;
+            let loaded_file = replace_board(path, &pattern, manifest, board, game_state, frame_state)?;
+            notify(load_fit_message(board, &loaded_file, &pattern, board.width, board.height), game_state, frame_state);
+            game_state.loaded_file = Some(loaded_file);
+        }
+        Command::LoadBundled(_) if board.automaton != Automaton::Life => return Err("Bundled patterns only work in Life".to_string()),
+        Command::LoadBundled(index) => {
+            let bundled_pattern = &BUNDLED_PATTERNS[index];
+            let pattern = pattern::parse_rle(bundled_pattern.rle).unwrap(); // bundled patterns are always valid
+            replace_board(bundled_pattern.name.to_string(), &pattern, None, board, game_state, frame_state)?;
+            notify(format!("Loaded {}", bundled_pattern.name), game_state, frame_state);
+            game_state.loaded_file = None; // there's no file for W to save it back to
+        }
+        Command::Save(path) => {
+            let saved = match (path, &game_state.loaded_file) {
+                (Some(path), _) => {
+                    let loaded_file = LoadedFile { path, manifest: None, offset: Point{x: 0, y: 0} };
+                    let saved = save_loaded_file(board, &loaded_file, game_state.replaying)?;
+                    game_state.loaded_file = Some(loaded_file); // W saves here from now on
+                    saved
+                }
+                (None, Some(loaded_file)) => save_loaded_file(board, loaded_file, game_state.replaying)?,
+                (None, None) => return Err("Usage: save <file> (nothing was loaded)".to_string())
+            };
+            game_state.edited = false;
+            notify(saved, game_state, frame_state);
+        }
+        Command::Stamp(_) if board.automaton != Automaton::Life => return Err("Bundled patterns only work in Life".to_string()),
+        Command::Stamp(index) => {
+            take_from_library(index, game_state);
+            frame_state.message_updated = true;
+        }
+        Command::StampApgcode(_, _) if board.automaton != Automaton::Life => return Err("apgcodes only work in Life".to_string()),
+        Command::StampApgcode(apgcode, pattern) => {
+            game_state.clipboard = Some(pattern);
+            game_state.library_index = None;
+            game_state.pasting = true;
+            game_state.message = format!("Stamping {}: R rotate, H/V flip, Enter stamp, Esc cancel", apgcode);
+            frame_state.message_updated = true;
+        }
+        Command::Theme(index) => {
+            game_state.theme = index;
+            notify(format!("Theme: {}", THEMES[index].name), game_state, frame_state);
+            frame_state.layout_updated = true;
+        }
+        Command::Grid(spacing) => {
+            game_state.grid = spacing;
+            notify(match spacing {
+                Some(spacing) => format!("Grid lines every {} cells", spacing),
+                None => "Grid lines: off".to_string()
+            }, game_state, frame_state);
+            frame_state.layout_updated = true; // puts the top border back without the column numbers
+        }
+        Command::ToggleGrid => {
+            let spacing = match game_state.grid {
+                Some(_) => None,
+                None => Some(DEFAULT_GRID_SPACING)
+            };
+            return run_command(Command::Grid(spacing), board, game_state, frame_state);
+        }
+        Command::Goto(point) if point.x < 0 || point.y < 0 || point.x >= board.width as i16 || point.y >= board.height as i16 => {
+            return Err(format!("{},{} is off the {}x{} board", point.x, point.y, board.width, board.height));
+        }
+        Command::Goto(point) => {
+            game_state.cursor_position = point;
+            frame_state.board_updated |= game_state.pasting; // move the ghost
+        }
+        Command::Seed(seed) => {
+            board.init_from_seed(seed);
+            game_state.edited = false;
+            clear_history(game_state);
+            if let Some(petri_dish) = &mut game_state.petri_dish {
+                petri_dish.record(board);
+            }
+        }
+        Command::Resize(width, height) if width > i16::MAX as u32 || height > i16::MAX as u32 => return Err(format!("Boards can't be over {} cells across", i16::MAX)),
+        Command::Resize(width, height) if game_state.memory_limit.is_some_and(|limit| width as u64 * height as u64 * bytes_per_cell(game_state) > limit) => {
+            return Err(format!("{}x{} could need more than --memory-limit", width, height));
+        }
+        Command::Resize(width, height) => {
+            if let Some(petri_dish) = game_state.petri_dish.take() { // its compartments were made for the old size
+                petri_dish.remove_walls(board);
+                frame_state.petri_dish_toggled = true;
+            }
+            board.resize(width, height);
+            if let Some(heatmap) = &mut game_state.heatmap {
+                heatmap.clear();
+            }
+            game_state.viewport = Point{x: 0, y: 0}; // it gets scrolled back to the cursor
+            frame_state.layout_updated = true; // the borders move
+            notify(format!("Resized the board to {}x{}", width, height), game_state, frame_state);
+        }
+        Command::Clear => {
+            board.clear();
+            game_state.edited = false;
+            clear_history(game_state);
+        }
+        Command::Census if board.automaton != Automaton::Life => return Err("The census only works in Life".to_string()),
+        Command::Census => {
+            let objects = objects::find_objects(board);
+            let counts: Vec<String> = objects::census(&objects).iter().map(|(count, object)| format!("{} {}", count, object.label())).collect();
+            game_state.message = match counts.is_empty() {
+                true => "Census: the board is empty".to_string(),
+                false => format!("Census: {}", counts.join(", "))
+            };
+            frame_state.message_updated = true;
+        }
+        Command::Quit => game_state.game_running = false,
+    }
+    return Ok(());
+}
+
+
+// computes as many generations of a skip as fit in SKIP_TIME_PER_FRAME, the board only gets printed once it's done
+// unless generations is given, then exactly that many get done (so a replay does the same amount each frame as the recording did, however fast it is)
+// returns how many generations got done
+fn continue_skip(board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState, generations: Option<u32>) -> u32 {
+    let mut skip = game_state.skip.take().unwrap(); // put back below unless it's finished
+    let start = time::Instant::now();
+    let mut done = 0;
+    while skip.done < skip.total && generations.map(|generations| done < generations).unwrap_or_else(|| start.elapsed() < SKIP_TIME_PER_FRAME) {
+        board.update_cells();
+        skip.done += 1;
+        done += 1;
+        grow_board(board, game_state);
+    }
+    game_state.generations += done as u64;
+    if skip.done == skip.total {
+        game_state.message = format!("Skipped {} generations", skip.total);
+        game_state.peak_population = game_state.peak_population.max(board.population()); // only the population at the end of a skip counts, checking every generation would slow it down
+        clear_history(game_state);
+        if let Some(petri_dish) = &mut game_state.petri_dish {
+            petri_dish.record(board);
+        }
+        frame_state.board_updated = true;
+    } else {
+        let spinner_chars: &[char] = if game_state.unicode_enabled { &SPINNER_CHARS_UNICODE } else { &SPINNER_CHARS_ASCII };
+        let spinner_frame = (skip.started.elapsed().as_millis() / SPINNER_FRAME_TIME.as_millis()) as usize % spinner_chars.len();
+        game_state.message = format!("Skipping {} {}/{} (Esc to stop)", spinner_chars[spinner_frame], skip.done, skip.total);
+        game_state.skip = Some(skip);
+    }
+    frame_state.message_updated = true;
+    return done;
+}
+
+
+// trails and the heatmap only make sense for cells that changed one generation at a time
+fn clear_history(game_state: &mut GameState) {
+    if let Some(trails) = &mut game_state.trails {
+        trails.clear();
+    }
+    if let Some(heatmap) = &mut game_state.heatmap {
+        heatmap.clear();
+    }
+}
+
+
+// advances the board by one generation and records anything that's tracked per generation
+fn advance_generation(board: &mut IncrementalBoard, game_state: &mut GameState) {
+    let previous_cells: Vec<Point> = match game_state.trails {
+        Some(_) => board.cells.keys().cloned().collect(),
+        None => Vec::new()
+    };
+    board.update_cells();
+    game_state.generations += 1;
+    if let Some(heatmap) = &mut game_state.heatmap {
+        heatmap.record(board);
+    }
+    if let Some(trails) = &mut game_state.trails {
+        trails.retain(|cell, age| {
+            *age += 1;
+            return *age < TRAIL_LENGTH && !board.cells.contains_key(cell);
+        });
+        for cell in previous_cells {
+            if !board.cells.contains_key(&cell) {
+                trails.insert(cell, 0);
+            }
+        }
+    }
+    if let Some(petri_dish) = &mut game_state.petri_dish {
+        petri_dish.record(board);
+    }
+    if let Some(puzzle) = &mut game_state.puzzle {
+        if let Some((cells, message)) = puzzle.generation(board) {
+            board.cells = cells;
+            game_state.paused = true;
+            game_state.message = message;
+        }
+    }
+    if let Some(demo) = &mut game_state.demo {
+        demo.generations += 1;
+        if demo.generations >= DEMO_GENERATIONS {
+            let next = (demo.index + 1) % DEMO_PATTERNS.len();
+            show_demo_pattern(next, board, game_state);
+        }
+    }
+    if let Some(countdown) = &mut game_state.rival_ai {
+        if *countdown == 0 {
+            seed_rival(board, &mut game_state.rng);
+            *countdown = AI_SEED_INTERVAL;
+        } else {
+            *countdown -= 1;
+        }
+    }
+    grow_board(board, game_state);
+    let population = board.population();
+    game_state.peak_population = game_state.peak_population.max(population);
+    game_state.population_history.push_back(population);
+    if game_state.population_history.len() > POPULATION_HISTORY_LENGTH {
+        game_state.population_history.pop_front();
+    }
+}
+
+
+// --infinite: grows the board before anything reaches an edge, and if it grew left or up then everything that points at cells moves along with them
+fn grow_board(board: &mut IncrementalBoard, game_state: &mut GameState) {
+    if !game_state.infinite || game_state.petri_dish.is_some() { // the petri dish's compartments are walled off anyway
+        return;
+    }
+    let (width, height) = viewport_size(board, game_state);
+    let margin = match game_state.follow {
+        true => INFINITE_MARGIN.max((width.max(height) / 2 + 1) as i16), // room for the view to keep the population in the middle
+        false => INFINITE_MARGIN
+    };
+    let shift = board.grow_to_fit(margin);
+    if shift.x == 0 && shift.y == 0 {
+        return;
+    }
+    let moved = |cell: &Point| Point{x: cell.x + shift.x, y: cell.y + shift.y};
+    game_state.cursor_position = moved(&game_state.cursor_position);
+    game_state.viewport = moved(&game_state.viewport);
+    game_state.shape_start = game_state.shape_start.as_ref().map(moved);
+    if let Some(trails) = &mut game_state.trails {
+        *trails = trails.iter().map(|(cell, &age)| (moved(cell), age)).collect();
+    }
+    if let Some(heatmap) = &mut game_state.heatmap {
+        heatmap.clear(); // it fills back up soon enough
+    }
+    for bookmark in game_state.bookmarks.iter_mut().flatten() {
+        bookmark.cells = bookmark.cells.iter().map(|(cell, &state)| (moved(cell), state)).collect();
+        for ant in bookmark.ants.iter_mut() {
+            ant.position = moved(&ant.position);
+        }
+    }
+    if let Some(loaded_file) = &mut game_state.loaded_file {
+        loaded_file.offset = moved(&loaded_file.offset); // so that saving puts the file's cells back where they were
+    }
+}
+
+
+fn demo_pattern(index: usize) -> &'static BundledPattern {
+    return bundled::find(DEMO_PATTERNS[index]).unwrap(); // the demo patterns are all bundled
+}
+
+
+// clears the board and puts one of the demo patterns in the middle of it
+fn show_demo_pattern(index: usize, board: &mut Board, game_state: &mut GameState) {
+    let bundled_pattern = demo_pattern(index);
+    let pattern = pattern::parse_rle(bundled_pattern.rle).unwrap(); // bundled patterns are always valid
+    board.clear();
+    board.automaton = Automaton::Life; // they're all life patterns
+    board.rule = Rule::conways_life();
+    board.place_pattern(&pattern, &Point{
+        x: (board.width as i16 - pattern.width as i16) / 2,
+        y: (board.height as i16 - pattern.height as i16) / 2
+    });
+    clear_history(game_state);
+    game_state.population_history.clear();
+    game_state.demo = Some(Demo { index, generations: 0 });
+    game_state.message = format!("Demo: {}", bundled_pattern.name);
+}
+
+
+// the AI's move in immigration: a random patch of player two's cells dropped near one of player one's cells (anywhere if player one has none)
+fn seed_rival(board: &mut Board, rng: &mut StdRng) {
+    let mut targets: Vec<Point> = board.cells.iter()
+        .filter(|(_, &state)| state == PLAYER_ONE)
+        .map(|(cell, _)| cell.clone())
+        .collect();
+    targets.sort(); // the cells come out of the map in a different order every run, which would break replays
+    let centre = match targets.is_empty() {
+        true => Point{x: rng.gen_range(0..board.width as i16), y: rng.gen_range(0..board.height as i16)},
+        false => {
+            let target = &targets[rng.gen_range(0..targets.len())];
+            Point{x: target.x + rng.gen_range(-AI_SEED_DISTANCE..=AI_SEED_DISTANCE), y: target.y + rng.gen_range(-AI_SEED_DISTANCE..=AI_SEED_DISTANCE)}
+        }
+    };
+    for y in (centre.y - AI_SEED_SIZE / 2)..(centre.y - AI_SEED_SIZE / 2 + AI_SEED_SIZE) {
+        for x in (centre.x - AI_SEED_SIZE / 2)..(centre.x - AI_SEED_SIZE / 2 + AI_SEED_SIZE) {
+            let cell = Point{x, y};
+            let on_board = x >= 0 && y >= 0 && x < board.width as i16 && y < board.height as i16;
+            if rng.gen_bool(0.5) && on_board && !board.cells.contains_key(&cell) && !board.walls.contains(&cell) {
+                board.cells.insert(cell, PLAYER_TWO);
+            }
+        }
+    }
+}
+
+
+// pops up a message in the top right corner of the board, for things that happened rather than things that are going on (those go on the status line)
+fn notify(text: String, game_state: &mut GameState, frame_state: &mut FrameState) {
+    game_state.toasts.push_back(Toast { text, shown: time::Instant::now() });
+    if game_state.toasts.len() > MAX_TOASTS {
+        game_state.toasts.pop_front();
+    }
+    frame_state.board_updated = true; // so whatever was under the old toasts gets drawn again
+}
+
+
+fn save_bookmark(slot: usize, board: &Board, game_state: &mut GameState, frame_state: &mut FrameState) {
+    game_state.bookmarks[slot] = Some(Bookmark {
+        cells: board.cells.clone(),
+        ants: board.ants.clone()
+    });
+    notify(format!("Saved bookmark {}", slot + 1), game_state, frame_state);
+    frame_state.bookmarks_updated = true;
+}
+
+
+// puzzles and demos only have the one board, and replays have to play out on the board they were recorded on
+fn tabs_allowed(game_state: &GameState) -> bool {
+    return game_state.puzzle.is_none() && game_state.demo.is_none() && !game_state.replaying;
+}
+
+
+// swaps the board being shown (and everything that goes with it) with the one in tab
+fn swap_tab(tab: &mut Tab, board: &mut IncrementalBoard, game_state: &mut GameState) {
+    mem::swap(&mut tab.board, board);
+    mem::swap(&mut tab.cursor_position, &mut game_state.cursor_position);
+    mem::swap(&mut tab.viewport, &mut game_state.viewport);
+    mem::swap(&mut tab.loaded_file, &mut game_state.loaded_file);
+    mem::swap(&mut tab.petri_dish, &mut game_state.petri_dish);
+    mem::swap(&mut tab.bookmarks, &mut game_state.bookmarks);
+    mem::swap(&mut tab.population_history, &mut game_state.population_history);
+    mem::swap(&mut tab.generations, &mut game_state.generations);
+    mem::swap(&mut tab.peak_population, &mut game_state.peak_population);
+    mem::swap(&mut tab.edited, &mut game_state.edited);
+}
+
+
+// everything gets redrawn for the board that's just come up (which might be a different size), trails and the heatmap start over
+fn show_tab(game_state: &mut GameState, frame_state: &mut FrameState) {
+    clear_history(game_state);
+    game_state.shape_start = None;
+    frame_state.layout_updated = true;
+    frame_state.petri_dish_toggled = true;
+    frame_state.bookmarks_updated = true;
+    notify(format!("Tab {}/{}", game_state.tab + 1, game_state.tabs.len() + 1), game_state, frame_state);
+}
+
+
+// Ctrl+T: a copy of the board in a new tab after this one, which is where editing carries on (the copy doesn't get the petri dish or the loaded file)
+fn new_tab(board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) {
+    let cells = board.width as u64 * board.height as u64 * (game_state.tabs.len() as u64 + 2);
+    if game_state.memory_limit.is_some_and(|limit| cells * bytes_per_cell(game_state) > limit) {
+        notify("Another tab of this size would go over the memory limit (see --memory-limit)".to_string(), game_state, frame_state);
+        return;
+    }
+    let mut copy = board.clone();
+    if let Some(petri_dish) = &game_state.petri_dish {
+        petri_dish.remove_walls(&mut copy);
+    }
+    let mut tab = Tab {
+        board: IncrementalBoard::new(copy),
+        cursor_position: game_state.cursor_position.clone(),
+        viewport: game_state.viewport.clone(),
+        loaded_file: None,
+        petri_dish: None,
+        bookmarks: Default::default(),
+        population_history: game_state.population_history.clone(),
+        generations: game_state.generations,
+        peak_population: game_state.peak_population,
+        edited: game_state.edited,
+    };
+    swap_tab(&mut tab, board, game_state);
+    game_state.tabs.insert(game_state.tab, tab);
+    game_state.tab += 1;
+    show_tab(game_state, frame_state);
+}
+
+
+// shows the tab at index (counting all of them), game_state.tabs stays in order with the one that was being shown put back in its place
+fn switch_tab(index: usize, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) {
+    let shown = game_state.tab;
+    let mut tab = game_state.tabs.remove(if index < shown { index } else { index - 1 });
+    swap_tab(&mut tab, board, game_state);
+    game_state.tabs.insert(if shown < index { shown } else { shown - 1 }, tab);
+    game_state.tab = index;
+    show_tab(game_state, frame_state);
+}
+
+
+// Ctrl+W: throws away the tab being shown and shows the next one (or the one before if it was the last)
+fn close_tab(board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) {
+    if game_state.tabs.is_empty() {
+        notify("Can't close the only tab".to_string(), game_state, frame_state);
+        return;
+    }
+    let index = game_state.tab.min(game_state.tabs.len() - 1);
+    let mut tab = game_state.tabs.remove(index);
+    swap_tab(&mut tab, board, game_state);
+    game_state.tab = index;
+    frame_state.petri_dish_toggled |= tab.petri_dish.is_some();
+    show_tab(game_state, frame_state);
+}
+
+
+// writes the board back to the file it was loaded from (or to each of its pieces if it was a manifest)
+fn save_loaded_file(board: &Board, loaded_file: &LoadedFile, replaying: bool) -> Result<String, String> {
+    if loaded_file.path == STDIN_PATH {
+        return Err("The board came from stdin, save it with :save <file>".to_string());
+    }
+    if pattern::is_apgcode(&loaded_file.path) && !std::path::Path::new(&loaded_file.path).exists() {
+        return Err(format!("{} is an apgcode, save it with :save <file>", loaded_file.path));
+    }
+    if replaying {
+        return Ok(format!("Didn't save {} (replaying)", loaded_file.path));
+    }
+    if let Some(manifest) = &loaded_file.manifest {
+        let num_saved = manifest.save_components(board, &loaded_file.offset)?;
+        return Ok(format!("Saved {} of {} pieces", num_saved, manifest.components.len()));
+    }
+    let cells = board.cells.iter()
+        .filter(|(_, &state)| state == ALIVE || matches!(board.automaton, Automaton::Wireworld | Automaton::Table | Automaton::Immigration)) // dying cells can't be saved
+        .map(|(cell, &state)| (cell.clone(), state))
+        .collect();
+    let mut pattern = Pattern::from_cells(cells, None);
+    if uses_rule(board.automaton) {
+        pattern.rule = Some(board.rule.to_string());
+    }
+    let format = PatternFormat::from_path(&loaded_file.path).unwrap_or(PatternFormat::Rle);
+    fs::write(&loaded_file.path, pattern::write_pattern(&pattern, format))
+        .map_err(|error| format!("couldn't write {}: {}", loaded_file.path, error))?;
+    return Ok(format!("Saved {}", loaded_file.path));
+}
+
+
+fn recovery_path() -> Option<String> {
+    let home = env::var("HOME").ok()?;
+    return Some(std::path::Path::new(&home).join(RECOVERY_FILE_NAME).to_string_lossy().to_string());
+}
+
+
+// the board gets written here however the game ends (quitting, a signal or a panic) so that --recover can bring it back, returns where it went
+fn save_recovery_file(board: &Board) -> Result<String, String> {
+    let path = recovery_path().ok_or("couldn't save the board for --recover, HOME isn't set")?;
+    let loaded_file = LoadedFile { path, manifest: None, offset: Point{x: 0, y: 0} };
+    save_loaded_file(board, &loaded_file, false)?;
+    return Ok(loaded_file.path);
+}
+
+
+// --vim: h/j/k/l (or the arrow keys) move the cursor, gg jumps to the top left corner and G to the bottom right
+// they can all have a count typed before them, 10l moves 10 cells right and 5G jumps to row 5 like in vim
+// returns whether the key got used, anything else goes on to do what it usually does
+fn handle_vim_key_press(key: Key, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) -> bool {
+    let pending_g = std::mem::replace(&mut game_state.pending_g, false);
+    let count = match game_state.count.is_empty() {
+        true => None,
+        false => Some(game_state.count.parse::<u32>().unwrap_or(u32::MAX).min(i16::MAX as u32) as i16) // the cursor gets kept on the board afterwards anyway
+    };
+    let cursor = &mut game_state.cursor_position;
+    match key {
+        Key::Char(digit @ '0'..='9') if digit != '0' || count.is_some() => { // a 0 on its own isn't a count
+            game_state.count.push(digit);
+            game_state.message = format!("Count: {}", game_state.count);
+            frame_state.message_updated = true;
+            return true;
+        }
+        Key::Char('h') | Key::Left => cursor.x = cursor.x.saturating_sub(count.unwrap_or(1)),
+        Key::Char('j') | Key::Down => cursor.y = cursor.y.saturating_add(count.unwrap_or(1)),
+        Key::Char('k') | Key::Up => cursor.y = cursor.y.saturating_sub(count.unwrap_or(1)),
+        Key::Char('l') | Key::Right => cursor.x = cursor.x.saturating_add(count.unwrap_or(1)),
+        Key::Char('g') if !pending_g => {
+            game_state.pending_g = true;
+            return true; // the count is kept for the second g
+        }
+        Key::Char('g') => *cursor = match count {
+            Some(row) => Point{x: cursor.x, y: row - 1},
+            None => Point{x: 0, y: 0}
+        },
+        Key::Char('G') => *cursor = match count {
+            Some(row) => Point{x: cursor.x, y: row - 1},
+            None => Point{x: board.width as i16 - 1, y: board.height as i16 - 1}
+        },
+        Key::Esc if count.is_some() => (), // just cancels the count
+        _ => {
+            // digits that didn't end up being a count do what they usually do, then so does the key
+            let digits = std::mem::take(&mut game_state.count);
+            game_state.vim_keys = false;
+            for digit in digits.chars() {
+                handle_key_press(Key::Char(digit), board, game_state, frame_state);
+            }
+            game_state.vim_keys = true;
+            return false;
+        }
+    }
+    if count.is_some() {
+        game_state.count.clear();
+        game_state.message = String::new();
+        frame_state.message_updated = true;
+    }
+    frame_state.board_updated |= game_state.pasting; // move the ghost
+    return true;
+}
+
+
+fn handle_key_press(key: Key, board: &mut IncrementalBoard, game_state: &mut GameState, frame_state: &mut FrameState) {
+    if key == Key::Ctrl('c') { // ctrl+c doesn't send SIGINT in raw mode so it has to be handled here, before a prompt or anything else can take it
+        game_state.game_running = false;
+        return;
+    }
+    if game_state.help_shown { // any key closes the help, and only closes it
+        game_state.help_shown = false;
+        frame_state.layout_updated = true;
+        return;
+    }
+    if game_state.prompt.is_some() {
+        handle_prompt_key_press(key, board, game_state, frame_state);
+        return;
+    }
+    if game_state.pasting && handle_paste_key_press(key, board, game_state, frame_state) {
+        return;
+    }
+    if game_state.vim_keys && handle_vim_key_press(key, board, game_state, frame_state) {
+        return;
+    }
+    match key {
+        Key::Char('q') | Key::Char('Q') if game_state.confirm && game_state.tabs.iter().any(|tab| tab.edited) => {
+            game_state.prompt = Some(Prompt::new(PromptKind::Confirm(key), "Quit with edits in other tabs?".to_string()));
+        }
+        Key::Char('q') | Key::Char('Q') | Key::Char('r') | Key::Char('R') | Key::Char('c') | Key::Char('C') | Key::Ctrl('w') if game_state.edited && game_state.confirm && (key != Key::Ctrl('w') || !game_state.tabs.is_empty()) => {
+            let question = match key {
+                Key::Char('q') | Key::Char('Q') => "Quit?",
+                Key::Char('r') | Key::Char('R') => "Randomize the board?",
+                Key::Ctrl('w') => "Close the tab?",
+                _ => "Clear the board?"
+            };
+            game_state.prompt = Some(Prompt::new(PromptKind::Confirm(key), question.to_string()));
+        }
+        Key::Char('q') | Key::Char('Q') => game_state.game_running = false,
+        Key::Char(' ') => match game_state.puzzle.as_mut().and_then(|puzzle| puzzle.space(board)) {
+            Some((paused, message)) => {
+                game_state.paused = paused;
+                game_state.message = message;
+                frame_state.message_updated = true;
+                frame_state.board_updated = true;
+            }
+            None => {
+                game_state.paused = !game_state.paused;
+                frame_state.board_updated |= game_state.neighbour_counts || game_state.preview; // they only show while paused
+            }
+        },
+        Key::Char('r') | Key::Char('R') => { // initialize randomly
+            match &mut game_state.petri_dish {
+                Some(petri_dish) => petri_dish.reseed(board, &mut game_state.rng),
+                None => board.init_randomly_with(&mut game_state.rng)
+            }
+            game_state.edited = false;
+            clear_history(game_state);
+            frame_state.board_updated = true;
+        },
+        Key::Char('p') | Key::Char('P') if board.automaton == Automaton::Life => { // turn petri dish mode on/off
+            game_state.petri_dish = match game_state.petri_dish.take() {
+                Some(petri_dish) => {
+                    petri_dish.remove_walls(board);
+                    None
+                }
+                None => Some(PetriDish::new(board, &mut game_state.rng))
+            };
+            frame_state.board_updated = true;
+            frame_state.petri_dish_toggled = true;
+        }
+        Key::Char('c') | Key::Char('C') => { // clear board
+            board.clear();
+            game_state.edited = false;
+            clear_history(game_state);
+            frame_state.board_updated = true;
+        }
+        Key::Char('f') | Key::Char('F') if game_state.paused => { // move forward one frame
+            advance_generation(board, game_state);
+            frame_state.board_updated = true;
+        }
+        Key::Right | Key::Down | Key::Left | Key::Up => {
+            match key {
+                Key::Right => game_state.cursor_position.x += 1,
+                Key::Down => game_state.cursor_position.y += 1,
+                Key::Left => game_state.cursor_position.x -= 1,
+                _ => game_state.cursor_position.y -= 1,
+            }
+            frame_state.board_updated |= game_state.pasting; // move the ghost
+        }
+        Key::Char('h') | Key::Char('H') => { // hide cursor
+            game_state.cursor_visible = !game_state.cursor_visible;
+        }
+        Key::Char('a') | Key::Char('A') => { // create/kill a cell
+            let placed_state = drawn_state(board, game_state);
+            let killing = board.cell_state(&game_state.cursor_position) == placed_state; // the rest of the brush (and the mirrored cells) do whatever the cursor's cell does
+            for cell in brushed_cells(board, game_state) {
+                if killing {
+                    board.cells.remove(&cell);
+                } else {
+                    board.cells.insert(cell, placed_state);
+                }
+            }
+            game_state.edited = true;
+            frame_state.board_updated = true;
+        }
+        Key::Char('x') | Key::Char('X') => { // erase everything under the brush
+            for cell in brushed_cells(board, game_state) {
+                board.cells.remove(&cell);
+            }
+            game_state.edited = true;
+            frame_state.board_updated = true;
+        }
+        Key::Char('k') | Key::Char('K') => { // cells under the brush go from normal to always alive to always dead and back to normal
+            let cursor = &game_state.cursor_position;
+            let (source, wall, name) = match (board.sources.contains(cursor), board.walls.contains(cursor)) {
+                (false, false) => (true, false, "Always alive"),
+                (true, _) => (false, true, "Always dead"),
+                (false, true) => (false, false, "Normal cells")
+            };
+            for cell in brushed_cells(board, game_state) {
+                board.sources.remove(&cell);
+                board.walls.remove(&cell);
+                board.cells.remove(&cell);
+                if source {
+                    board.sources.insert(cell.clone());
+                    board.cells.insert(cell, ALIVE);
+                } else if wall {
+                    board.walls.insert(cell);
+                }
+            }
+            game_state.edited = true;
+            notify(name.to_string(), game_state, frame_state);
+            frame_state.board_updated = true;
+        }
+        Key::Char('b') | Key::Char('B') => {
+            game_state.brush = game_state.brush.next();
+            notify(format!("Brush: {}", game_state.brush.name()), game_state, frame_state);
+        }
+        Key::Char('m') | Key::Char('M') => {
+            game_state.symmetry = game_state.symmetry.next();
+            notify(format!("Symmetry: {}", game_state.symmetry.name()), game_state, frame_state);
+        }
+        Key::Char('e') | Key::Char('E') if uses_rule(board.automaton) => { // open rule editor
+            game_state.prompt = Some(Prompt::new(PromptKind::Rule, board.rule.to_string()));
+        }
+        Key::Char('[') | Key::Char(']') if uses_rule(board.automaton) => { // previous/next named rule
+            let preset = &presets::RULE_PRESETS[presets::cycle(&board.rule, key == Key::Char('['))];
+            board.rule = Rule::parse(preset.rule).unwrap(); // the presets are all valid
+            notify(format!("Switched to {} ({})", preset.name, preset.rule), game_state, frame_state);
+            frame_state.legend_updated = true; // the number of states might have changed
+        }
+        Key::Char('s') | Key::Char('S') if game_state.skip.is_none() => {
+            game_state.prompt = Some(Prompt::new(PromptKind::Skip, String::new()));
+        }
+        Key::Char('?') => {
+            game_state.help_shown = true;
+            frame_state.layout_updated = true;
+        }
+        Key::Char(':') => {
+            game_state.prompt = Some(Prompt::new(PromptKind::Command, String::new()));
+            game_state.command_history.reset_position();
+        }
+        Key::Char('y') | Key::Char('Y') => { // copy the rectangle between the V mark and the cursor
+            let message = match game_state.shape_start.take() {
+                Some(start) => {
+                    let pattern = copy_area(board, &start, &game_state.cursor_position);
+                    let message = format!("Copied {}x{} area (I to paste)", pattern.width, pattern.height);
+                    game_state.clipboard = Some(pattern);
+                    game_state.library_index = None;
+                    game_state.message = String::new(); // the drawing hint
+                    frame_state.message_updated = true;
+                    message
+                }
+                None => "Mark a corner with V first".to_string()
+            };
+            notify(message, game_state, frame_state);
+        }
+        Key::Char('i') | Key::Char('I') => {
+            if game_state.clipboard.is_some() {
+                game_state.pasting = true;
+                game_state.message = "Pasting: R rotate, H/V flip, Enter paste, Esc cancel".to_string();
+                frame_state.message_updated = true;
+                frame_state.board_updated = true;
+            } else {
+                notify("Nothing has been copied".to_string(), game_state, frame_state);
+            }
+        }
+        Key::Char('l') | Key::Char('L') if board.automaton == Automaton::Life => { // all of the bundled patterns are life patterns
+            take_from_library(0, game_state);
+            frame_state.board_updated = true;
+            frame_state.message_updated = true;
+        }
+        Key::Char('t') | Key::Char('T') if board.automaton != Automaton::Wireworld => { // wireworld cells never die so it uses T for electron tails instead
+            game_state.trails = match game_state.trails {
+                Some(_) => None,
+                None => Some(CellMap::new())
+            };
+            notify(format!("Trails: {}", if game_state.trails.is_some() { "on" } else { "off" }), game_state, frame_state);
+            frame_state.board_updated = true;
+        }
+        Key::Char('g') | Key::Char('G') | Key::Ctrl('g') => { // ctrl+g is for --vim, where g and G jump around
+            game_state.heatmap = match game_state.heatmap {
+                Some(_) => None,
+                None => Some(Heatmap::new())
+            };
+            notify(format!("Heatmap: {}", if game_state.heatmap.is_some() { "on" } else { "off" }), game_state, frame_state);
+            frame_state.board_updated = true;
+        }
+        Key::Char('d') | Key::Char('D') => {
+            game_state.shape = game_state.shape.next();
+            notify(format!("Shape: {}", game_state.shape.name()), game_state, frame_state);
+        }
+        Key::Char('v') | Key::Char('V') => { // the first press marks where the shape starts and the second one draws it
+            match game_state.shape_start.take() {
+                None => {
+                    game_state.shape_start = Some(game_state.cursor_position.clone());
+                    game_state.message = format!("Drawing a {} (V to finish, Esc to cancel)", game_state.shape.name());
+                }
+                Some(start) => {
+                    let placed_state = drawn_state(board, game_state);
+                    for cell in game_state.shape.cells(&start, &game_state.cursor_position) {
+                        for mirrored_cell in game_state.symmetry.symmetric_cells(&cell, board) {
+                            board.cells.insert(mirrored_cell, placed_state);
+                        }
+                    }
+                    game_state.edited = true;
+                    game_state.message = String::new();
+                    frame_state.board_updated = true;
+                }
+            }
+            frame_state.message_updated = true;
+        }
+        Key::Esc if game_state.shape_start.is_some() => {
+            game_state.shape_start = None;
+            game_state.message = String::new();
+            frame_state.message_updated = true;
+        }
+        Key::Esc if game_state.skip.is_some() => { // stop skipping wherever it's gotten to
+            game_state.message = format!("Skipped {} generations", game_state.skip.take().unwrap().done);
+            clear_history(game_state);
+            if let Some(petri_dish) = &mut game_state.petri_dish {
+                petri_dish.record(board);
+            }
+            frame_state.message_updated = true;
+            frame_state.board_updated = true;
+        }
+        Key::Char('e') | Key::Char('E') | Key::Char('t') | Key::Char('T') if board.automaton == Automaton::Wireworld => { // place an electron head/tail
+            let placed_state = match key {
+                Key::Char('e') | Key::Char('E') => ELECTRON_HEAD,
+                _ => ELECTRON_TAIL
+            };
+            board.cells.insert(game_state.cursor_position.clone(), placed_state);
+            game_state.edited = true;
+            frame_state.board_updated = true;
+        }
+        Key::Char('n') | Key::Char('N') if board.automaton == Automaton::LangtonsAnt => { // place an ant, turn it clockwise, then remove it after it's faced every direction
+            match board.ant_at(&game_state.cursor_position) {
+                Some(ant_index) if board.ants[ant_index].direction == 3 => {
+                    board.ants.remove(ant_index);
+                }
+                Some(ant_index) => board.ants[ant_index].direction += 1,
+                None => board.ants.push(Ant {
+                    position: game_state.cursor_position.clone(),
+                    direction: 0
+                })
+            }
+            frame_state.board_updated = true;
+        }
+        Key::Char('w') | Key::Char('W') => { // save
+            let saved = match &game_state.loaded_file {
+                Some(loaded_file) => save_loaded_file(board, loaded_file, game_state.replaying),
+                None => Err("Nothing was loaded with --load".to_string())
+            };
+            game_state.edited &= saved.is_err();
+            let message = saved.unwrap_or_else(|message| message);
+            notify(message, game_state, frame_state);
+        }
+        Key::Char('z') | Key::Char('Z') => { // save the whole board as an image
+            let message = match game_state.replaying {
+                true => "Didn't save a screenshot (replaying)".to_string(),
+                false => render::save_screenshot(board, game_state.screenshot_scale, &render::screenshot_path()).unwrap_or_else(|message| message)
+            };
+            notify(message, game_state, frame_state);
+        }
+        Key::Char('u') | Key::Char('U') => { // unicode, then braille, then ascii, then pixels if the terminal can draw images
+            let can_draw_pixels = game_state.graphics.is_some();
+            (game_state.unicode_enabled, game_state.braille, game_state.pixels) = match (game_state.unicode_enabled, game_state.braille, game_state.pixels) {
+                (true, false, false) => (true, true, false),
+                (true, true, false) => (false, false, false),
+                (false, _, false) if can_draw_pixels => (true, false, true),
+                _ => (true, false, false)
+            };
+            let display = match &game_state.graphics {
+                Some(graphics) if game_state.pixels => graphics.name(),
+                _ if game_state.braille => "braille",
+                _ if game_state.unicode_enabled => "unicode",
+                _ => "ascii"
+            };
+            notify(format!("Display: {}", display), game_state, frame_state);
+            frame_state.layout_updated = true; // braille changes the size of the board on the screen
+        }
+        Key::Char('j') | Key::Char('J') => { // the panel goes under the board, next to it, or away
+            game_state.panel = game_state.panel.next();
+            game_state.max_screen_size = max_screen_size(game_state.panel);
+            notify(format!("Panel: {}", game_state.panel.name()), game_state, frame_state);
+            frame_state.layout_updated = true;
+        }
+        Key::Ctrl('f') => {
+            game_state.follow = !game_state.follow;
+            notify(format!("Following the action: {}", if game_state.follow { "on" } else { "off" }), game_state, frame_state);
+            frame_state.board_updated = true; // so it jumps there straight away
+        }
+        Key::Ctrl('n') if !counts_neighbours(board.automaton) => notify("Neighbour counts are only for automata that count neighbours".to_string(), game_state, frame_state),
+        Key::Ctrl('n') => {
+            game_state.neighbour_counts = !game_state.neighbour_counts;
+            notify(format!("Neighbour counts: {}", if game_state.neighbour_counts { "on (while paused)" } else { "off" }), game_state, frame_state);
+            frame_state.board_updated = true;
+        }
+        Key::Ctrl('t') | Key::Char('\t') | Key::BackTab | Key::Ctrl('w') if !tabs_allowed(game_state) => {
+            notify("Tabs can't be used in puzzles, demos or replays".to_string(), game_state, frame_state);
+        }
+        Key::Ctrl('t') => new_tab(board, game_state, frame_state),
+        Key::Char('\t') | Key::BackTab if game_state.tabs.is_empty() => notify("There's only one tab, Ctrl+T makes another".to_string(), game_state, frame_state),
+        Key::Char('\t') => {
+            let next = (game_state.tab + 1) % (game_state.tabs.len() + 1);
+            switch_tab(next, board, game_state, frame_state);
+        }
+        Key::BackTab => {
+            let previous = (game_state.tab + game_state.tabs.len()) % (game_state.tabs.len() + 1);
+            switch_tab(previous, board, game_state, frame_state);
+        }
+        Key::Ctrl('w') => close_tab(board, game_state, frame_state),
+        Key::Ctrl('p') => {
+            game_state.preview = !game_state.preview;
+            notify(format!("Next generation preview: {}", if game_state.preview { "on (while paused)" } else { "off" }), game_state, frame_state);
+            frame_state.board_updated = true;
+        }
+        Key::Char('o') | Key::Char('O') => { // next colour theme
+            game_state.theme = (game_state.theme + 1) % THEMES.len();
+            notify(format!("Theme: {}", THEMES[game_state.theme].name), game_state, frame_state);
+            frame_state.layout_updated = true;
+        }
+        Key::Char('-') | Key::Char('_') | Key::Char('=') | Key::Char('+') => {
+            match key {
+                Key::Char('-') | Key::Char('_') => game_state.frame_delay -= 1,
+                _ => game_state.frame_delay += 1
+            }
+            game_state.frame_delay = game_state.frame_delay.clamp(MIN_FRAME_DELAY, MAX_FRAME_DELAY);
+            frame_state.frame_delay_updated = true;
+        }
+        Key::Char('<') | Key::Char(',') | Key::Char('>') | Key::Char('.') => { // halve or double the generations per frame
+            game_state.generations_per_frame = match key {
+                Key::Char('<') | Key::Char(',') => game_state.generations_per_frame / 2,
+                _ => game_state.generations_per_frame * 2
+            }.clamp(1, MAX_GENERATIONS_PER_FRAME);
+            frame_state.frame_delay_updated = true;
+        }
+        Key::Char(c) if BOOKMARK_KEYS.contains(&c) => {
+            let slot = BOOKMARK_KEYS.iter().position(|&key| key == c).unwrap();
+            save_bookmark(slot, board, game_state, frame_state);
+        }
+        Key::Char('{') | Key::Char('}') if num_paintable_states(board) > 1 => { // previous/next state to draw with, going round from the last state to the first
+            let (states, state) = (num_paintable_states(board), drawn_state(board, game_state));
+            game_state.paint_state = Some(match key {
+                Key::Char('{') if state <= 1 => states,
+                Key::Char('{') => state - 1,
+                _ => state % states + 1
+            });
+            frame_state.legend_updated = true;
+            frame_state.frame_delay_updated = true;
+        }
+        Key::Ctrl(digit @ '1'..='9') => save_bookmark(digit as usize - '1' as usize, board, game_state, frame_state), // only some terminals can send these
+        Key::Char(digit @ '1'..='9') if game_state.paused => { // numbers load bookmarks while paused and change the speed otherwise
+            let slot = digit as usize - '1' as usize;
+            let message = match &game_state.bookmarks[slot] {
+                Some(bookmark) => {
+                    board.cells = bookmark.cells.clone();
+                    board.ants = bookmark.ants.clone();
+                    clear_history(game_state);
+                    frame_state.board_updated = true;
+                    format!("Loaded bookmark {}", slot + 1)
+                }
+                None => format!("Bookmark {} is empty", slot + 1)
+            };
+            notify(message, game_state, frame_state);
+        }
+        Key::Char(digit @ '1'..='9') => { // jump straight to a preset speed
+            game_state.frame_delay = FRAME_DELAY_PRESETS[digit as usize - '1' as usize];
+            frame_state.frame_delay_updated = true;
+        }
+        _ => ()
+    };
+}
+
+
+// how the game was started, for setting up the GameState
+struct GameOptions {
+    loaded_file: Option<LoadedFile>,
+    pattern_of_the_day: Option<&'static BundledPattern>,
+    generations_per_frame: u32,
+    theme: usize,
+    braille: bool,
+    graphics: Option<Graphics>,
+    pixels: bool,
+    screenshot_scale: u32,
+    rng: StdRng,
+    rival_ai: bool, // the AI plays player two in immigration
+    vim_keys: bool,
+    panel: Panel,
+    puzzle: Option<Puzzle>,
+    demo: bool, // cycle through DEMO_PATTERNS
+    recorder: Option<Recorder>, // Some with --record
+    replay: Option<Replay>, // Some with --replay, then the events come from this instead of the keyboard and the clock
+    message: String, // shown next to the frame time until something else replaces it
+    generation_limit: Option<u32>, // quit after this many generations (for --plain without a keyboard, which would otherwise never stop)
+    confirm: bool,
+    memory_limit: Option<u64>,
+    infinite: bool,
+    grid: Option<u32>,
+}
+
+
+// returns a summary of the game for after it's over, and whether a replay came out the same as the recording (Err if it didn't) once it's played all the way through
+// or Err if the terminal stopped working (drawing to it or reading keys from it failed)
+fn play_game(board: &mut IncrementalBoard, key_input: &mpsc::Receiver<Key>, renderer: &mut dyn Renderer, options: GameOptions) -> (String, Option<Result<String, String>>) {
+    let mut game_state = GameState {
+        paused: options.puzzle.is_some(), // puzzles start out being edited
+        game_running: true,
+        cursor_position: Point{x:0, y:0}, // we will consider the top left of the board to be 0,0 to conform with board.occupied_cells Points
+        cursor_visible: true,
+        viewport: Point{x: 0, y: 0},
+        max_screen_size: max_screen_size(options.panel),
+        panel: options.panel,
+        unicode_enabled: true,
+        braille: options.braille,
+        graphics: options.graphics,
+        pixels: options.pixels,
+        theme: options.theme,
+        frame_delay: 30,
+        generations_per_frame: options.generations_per_frame,
+        is_first_frame: true,
+        prompt: None,
+        help_shown: false,
+        command_history: match options.recorder.is_some() || options.replay.is_some() {
+            true => History::unsaved(),
+            false => History::load()
+        },
+        skip: None,
+        bookmarks: Default::default(),
+        symmetry: Symmetry::Off,
+        brush: Brush::Single,
+        shape: Shape::Line,
+        shape_start: None,
+        petri_dish: None,
+        puzzle: options.puzzle,
+        demo: None,
+        rival_ai: if options.rival_ai { Some(AI_SEED_INTERVAL) } else { None },
+        vim_keys: options.vim_keys,
+        count: String::new(),
+        pending_g: false,
+        pattern_of_the_day: options.pattern_of_the_day,
+        paint_state: None,
+        clipboard: None,
+        pasting: false,
+        heatmap: None,
+        trails: None,
+        library_index: None,
+        population_history: VecDeque::new(),
+        loaded_file: options.loaded_file,
+        screenshot_scale: options.screenshot_scale,
+        rng: options.rng,
+        replaying: options.replay.is_some(),
+        message: options.message,
+        toasts: VecDeque::new(),
+        generations: 0,
+        peak_population: 0,
+        infinite: options.infinite,
+        follow: false,
+        neighbour_counts: false,
+        preview: false,
+        grid: options.grid,
+        memory_limit: options.memory_limit,
+        edited: false,
+        confirm: options.confirm,
+        tabs: Vec::new(),
+        tab: 0
+    };
+    let generation_limit = options.generation_limit;
+    if options.demo {
+        show_demo_pattern(0, board, &mut game_state);
+    }
+    game_state.peak_population = board.population();
+    let mut recorder = options.recorder;
+    let mut replay = options.replay;
+    let replay_start = time::Instant::now();
+    let started = time::Instant::now();
+
+    // when the board should next be advanced, generations are scheduled frame_delay apart no matter how long updating and printing takes (as long as it takes less than frame_delay)
+    // the loop itself runs whenever a generation is due or a key is pressed, so keys get handled straight away even when the simulation is slowed right down
+    let mut next_generation_time = time::Instant::now();
+
+    let mut waiting_key = None; // a key that was received while waiting for the next generation
+    let mut terminal_error = None;
+
+    while game_state.game_running && !terminal::quit_requested() && generation_limit.is_none_or(|limit| game_state.generations < limit as u64) {
+
+        let mut frame_state = FrameState {
+            board_updated: false,
+            frame_delay_updated: false,
+            legend_updated: false,
+            message_updated: false,
+            bookmarks_updated: false,
+            layout_updated: false,
+            petri_dish_toggled: false
+        };
+        let cursor_before = game_state.cursor_position.clone(); // the cursor's coordinates are in the status bar, which gets redrawn if it moves
+        let size_before = (board.width, board.height); // --infinite can grow the board
+
+        // update_cells before we handle key presses so that if a keypress causes a cell to be born or die we will see that effect directly on the next frame (if we were to call update_cells after handling input (but before printing the frame) then we would never see the direct result of the user input because update_cells would be called because the user input has a chance to be printed to the screen)
+        // the downside of doing it this way is that that a user keypress actually effects the state of the next cell update, and not the current cell update (the one that the user is currently looking at), although this is only noticable at low framerates
+        if let Some(replay) = &mut replay {
+            // generations happen when the recording says they did instead of when they're due
+            let due = replay.events.front().filter(|(time, _)| *time <= replay_start.elapsed()).map(|(_, event)| event);
+            match due {
+                Some(Event::Step) => {
+                    for _ in 0..game_state.generations_per_frame {
+                        advance_generation(board, &mut game_state);
+                        if game_state.paused {
+                            break; // a puzzle attempt finished
+                        }
+                    }
+                    frame_state.board_updated = true;
+                    replay.events.pop_front();
+                }
+                Some(&Event::Skip(generations)) if game_state.skip.is_some() => {
+                    continue_skip(board, &mut game_state, &mut frame_state, Some(generations));
+                    replay.events.pop_front();
+                }
+                _ => ()
+            }
+        } else if game_state.skip.is_some() {
+            let generations = continue_skip(board, &mut game_state, &mut frame_state, None);
+            if let Some(recorder) = &mut recorder {
+                if let Err(message) = recorder.record(&Event::Skip(generations)) {
+                    notify(message, &mut game_state, &mut frame_state);
+                }
+            }
+        } else if !game_state.paused && time::Instant::now() >= next_generation_time {
+            for _ in 0..game_state.generations_per_frame {
+                advance_generation(board, &mut game_state);
+                if game_state.paused {
+                    break; // a puzzle attempt finished
+                }
+            }
+            frame_state.board_updated = true;
+            if let Some(recorder) = &mut recorder {
+                if let Err(message) = recorder.record(&Event::Step) {
+                    notify(message, &mut game_state, &mut frame_state);
+                }
+            }
+            next_generation_time += time::Duration::from_millis(game_state.frame_delay as u64);
+            let now = time::Instant::now();
+            if next_generation_time < now {
+                next_generation_time = now; // that took longer than frame_delay, don't try to catch up by rushing the next few generations
+            }
+        }
+
+        // handle key presses
+        // every key pressed since the last frame gets handled (in order) so that holding down a key at a high frame delay doesn't leave a backlog of key presses to get through
+        let live_keys: Vec<Key> = waiting_key.take().into_iter().chain(key_input.try_iter()).collect();
+        let keys = match &mut replay {
+            Some(replay) => {
+                // while replaying the only thing the keyboard can do is stop it
+                if live_keys.iter().any(|key| matches!(key, Key::Char('q') | Key::Char('Q') | Key::Ctrl('c'))) {
+                    break;
+                }
+                replay.due_keys(replay_start.elapsed())
+            }
+            None => live_keys
+        };
+        let was_hex = hex_layout(board, &game_state);
+        for key in keys {
+            if let Some(recorder) = &mut recorder {
+                if let Err(message) = recorder.record(&Event::Key(key)) {
+                    notify(message, &mut game_state, &mut frame_state);
+                }
+            }
+            handle_key_press(key, board, &mut game_state, &mut frame_state); // kinda yucky that handle_key_press can mutate any of its input, would be more clear if it returned a BoardState and FrameState but then rust gets angry about borrows and moves and fixing it ends up being even worse than this
+            if !game_state.game_running {
+                break; // don't bother with anything typed after quitting
+            }
+        }
+        frame_state.layout_updated |= hex_layout(board, &game_state) != was_hex; // switching to or from a hexagonal rule changes the size of the board on the screen
+        if game_state.petri_dish.as_ref().is_some_and(|petri_dish| !petri_dish.fits(board)) { // the new rule reaches further than the walls are thick
+            game_state.petri_dish = Some(PetriDish::new(board, &mut game_state.rng));
+            notify("Rebuilt the petri dish with thicker walls for the new rule".to_string(), &mut game_state, &mut frame_state);
+            frame_state.board_updated = true;
+        }
+        frame_state.layout_updated |= (board.width, board.height) != size_before;
+
+        // ensure cursor is at correct location, and that it's on the screen
+        game_state.cursor_position.bound(
+            0, 0, 
+            board.width as i16 - 1, board.height as i16 - 1
+        );
+        if game_state.follow && frame_state.board_updated {
+            follow_population(board, &mut game_state);
+        }
+        frame_state.board_updated |= scroll_to_cursor(board, &mut game_state);
+        frame_state.frame_delay_updated |= game_state.cursor_position != cursor_before;
+        let toasts_shown = game_state.toasts.len();
+        game_state.toasts.retain(|toast| toast.shown.elapsed() < TOAST_TIME);
+        frame_state.board_updated |= game_state.toasts.len() != toasts_shown; // to get rid of the ones that just went
+
+        let redraw_everything = game_state.is_first_frame || frame_state.layout_updated;
+        renderer.set_theme(game_state.theme);
+        if redraw_everything {
+            print_static_text(renderer, board, &game_state); // clearing the screen also gets rid of the image in case pixel mode was just turned off
+            frame_state.board_updated = true;
+        }
+
+        if game_state.help_shown {
+            if redraw_everything {
+                print_help(renderer, board, &game_state); // the help goes over the board, image and all
+                renderer.place_cursor(None);
+            }
+        } else {
+            // print board
+            if frame_state.board_updated {
+                let theme = &THEMES[game_state.theme];
+                let (screen_width, screen_height) = screen_size(board, &game_state);
+                let (viewport_width, viewport_height) = viewport_size(board, &game_state);
+                match (game_state.pixels, &game_state.graphics) {
+                    (true, Some(_)) => {
+                        renderer.draw_image(2, 2, &board_to_pixels(board, &game_state, viewport_width, viewport_height), viewport_width, viewport_height);
+                    }
+                    _ if game_state.braille => {
+                        draw_chars(renderer, 1, 2, &board_to_braille(board, theme, &game_state.viewport, screen_width, screen_height));
+                    }
+                    _ => {
+                        draw_chars(renderer, 1, 2, &board_to_chars(board, &game_state, viewport_width, viewport_height));
+                        if let (Some(spacing), false) = (game_state.grid, hexagonal(board)) {
+                            print_grid_labels(renderer, &game_state, spacing, viewport_width);
+                        }
+                    }
+                }
+                print_population(renderer, board, &game_state);
+                // overlays go on individual cells, which braille characters are too small for (and pixel mode draws them into the image)
+                let overlays_shown = !game_state.braille && !game_state.pixels;
+                if let (Some(trails), true) = (&game_state.trails, overlays_shown) {
+                    print_trails(renderer, board, trails, &game_state);
+                }
+                if let (Some(heatmap), true) = (&game_state.heatmap, overlays_shown) {
+                    print_heatmap(renderer, board, heatmap, &game_state);
+                }
+                if game_state.preview && game_state.paused && overlays_shown {
+                    print_preview(renderer, board, &game_state);
+                }
+                if game_state.neighbour_counts && game_state.paused && overlays_shown {
+                    print_neighbour_counts(renderer, board, &game_state);
+                }
+            }
+
+            // write frame delay
+            let panel_updated = frame_state.board_updated && (game_state.puzzle.is_some() || game_state.demo.is_some()); // the puzzle's edits and the demo's pattern change with the board
+            if frame_state.frame_delay_updated || frame_state.message_updated || panel_updated || redraw_everything {
+                let last_line = status_line(board, &game_state);
+                let rule = match board.automaton {
+                    Automaton::Life | Automaton::Immigration => format!("Rule: {}   ", presets::rule_name(&board.rule)),
+                    Automaton::Margolus(block_rule) => format!("Rule: {}   ", block_rule.name()),
+                    Automaton::Table => format!("Rule: {}   ", board.table.as_ref().map_or("", |table| &table.name)),
+                    _ => String::new()
+                };
+                let tab = match game_state.tabs.len() {
+                    0 => String::new(),
+                    others => format!("Tab: {}/{}   ", game_state.tab + 1, others + 1)
+                };
+                let painting = if num_paintable_states(board) > 1 {
+                    format!("Painting: {}   ", state_name(board, drawn_state(board, &game_state)))
+                } else {
+                    String::new()
+                };
+                renderer.draw_status(last_line, &format!( // the whole line gets redrawn, which gets rid of old trailing zeros and messages
+                    "?: Help   Time per frame: {} ms   Generations per frame: {}   Cursor: {},{}   {}{}{}{}",
+                    game_state.frame_delay,
+                    game_state.generations_per_frame,
+                    game_state.cursor_position.x,
+                    game_state.cursor_position.y,
+                    tab,
+                    rule,
+                    painting,
+                    game_state.message
+                ));
+            }
+
+            if frame_state.legend_updated || frame_state.petri_dish_toggled || (frame_state.board_updated && game_state.petri_dish.is_some()) || panel_updated || redraw_everything {
+                print_side_panel(renderer, board, &game_state);
+            }
+
+            if frame_state.bookmarks_updated || frame_state.legend_updated || redraw_everything { // legend_updated because of the unicode toggle
+                print_bookmarks(renderer, board, &game_state);
+            }
+
+            // show where the clipboard would get pasted
+            if let (true, true, false, false, Some(clipboard)) = (frame_state.board_updated, game_state.pasting, game_state.braille, game_state.pixels, &game_state.clipboard) {
+                print_ghost(renderer, board, clipboard, &game_state.cursor_position, &game_state);
+            }
+            if frame_state.board_updated {
+                print_minimap(renderer, board, &game_state); // after the ghost so it stays on top
+            }
+            if !game_state.toasts.is_empty() { // every frame so they keep fading
+                print_toasts(renderer, board, &game_state);
+            }
+            let (cursor_x, cursor_y) = ( // where the cursor is on the screen (in cells), then the character that cell is in
+                (game_state.cursor_position.x - game_state.viewport.x) as u16,
+                (game_state.cursor_position.y - game_state.viewport.y) as u16
+            );
+            let (cursor_x, cursor_y) = match (game_state.pixels, &game_state.graphics) {
+                (true, Some(graphics)) => (
+                    (cursor_x as u32 * graphics::PIXELS_PER_CELL / graphics.char_width) as u16,
+                    (cursor_y as u32 * graphics::PIXELS_PER_CELL / graphics.char_height) as u16
+                ),
+                _ if game_state.braille => (cursor_x / 2, cursor_y / 4),
+                _ if hex_layout(board, &game_state) => (hex_column(cursor_x as i16, cursor_y as i16, viewport_size(board, &game_state).1), cursor_y),
+                _ => (cursor_x, cursor_y)
+            };
+
+            // draw the prompt over the board (the cursor goes at the end of the typed text instead)
+            match &game_state.prompt {
+                Some(prompt) => {
+                    let position = print_prompt(renderer, board, &game_state, prompt);
+                    renderer.place_cursor(Some(position));
+                }
+                None if game_state.cursor_visible => renderer.place_cursor(Some((cursor_x + 2, cursor_y + 2))),
+                None => renderer.place_cursor(None)
+            }
+        }
+
+        game_state.is_first_frame = false;
+
+        // ensure the frame gets to the screen, if it doesn't then the terminal's gone (or stdout got closed)
+        if let Err(error) = renderer.present() {
+            terminal_error = Some(format!("couldn't draw to the terminal: {}", error));
+            break;
+        }
+
+        if replay.as_ref().is_some_and(|replay| replay.events.is_empty()) {
+            break; // the recording ended without quitting (it got killed)
+        }
+
+        // skipping already took up the frame so go straight to the next chunk
+        if game_state.skip.is_some() && replay.is_none() {
+            next_generation_time = time::Instant::now();
+            continue;
+        }
+
+        // wait for a key press or for the next generation, whichever comes first (nothing changes while paused so that's just waiting for a key)
+        // replays wait for their next event instead
+        let timeout = match (&replay, game_state.paused) {
+            (Some(replay), _) => replay.events.front()
+                .map(|(time, _)| time.saturating_sub(replay_start.elapsed()))
+                .unwrap_or_default()
+                .min(INPUT_TIMEOUT),
+            (None, true) => INPUT_TIMEOUT,
+            (None, false) => next_generation_time.saturating_duration_since(time::Instant::now()).min(INPUT_TIMEOUT)
+        };
+        match key_input.recv_timeout(timeout) {
+            Ok(key) => waiting_key = Some(key),
+            Err(mpsc::RecvTimeoutError::Timeout) => (),
+            Err(mpsc::RecvTimeoutError::Disconnected) => match terminal::key_read_error() {
+                Some(message) => {
+                    terminal_error = Some(message.clone());
+                    break;
+                }
+                None => thread::sleep(timeout) // stdin was closed so keys are never coming
+            }
+        }
+        if game_state.paused {
+            next_generation_time = time::Instant::now(); // so that unpausing doesn't try to make up for the time spent paused
+        }
+    }
+
+    if let Some(recorder) = recorder {
+        recorder.finish(board.state_hash());
+    }
+    let summary = session_summary(board, &game_state, started.elapsed());
+    return match terminal_error {
+        Some(message) => (summary, Some(Err(message))),
+        None => (summary, check_replay(board, replay))
+    };
+}
+
+
+// printed once the game's over, so that there's something to show for it
+fn session_summary(board: &Board, game_state: &GameState, elapsed: time::Duration) -> String {
+    return format!(
+        "Ran {} generation{} in {:.1}s ({:.1} generations per second), the population ended at {} and peaked at {}",
+        game_state.generations, if game_state.generations == 1 { "" } else { "s" }, elapsed.as_secs_f64(),
+        game_state.generations as f64 / elapsed.as_secs_f64().max(0.001), board.population(), game_state.peak_population
+    );
+}
+
+
+// whether a replay came out the same as the recording (Err if it didn't), None if it didn't play all the way through (or there wasn't one)
+fn check_replay(board: &Board, replay: Option<Replay>) -> Option<Result<String, String>> {
+    let replay = replay.filter(|replay| replay.events.is_empty())?; // stopped partway through
+    return match replay.hash {
+        Some(hash) if hash == board.state_hash() => Some(Ok("The replay ended with the same board as the recording".to_string())),
+        Some(hash) => Some(Err(format!("The replay ended with a different board to the recording (hash {:016x}, expected {:016x})", board.state_hash(), hash))),
+        None => None // the recording didn't get to the end
+    };
+}
+
+
+fn default_board_dimensions(panel: Panel) -> (u16, u16) {
+    let (terminal_width, terminal_height) = terminal::terminal_size().unwrap_or_else(|message| {
+        println!("{}", message);
+        process::exit(1);
+    });
+    let (reserved_width, reserved_height) = panel.reserved_size(); // the board's borders and everything around it
+    let min_board_height = 1;
+    let min_board_width = PANEL_WIDTH;
+    let max_board_width = terminal_width.saturating_sub(reserved_width);
+    let max_board_height = terminal_height.saturating_sub(reserved_height);
+    if max_board_height < min_board_height || max_board_width < min_board_width {
+        println!("your terminal is too small to play :(");
+        process::exit(1);
+    }
+    return (max_board_width, max_board_height);
+}
+
+
+// everything that can be set with command line arguments
+struct Args {
+    board_width: Option<u16>, // None means pick a size automatically
+    board_height: Option<u16>,
+    automaton: Automaton,
+    rule: Option<Rule>, // None means use the default (or the pattern's rule)
+    against: Option<Rule>, // the compare subcommand's second rule
+    rule_table: Option<RuleTable>, // from --rule-file, which makes the automaton Automaton::Table
+    pattern_path: Option<String>,
+    load_path: Option<String>, // pattern or manifest to start the game with
+    generations: u32, // how many generations to run for in headless subcommands
+    out_path: Option<String>,
+    fps: u32, // for rendered videos
+    scale: u32, // pixels per cell in rendered videos and screenshots
+    generations_per_frame: u32,
+    device: Option<String>, // framebuffer to draw to
+    pattern_of_the_day: bool, // start with one of the bundled patterns instead of a random board
+    memory_limit_mb: u64, // boards that could need more memory than this won't be created without --force
+    force: bool,
+    theme: usize, // index into THEMES
+    braille: bool, // start with the board drawn in braille (which also makes the default board 8 times bigger)
+    screenshot_on_exit: bool,
+    graphics: bool, // start with the board drawn as an image if the terminal can do it (the default board gets as big as the terminal's pixels allow)
+    seed: Option<u64>, // for the verify subcommand
+    expect_hash: Option<String>,
+    engine: Option<EngineKind>, // what the subcommands that run without the UI run on, None is the incremental one for all of them but bench (where it's all of them)
+    record_path: Option<String>, // where to record the game to so it can be replayed
+    replay_path: Option<String>,
+    emit: Option<String>, // write every generation to stdout in this format instead of playing
+    cells_format: Option<String>, // how --emit writes the cells
+    soups: u32, // how many soups the search subcommand runs
+    soup_size: (u16, u16), // width and height of the search subcommand's soups
+    rival_ai: bool, // --ai, the AI plays player two in immigration
+    vim_keys: bool, // --vim
+    recover: bool, // load the board that was saved when the last game ended
+    confirm: bool, // ask before throwing away hand edits, false with --no-confirm
+    infinite: bool, // the board grows as things reach its edges instead of stopping them
+    grid: Option<u32>, // --grid, how many cells apart the grid lines are
+    plain: bool, // --plain or --no-altscreen, draw in the normal screen without raw mode
+    panel: Panel,
+    puzzle: Option<Puzzle>, // from --puzzle
+    demo: bool,
+}
+
+
+fn parse_args(args: Vec<String>) -> Args {
+    let mut parsed_args = Args {
+        board_width: None,
+        board_height: None,
+        automaton: Automaton::Life,
+        rule: None,
+        against: None,
+        rule_table: None,
+        pattern_path: None,
+        load_path: None,
+        generations: 1000,
+        out_path: None,
+        fps: 30,
+        scale: 4,
+        generations_per_frame: 1,
+        device: None,
+        pattern_of_the_day: false,
+        memory_limit_mb: DEFAULT_MEMORY_LIMIT_MB,
+        force: false,
+        theme: 0,
+        braille: false,
+        graphics: false,
+        screenshot_on_exit: false,
+        seed: None,
+        expect_hash: None,
+        engine: None,
+        record_path: None,
+        replay_path: None,
+        emit: None,
+        cells_format: None,
+        soups: 1000,
+        soup_size: (16, 16),
+        rival_ai: false,
+        vim_keys: false,
+        recover: false,
+        confirm: true,
+        infinite: false,
+        grid: None,
+        plain: false,
+        panel: Panel::Bottom,
+        puzzle: None,
+        demo: false,
+    };
+    let mut last_arg: String = "".to_string(); // convert to String since string literals are of type &str
+    for arg in args {
+        if arg == "--potd" {
+            parsed_args.pattern_of_the_day = true;
+        }
+        if arg == "--recover" {
+            parsed_args.recover = true;
+        }
+        if arg == "--no-confirm" {
+            parsed_args.confirm = false;
+        }
+        if arg == "--infinite" {
+            parsed_args.infinite = true;
+        }
+        if arg == "--force" {
+            parsed_args.force = true;
+        }
+        if arg == "--braille" {
+            parsed_args.braille = true;
+        }
+        if arg == "--graphics" {
+            parsed_args.graphics = true;
+        }
+        if arg == "--demo" {
+            parsed_args.demo = true;
+        }
+        if arg == "--vim" {
+            parsed_args.vim_keys = true;
+        }
+        if arg == "--plain" || arg == "--no-altscreen" {
+            parsed_args.plain = true;
+        }
+        if arg == "--ai" {
+            parsed_args.rival_ai = true;
+        }
+        if arg == "--screenshot-on-exit" {
+            parsed_args.screenshot_on_exit = true;
+        }
+        if arg == STDIN_PATH { // a pattern piped in, like --load -
+            parsed_args.load_path = Some(arg.clone());
+        }
+        match last_arg.as_str() {
+            "--height" | "--width" => {
+                if let Ok(val) = arg.parse::<u16>() {
+                    if last_arg == "--height" {
+                        parsed_args.board_height = Some(val);
+                    } else {
+                        parsed_args.board_width = Some(val);
+                    }
+                }
+            }
+            "--rule" => {
+                match Rule::parse(&arg) {
+                    Ok(rule) => parsed_args.rule = Some(rule),
+                    Err(message) => {
+                        println!("invalid rule: {}", message);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--engine" => {
+                match EngineKind::parse(&arg) {
+                    Ok(engine) => parsed_args.engine = Some(engine),
+                    Err(message) => {
+                        println!("{}", message);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--against" => {
+                match Rule::parse(&arg) {
+                    Ok(rule) => parsed_args.against = Some(rule),
+                    Err(message) => {
+                        println!("invalid rule: {}", message);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--puzzle" => {
+                match Puzzle::load(&arg) {
+                    Ok(puzzle) => parsed_args.puzzle = Some(puzzle),
+                    Err(message) => {
+                        println!("{}", message);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--rule-file" => {
+                let table = fs::read_to_string(&arg).map_err(|error| error.to_string()).and_then(|text| RuleTable::parse(&text));
+                match table {
+                    Ok(table) => parsed_args.rule_table = Some(table),
+                    Err(message) => {
+                        println!("{}: {}", arg, message);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--automaton" => {
+                parsed_args.automaton = match arg.to_lowercase().as_str() {
+                    "life" => Automaton::Life,
+                    "wireworld" => Automaton::Wireworld,
+                    "ant" | "langtons-ant" => Automaton::LangtonsAnt,
+                    "margolus" => Automaton::Margolus(BlockRule::critters()),
+                    "immigration" => Automaton::Immigration,
+                    _ => match BlockRule::parse(&arg) { // critters, tron or a block rule's table
+                        Ok(block_rule) => Automaton::Margolus(block_rule),
+                        Err(message) if arg.to_lowercase().starts_with("ms,d") => {
+                            println!("{}", message);
+                            process::exit(1);
+                        }
+                        Err(_) => {
+                            println!("unknown automaton \"{}\" (expected life, wireworld, ant, immigration, critters, tron or a block rule like MS,D15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0)", arg);
+                            process::exit(1);
+                        }
+                    }
+                };
+            }
+            "--pattern" => parsed_args.pattern_path = Some(arg.clone()),
+            "--load" => parsed_args.load_path = Some(arg.clone()),
+            "--generations" => {
+                if let Ok(val) = arg.parse::<u32>() {
+                    parsed_args.generations = val;
+                }
+            }
+            "--grid" => {
+                match arg.parse::<u32>() {
+                    Ok(spacing) if spacing >= 2 => parsed_args.grid = Some(spacing),
+                    _ => {
+                        println!("invalid grid spacing \"{}\" (expected a number of cells, at least 2)", arg);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--generations-per-frame" => {
+                if let Ok(val) = arg.parse::<u32>() {
+                    parsed_args.generations_per_frame = val.clamp(1, MAX_GENERATIONS_PER_FRAME);
+                }
+            }
+            "--memory-limit" => {
+                if let Ok(val) = arg.parse::<u64>() {
+                    parsed_args.memory_limit_mb = val;
+                }
+            }
+            "--out" => parsed_args.out_path = Some(arg.clone()),
+            "--device" => parsed_args.device = Some(arg.clone()),
+            "--seed" => {
+                if let Ok(val) = arg.parse::<u64>() {
+                    parsed_args.seed = Some(val);
+                }
+            }
+            "--expect-hash" => parsed_args.expect_hash = Some(arg.clone()),
+            "--record" => parsed_args.record_path = Some(arg.clone()),
+            "--replay" => parsed_args.replay_path = Some(arg.clone()),
+            "--emit" => parsed_args.emit = Some(arg.to_lowercase()),
+            "--cells" => parsed_args.cells_format = Some(arg.to_lowercase()),
+            "--soups" => {
+                if let Ok(val) = arg.parse::<u32>() {
+                    parsed_args.soups = val;
+                }
+            }
+            "--size" => {
+                let size = arg.to_lowercase().split_once('x').and_then(|(width, height)| Some((width.parse::<u16>().ok()?, height.parse::<u16>().ok()?)));
+                match size {
+                    Some((width, height)) if width > 0 && height > 0 => parsed_args.soup_size = (width, height),
+                    _ => {
+                        println!("invalid size \"{}\" (expected something like 16x16)", arg);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--panel" => {
+                parsed_args.panel = Panel::parse(&arg.to_lowercase()).unwrap_or_else(|| {
+                    println!("unknown panel position \"{}\" (expected bottom, side or hidden)", arg);
+                    process::exit(1);
+                });
+            }
+            "--theme" => {
+                parsed_args.theme = theme::theme_index(&arg.to_lowercase()).unwrap_or_else(|| {
+                    let names: Vec<&str> = THEMES.iter().map(|theme| theme.name).collect();
+                    println!("unknown theme \"{}\" (expected {})", arg, names.join(", "));
+                    process::exit(1);
+                });
+            }
+            "--fps" | "--scale" => {
+                if let Ok(val) = arg.parse::<u32>() {
+                    if last_arg == "--fps" {
+                        parsed_args.fps = val;
+                    } else {
+                        parsed_args.scale = val;
+                    }
+                }
+            }
+            _ => ()
+        }
+        last_arg = arg;
+    }
+    if parsed_args.rule_table.is_some() {
+        parsed_args.automaton = Automaton::Table;
+    }
+    if parsed_args.recover {
+        if parsed_args.load_path.is_some() {
+            println!("--recover loads the board from the last game, so it can't be used with --load");
+            process::exit(1);
+        }
+        let path = recovery_path().filter(|path| std::path::Path::new(path).exists()).unwrap_or_else(|| {
+            println!("there's no board to recover, it gets saved whenever a game ends");
+            process::exit(1);
+        });
+        parsed_args.load_path = Some(path);
+    }
+    if parsed_args.rival_ai && parsed_args.automaton != Automaton::Immigration {
+        println!("--ai plays player two in immigration, so it needs --automaton immigration");
+        process::exit(1);
+    }
+    if parsed_args.puzzle.is_some() && (parsed_args.automaton != Automaton::Life || parsed_args.load_path.is_some()) {
+        println!("puzzles are played in Life on a board of their own, so --puzzle can't be used with other automata, --rule-file or --load");
+        process::exit(1);
+    }
+    if parsed_args.demo && (parsed_args.load_path.is_some() || parsed_args.puzzle.is_some()) {
+        println!("--demo picks its own patterns, so it can't be used with --load or --puzzle");
+        process::exit(1);
+    }
+    if parsed_args.infinite && parsed_args.puzzle.is_some() {
+        println!("puzzles have to fit on their board, so --infinite can't be used with --puzzle");
+        process::exit(1);
+    }
+    return parsed_args;
+}
+
+
+// exits with a warning if a board this big could use more memory than --memory-limit allows (unless --force was given), so that we don't get killed halfway through for running out
+// also makes sure that every cell can be given a Point
+fn check_board_size(args: &Args, width: u32, height: u32, bytes_per_cell: u64) {
+    if width > i16::MAX as u32 || height > i16::MAX as u32 {
+        eprintln!("a {}x{} board is too big, boards can't be more than {} cells wide or tall", width, height, i16::MAX);
+        process::exit(1);
+    }
+    let estimated_bytes = width as u64 * height as u64 * bytes_per_cell;
+    if estimated_bytes > args.memory_limit_mb * 1024 * 1024 && !args.force {
+        eprintln!(
+            "a {}x{} board could use about {:.1} GB of memory, which is more than the limit of {} MB\n\
+            use --force to run it anyway or --memory-limit <MB> to change the limit",
+            width, height, estimated_bytes as f64 / (1024.0 * 1024.0 * 1024.0), args.memory_limit_mb
+        );
+        process::exit(1);
+    }
+}
+
+
+// sets up a board with the --pattern file on it for the subcommands that run without the UI
+// unless a size is given the board is the size of the pattern plus some padding
+fn headless_board(args: &Args, usage: &str) -> Board {
+    let pattern_path = args.pattern_path.as_ref().unwrap_or_else(|| {
+        eprintln!("{}", usage); // stderr since stdout might be where a subcommand's output is going
+        process::exit(1);
+    });
+    let pattern = match pattern_path.as_str() {
+        STDIN_PATH => read_stdin_pattern(),
+        _ => manifest::load_pattern_or_manifest(pattern_path)
+    };
+    let pattern = pattern.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+
+    // use the pattern's own rule unless one was given
+    let rule = match (&args.rule, &pattern.rule) {
+        (Some(rule), _) => rule.clone(),
+        (None, Some(rule_string)) if uses_rule(args.automaton) => Rule::parse(rule_string).unwrap_or_else(|message| { // other automata don't use the rule
+            eprintln!("{}: invalid rule in pattern: {}", pattern_path, message);
+            process::exit(1);
+        }),
+        (None, _) => Rule::conways_life()
+    };
+
+    let width = args.board_width.map(|width| width as u32).unwrap_or(pattern.width + 2 * PATTERN_PADDING);
+    let height = args.board_height.map(|height| height as u32).unwrap_or(pattern.height + 2 * PATTERN_PADDING);
+    check_board_size(args, width, height, SIMULATION_BYTES_PER_CELL);
+    let mut board = Board::new(width, height, args.automaton, rule);
+    board.table = args.rule_table.clone();
+    board.place_pattern(&pattern, &Point{
+        x: (width as i16 - pattern.width as i16) / 2,
+        y: (height as i16 - pattern.height as i16) / 2
+    });
+    return board;
+}
+
+
+// the board on --engine (the incremental one unless it says otherwise) for the subcommands that run without the UI, exits if that engine can't run it
+fn headless_engine(args: &Args, board: Board) -> Box<dyn Engine> {
+    return args.engine.unwrap_or(EngineKind::Incremental).engine_for(board).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+}
+
+
+// a pattern piped in with "-" instead of a file, the format is guessed from what it looks like since there's no extension to go by
+// this has to happen before the game starts reading keys (which then come from the terminal instead of stdin)
+fn read_stdin_pattern() -> Result<Pattern, String> {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text).map_err(|error| format!("couldn't read stdin: {}", error))?;
+    return pattern::parse_pattern(&text, PatternFormat::detect(&text)).map_err(|message| format!("stdin: {}", message));
+}
+
+
+// reads a file to load (a pattern or a manifest, or a pattern from stdin)
+fn read_load_file(path: &str) -> Result<(Pattern, Option<Manifest>), String> {
+    let loaded = if path == STDIN_PATH {
+        read_stdin_pattern().map(|pattern| (pattern, None))
+    } else if manifest::is_manifest(path) {
+        manifest::load_manifest(path).and_then(|mut manifest| {
+            let pattern = manifest.compose()?;
+            return Ok((pattern, Some(manifest)));
+        })
+    } else {
+        pattern::load_pattern(path).map(|pattern| (pattern, None))
+    };
+    return loaded;
+}
+
+
+// puts a loaded file on the board (in the middle, unless it's a manifest) and uses its rule if no other rule was given
+fn load_file(board: &mut Board, path: String, pattern: &Pattern, manifest: Option<Manifest>, rule_given: bool) -> Result<LoadedFile, String> {
+    if let (Some(rule_string), false, true) = (&pattern.rule, rule_given, uses_rule(board.automaton)) { // other automata don't use the rule
+        board.rule = Rule::parse(rule_string).map_err(|message| format!("{}: invalid rule: {}", path, message))?;
+    }
+    let offset = match manifest {
+        Some(_) => Point{x: 0, y: 0},
+        None => Point{
+            x: (board.width as i16 - pattern.width as i16) / 2,
+            y: (board.height as i16 - pattern.height as i16) / 2
+        }
+    };
+    board.place_pattern(pattern, &offset);
+    return Ok(LoadedFile { path, manifest, offset });
+}
+
+
+// says how the board was changed to fit the --load file, or how much of it got cut off if it still didn't fit
+fn load_fit_message(board: &Board, loaded_file: &LoadedFile, pattern: &Pattern, requested_width: u32, requested_height: u32) -> String {
+    let cut_off = pattern.cells.iter()
+        .filter(|(cell, _)| {
+            let (x, y) = (loaded_file.offset.x + cell.x, loaded_file.offset.y + cell.y);
+            return x < 0 || y < 0 || x >= board.width as i16 || y >= board.height as i16;
+        })
+        .count();
+    let name = if loaded_file.path == STDIN_PATH { "the pattern from stdin" } else { &loaded_file.path };
+    if cut_off > 0 {
+        return format!(
+            "{} is {}x{} so {} cells didn't fit on the {}x{} board (a bigger terminal would fit more)",
+            name, pattern.width, pattern.height, cut_off, board.width, board.height
+        );
+    }
+    if board.width != requested_width || board.height != requested_height {
+        return format!("Grew the board to {}x{} to fit {}", board.width, board.height, name);
+    }
+    return String::new();
+}
+
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    // subcommands that run without the UI
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("stats") => {
+            stats::run_stats(parse_args(args));
+            return;
+        }
+        Some("convert") => {
+            convert::run_convert(&args[2..]);
+            return;
+        }
+        Some("render") => {
+            let input = args.get(2).filter(|arg| !arg.starts_with('-')).cloned(); // the pattern can be given without --pattern
+            let mut render_args = parse_args(args);
+            render_args.pattern_path = render_args.pattern_path.or(input);
+            render::run_render(render_args);
+            return;
+        }
+        Some("verify") => {
+            verify::run_verify(parse_args(args));
+            return;
+        }
+        Some("bench") => {
+            bench::run_bench(parse_args(args));
+            return;
+        }
+        Some("census") => {
+            census::run_census(parse_args(args));
+            return;
+        }
+        Some("search") => {
+            search::run_search(parse_args(args));
+            return;
+        }
+        Some("screensaver") => {
+            screensaver::run_screensaver(parse_args(args));
+            return;
+        }
+        Some("compare") => {
+            compare::run_compare(parse_args(args));
+            return;
+        }
+        Some("leaderboard") => {
+            leaderboard::print_leaderboard();
+            return;
+        }
+        #[cfg(feature = "framebuffer")]
+        Some("framebuffer") => {
+            let input = args.get(2).filter(|arg| !arg.starts_with('-')).cloned();
+            let mut framebuffer_args = parse_args(args);
+            framebuffer_args.pattern_path = framebuffer_args.pattern_path.or(input);
+            framebuffer::run_framebuffer(framebuffer_args);
+            return;
+        }
+        _ => ()
+    }
+
+    // a replay is played with the arguments it was recorded with
+    let recorded_args = replay::recorded_args(&args[1..]);
+    let args = parse_args(args);
+    if args.emit.is_some() { // no UI, so this has to happen before anything needs a terminal
+        emit::run_emit(args);
+        return;
+    }
+    let replay = args.replay_path.as_ref().map(|path| Replay::load(path).unwrap_or_else(|message| {
+        println!("{}", message);
+        process::exit(1);
+    }));
+    let mut args = match &replay {
+        Some(replay) => parse_args(replay.args.clone()),
+        None => args
+    };
+    let mut puzzle = args.puzzle.take();
+    let (mut defualt_board_width, mut default_board_height) = match (args.board_width, args.board_height) {
+        (Some(width), Some(height)) => (width, height), // the defaults aren't needed, and with --plain there might not be a terminal to size them from
+        _ => default_board_dimensions(args.panel)
+    };
+    let graphics = Graphics::detect(args.graphics);
+    let pixels = args.graphics && graphics.is_some();
+    if let (true, Some(graphics)) = (pixels, &graphics) {
+        (defualt_board_width, default_board_height) = graphics.cells_in(defualt_board_width, default_board_height);
+    } else if args.braille { // each character holds 2x4 cells
+        defualt_board_width = defualt_board_width.saturating_mul(2);
+        default_board_height = default_board_height.saturating_mul(4);
+    } else if uses_rule(args.automaton) && args.rule.as_ref().is_some_and(|rule| rule.neighbourhood == Neighbourhood::Hexagonal) { // hex grids take 2 characters per cell and lean over
+        defualt_board_width = ((defualt_board_width + 2).saturating_sub(default_board_height) / 2).max(1);
+    }
+    let rule_given = args.rule.is_some();
+    let requested_width = args.board_width.unwrap_or(defualt_board_width) as u32;
+    let requested_height = args.board_height.unwrap_or(default_board_height) as u32;
+
+    // the --load file (or the puzzle) is read before the board is made so that the board can be grown to fit it (with some room around it)
+    // but only as far as the terminal goes, anything bigger couldn't be shown
+    let load = args.load_path.clone().map(|path| {
+        let (pattern, manifest) = read_load_file(&path).unwrap_or_else(|message| {
+            println!("{}", message);
+            process::exit(1);
+        });
+        return (path, pattern, manifest);
+    });
+    let fit = match (&load, &puzzle) {
+        (Some((_, pattern, _)), _) => Some((pattern.width, pattern.height)),
+        (None, Some(puzzle)) => Some((puzzle.width, puzzle.height)),
+        (None, None) => None
+    };
+    let (board_width, board_height) = match (fit, &replay) {
+        (_, Some(replay)) => (replay.width, replay.height), // the terminal might be a different size now
+        (Some((width, height)), None) => (
+            requested_width.max((width + 2 * LOAD_PADDING).min(defualt_board_width as u32)),
+            requested_height.max((height + 2 * LOAD_PADDING).min(default_board_height as u32))
+        ),
+        (None, None) => (requested_width, requested_height)
+    };
+    check_board_size(&args, board_width, board_height, SIMULATION_BYTES_PER_CELL + if pixels { PIXEL_BYTES_PER_CELL } else { PRINTED_BYTES_PER_CELL });
+    let mut board = Board::new(
+        board_width, 
+        board_height,
+        args.automaton,
+        args.rule.or_else(|| puzzle.as_ref().and_then(|puzzle| puzzle.rule.clone())).unwrap_or_else(Rule::conways_life)
+    );
+    board.table = args.rule_table;
+
+    // start with the --load file if there is one, otherwise the pattern of the day or a random board
+    let mut message = match (args.graphics, graphics) {
+        (true, None) => "This terminal can't draw images (kitty graphics, iTerm2 or sixels), using characters instead".to_string(),
+        _ => String::new()
+    };
+    let loaded_file = load.map(|(path, pattern, manifest)| {
+        let loaded_file = load_file(&mut board, path, &pattern, manifest, rule_given).unwrap_or_else(|message| {
+            println!("{}", message);
+            process::exit(1);
+        });
+        message = load_fit_message(&board, &loaded_file, &pattern, requested_width, requested_height);
+        return loaded_file;
+    });
+    let pattern_of_the_day = match (args.pattern_of_the_day && loaded_file.is_none() && puzzle.is_none() && !args.demo, &replay) {
+        (true, Some(replay)) => { // the one from the day it was recorded
+            let name = replay.pattern_of_the_day.clone().unwrap_or_default();
+            Some(bundled::find(&name).unwrap_or_else(|| {
+                println!("the replay's pattern of the day \"{}\" isn't in this version of the game", name);
+                process::exit(1);
+            }))
+        }
+        (true, None) => Some(bundled::pattern_of_the_day()),
+        (false, _) => None
+    };
+    if let Some(replay) = &replay {
+        message = format!("Replaying {} (Q stops it)", replay.path);
+    }
+    let seed = replay.as_ref().map(|replay| replay.seed).unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    if let Some(bundled_pattern) = pattern_of_the_day {
+        let pattern = pattern::parse_rle(bundled_pattern.rle).unwrap(); // bundled patterns are always valid
+        board.automaton = Automaton::Life; // they're all life patterns
+        board.place_pattern(&pattern, &Point{
+            x: (board.width as i16 - pattern.width as i16) / 2,
+            y: (board.height as i16 - pattern.height as i16) / 2
+        });
+    } else if let Some(puzzle) = &mut puzzle {
+        puzzle.set_up(&mut board);
+        message = format!("{}: edit the board so it turns into the target, then press Space to try", puzzle.name);
+    } else if loaded_file.is_none() && !args.demo { // the demo puts its first pattern down once the game starts
+        board.init_randomly_with(&mut rng);
+    }
+    let recorder = args.record_path.as_ref().map(|path| {
+        return Recorder::create(path, seed, &recorded_args, board.width, board.height, pattern_of_the_day.map(|pattern| pattern.name)).unwrap_or_else(|message| {
+            println!("{}", message);
+            process::exit(1);
+        });
+    });
+
+    let key_source = match (terminal::key_source(), args.plain) {
+        (Ok(key_source), _) => Some(key_source),
+        (Err(_), true) => None, // nothing to read keys from (like in CI), so it stops by itself after --generations generations
+        (Err(message), false) => {
+            println!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    // make sure the terminal always gets put back to normal, even if we panic or get killed
+    terminal::save_terminal_state();
+    terminal::install_panic_hook();
+    terminal::install_signal_handlers();
+    let _terminal_guard = terminal::TerminalGuard; // declared before stdout so that it gets dropped after it
+
+    // switch to alternate screen buffer and enter raw mode, unless it's --plain (then keys only arrive when enter is pressed)
+    let mut stdout: Box<dyn Write> = match args.plain {
+        true => Box::new(io::stdout()),
+        false => Box::new(terminal::enter_raw_mode().unwrap_or_else(|message| { // raw mode means don't echo every key we press, don't move the cursor when we press keys, etc
+            println!("{}", message);
+            process::exit(1);
+        }))
+    };
+
+    // keys get read on another thread so that the game loop can check for them without blocking (or block with a timeout when it wants to)
+    let generation_limit = if key_source.is_none() { Some(args.generations) } else { None };
+    let key_input = match key_source {
+        Some(key_source) => terminal::spawn_key_reader(key_source),
+        None => mpsc::channel().1 // the sender's dropped straight away, no keys are ever coming
+    };
+
+    let replaying = replay.is_some();
+    let options = GameOptions {
+        loaded_file,
+        pattern_of_the_day,
+        generations_per_frame: args.generations_per_frame,
+        theme: args.theme,
+        braille: args.braille,
+        graphics,
+        pixels,
+        screenshot_scale: args.scale,
+        rng,
+        rival_ai: args.rival_ai,
+        vim_keys: args.vim_keys,
+        panel: args.panel,
+        puzzle,
+        demo: args.demo,
+        recorder,
+        replay,
+        message,
+        generation_limit,
+        confirm: args.confirm,
+        memory_limit: if args.force { None } else { Some(args.memory_limit_mb * 1024 * 1024) },
+        infinite: args.infinite,
+        grid: args.grid
+    };
+    let mut board = IncrementalBoard::new(board); // so generations only look near the last one's changes once the board settles down
+    // a panic gets caught just long enough to save the board for --recover
+    let columns = terminal::terminal_size().map(|(width, _)| width).unwrap_or(u16::MAX);
+    let mut renderer = renderer::for_terminal(&mut stdout, ColourDepth::detect(), args.theme, columns, options.graphics);
+    let played = panic::catch_unwind(panic::AssertUnwindSafe(|| play_game(&mut board, &key_input, renderer.as_mut(), options)));
+    drop(renderer);
+    let recovery_saved = match replaying {
+        true => None, // replays don't write files
+        false => Some(save_recovery_file(&board))
+    };
+    let (summary, game_result) = match played {
+        Ok(played) => played,
+        Err(panic) => { // the panic hook has already put the terminal back and printed the panic
+            match recovery_saved {
+                Some(Ok(path)) => println!("The board was saved to {}, --recover brings it back", path),
+                Some(Err(message)) => println!("{}", message),
+                None => ()
+            }
+            panic::resume_unwind(panic);
+        }
+    };
+    let screenshot_message = match args.screenshot_on_exit {
+        true => Some(render::save_screenshot(&board, args.scale, &render::screenshot_path()).unwrap_or_else(|message| message)),
+        false => None
+    };
+
+    // reset terminal to exit
+    if args.plain { // the last frame stays on the normal screen, so just get the cursor out from under it
+        let bottom = terminal::terminal_size().map(|(_, height)| height).unwrap_or(1);
+        write!(stdout, "{}{}{}\r\n", termion::style::Reset, termion::cursor::Show, termion::cursor::Goto(1, bottom)).ok();
+    } else {
+        write!(stdout, 
+            "{}{}{}", 
+            termion::cursor::Show, // make cursor visible again
+            termion::cursor::Goto(1,1), // move cursor back to a reasonable place (useful because some terminals won't exit the alternate screen buffer properly (maybe they only have 1 buffer?))
+            termion::clear::All // also for screens that don't exit the alternate screen properly
+        ).ok();
+    }
+    stdout.flush().ok(); // if the terminal's gone there's nothing to clean up anyway
+
+    // printed after leaving the alternate screen so that it's still there afterwards
+    drop(stdout);
+    if let Some(message) = screenshot_message {
+        println!("{}", message);
+    }
+    println!("{}", summary);
+    if let Some(Err(message)) = recovery_saved {
+        println!("{}", message);
+    }
+    match game_result {
+        Some(Ok(message)) => println!("{}", message),
+        Some(Err(message)) => {
+            println!("{}", message);
+            process::exit(1); // so scripts using replays as tests can tell
+        }
+        None => ()
+    }
 }
@@ -0,0 +1,205 @@
+use std::fs;
+use std::collections::HashSet;
+use std::path::Path;
+use crate::{Automaton, Board, Point, Rule, ALIVE};
+use crate::pattern::{self, Pattern, PatternFormat};
+
+
+
+// a manifest describes a construction made out of several pattern files, one line per instruction:
+//     # comment
+//     rule B3/S23
+//     place glider.rle at 10 5 rotate 90 flip-x phase 2
+// pattern paths are relative to the manifest (or can be apgcodes like xq4_153), transforms are applied in the order: phase, flips, rotation
+// keeping each piece in its own file means a construction can be put in version control and edited piece by piece
+pub struct Manifest {
+    pub rule: Option<String>,
+    pub components: Vec<Component>,
+}
+
+
+pub struct Component {
+    pub path: String, // relative to the current directory (not the manifest)
+    pub position: Point, // where the top left of the (transformed) pattern goes
+    pub quarter_turns: u8, // clockwise
+    pub flip_x: bool, // mirror left to right
+    pub flip_y: bool, // mirror top to bottom
+    pub phase: u32, // generations to run the pattern for before placing it
+    width: u32, // size of the transformed pattern, used to work out which cells belong to this component when saving
+    height: u32,
+}
+
+
+pub fn is_manifest(path: &str) -> bool {
+    return path.ends_with(".manifest");
+}
+
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, what: &str, line_num: usize) -> Result<T, String> {
+    return word
+        .and_then(|word| word.parse().ok())
+        .ok_or(format!("line {}: expected {}", line_num + 1, what));
+}
+
+
+pub fn load_manifest(path: &str) -> Result<Manifest, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("couldn't read {}: {}", path, error))?;
+    let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut manifest = Manifest { rule: None, components: Vec::new() };
+    for (line_num, line) in text.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => (),
+            Some(word) if word.starts_with('#') => (),
+            Some("rule") => manifest.rule = Some(words.next().ok_or(format!("line {}: expected a rule", line_num + 1))?.to_string()),
+            Some("place") => {
+                let pattern_path = words.next().ok_or(format!("line {}: expected a pattern file", line_num + 1))?;
+                let path = match pattern::is_apgcode(pattern_path) && !directory.join(pattern_path).exists() {
+                    true => pattern_path.to_string(), // an apgcode instead of a file
+                    false => directory.join(pattern_path).to_string_lossy().to_string()
+                };
+                let mut component = Component {
+                    path,
+                    position: Point{x: 0, y: 0},
+                    quarter_turns: 0,
+                    flip_x: false,
+                    flip_y: false,
+                    phase: 0,
+                    width: 0,
+                    height: 0,
+                };
+                while let Some(word) = words.next() {
+                    match word {
+                        "at" => component.position = Point{
+                            x: parse_number(words.next(), "an x coordinate", line_num)?,
+                            y: parse_number(words.next(), "a y coordinate", line_num)?
+                        },
+                        "rotate" => {
+                            let degrees: u32 = parse_number(words.next(), "a rotation in degrees", line_num)?;
+                            if !degrees.is_multiple_of(90) {
+                                return Err(format!("line {}: rotations have to be a multiple of 90 degrees", line_num + 1));
+                            }
+                            component.quarter_turns = (degrees / 90 % 4) as u8;
+                        }
+                        "flip-x" => component.flip_x = true,
+                        "flip-y" => component.flip_y = true,
+                        "phase" => component.phase = parse_number(words.next(), "a number of generations", line_num)?,
+                        _ => return Err(format!("line {}: unknown option \"{}\"", line_num + 1, word))
+                    }
+                }
+                manifest.components.push(component);
+            }
+            Some(word) => return Err(format!("line {}: unknown instruction \"{}\"", line_num + 1, word))
+        }
+    }
+    return Ok(manifest);
+}
+
+
+// runs a pattern on its own for some generations (with enough room around it that the edges of the board don't matter)
+fn advance_pattern(pattern: &Pattern, generations: u32, rule: &Rule) -> Pattern {
+    let padding = generations + 1; // nothing can spread faster than 1 cell per generation
+    let mut board = Board::new(pattern.width + 2 * padding, pattern.height + 2 * padding, Automaton::Life, rule.clone());
+    board.place_pattern(pattern, &Point{x: padding as i16, y: padding as i16});
+    for _ in 0..generations {
+        board.update_cells();
+    }
+    let cells = board.cells.into_iter().collect();
+    return Pattern::from_cells(cells, pattern.rule.clone());
+}
+
+
+impl Manifest {
+    pub fn rule(&self) -> Result<Rule, String> {
+        return match &self.rule {
+            Some(rule_string) => Rule::parse(rule_string),
+            None => Ok(Rule::conways_life())
+        };
+    }
+
+    // loads every component and puts them all together into one big pattern (with the same coordinates as the manifest)
+    pub fn compose(&mut self) -> Result<Pattern, String> {
+        let rule = self.rule()?;
+        let mut cells = Vec::new();
+        for component in self.components.iter_mut() {
+            let mut piece = pattern::load_pattern(&component.path)?;
+            if component.phase > 0 {
+                piece = advance_pattern(&piece, component.phase, &rule);
+            }
+            if component.flip_x {
+                piece = piece.flipped_horizontally();
+            }
+            if component.flip_y {
+                piece = piece.flipped_vertically();
+            }
+            for _ in 0..component.quarter_turns {
+                piece = piece.rotated_clockwise();
+            }
+            component.width = piece.width;
+            component.height = piece.height;
+            for (cell, state) in piece.cells {
+                cells.push((Point{x: cell.x + component.position.x, y: cell.y + component.position.y}, state));
+            }
+        }
+        let width = cells.iter().map(|(cell, _): &(Point, u8)| cell.x.max(0) as u32 + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|(cell, _): &(Point, u8)| cell.y.max(0) as u32 + 1).max().unwrap_or(0);
+        return Ok(Pattern { width, height, cells, rule: self.rule.clone() });
+    }
+
+    // writes the cells on the board back to the pattern files they came from
+    // a cell belongs to the first component whose area it's in (where the board is offset from the manifest's coordinates)
+    // components with a phase get skipped since there's no way to un-run a pattern, and files used by more than one component only get saved from the first one
+    // returns how many components were saved
+    pub fn save_components(&self, board: &Board, offset: &Point) -> Result<usize, String> {
+        let mut component_cells: Vec<Vec<(Point, u8)>> = vec![Vec::new(); self.components.len()];
+        for (cell, &state) in &board.cells {
+            if state != ALIVE {
+                continue; // pattern files can't hold dying cells
+            }
+            let x = cell.x - offset.x;
+            let y = cell.y - offset.y;
+            let owner = self.components.iter().position(|component| {
+                x >= component.position.x && y >= component.position.y
+                    && x < component.position.x + component.width as i16
+                    && y < component.position.y + component.height as i16
+            });
+            if let Some(owner) = owner {
+                let position = &self.components[owner].position;
+                component_cells[owner].push((Point{x: x - position.x, y: y - position.y}, state));
+            }
+        }
+
+        let mut num_saved = 0;
+        let mut saved_paths = HashSet::new();
+        for (component, cells) in self.components.iter().zip(component_cells) {
+            if component.phase > 0 || pattern::is_apgcode(&component.path) || !saved_paths.insert(&component.path) { // apgcodes aren't files
+                continue;
+            }
+            // undo the transforms in reverse order
+            let mut piece = Pattern { width: component.width, height: component.height, cells, rule: self.rule.clone() };
+            for _ in 0..(4 - component.quarter_turns) % 4 {
+                piece = piece.rotated_clockwise();
+            }
+            if component.flip_y {
+                piece = piece.flipped_vertically();
+            }
+            if component.flip_x {
+                piece = piece.flipped_horizontally();
+            }
+            let format = PatternFormat::from_path(&component.path).unwrap_or(PatternFormat::Rle);
+            fs::write(&component.path, pattern::write_pattern(&piece, format))
+                .map_err(|error| format!("couldn't write {}: {}", component.path, error))?;
+            num_saved += 1;
+        }
+        return Ok(num_saved);
+    }
+}
+
+
+// loads either a pattern file or a manifest as one pattern
+pub fn load_pattern_or_manifest(path: &str) -> Result<Pattern, String> {
+    if is_manifest(path) {
+        return load_manifest(path)?.compose().map_err(|message| format!("{}: {}", path, message));
+    }
+    return pattern::load_pattern(path);
+}
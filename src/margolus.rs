@@ -0,0 +1,80 @@
+use core::convert::TryInto;
+use core::fmt;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+
+
+// the board gets split into 2x2 blocks, and each block gets replaced by what the rule says that block turns into
+// blocks are numbered by which cells are alive: 1 for the top left, 2 for the top right, 4 for the bottom left and 8 for the bottom right
+// every other generation the blocks are shifted down and right by one cell so that things can move between them
+#[derive(PartialEq, Clone, Copy)]
+pub struct BlockRule {
+    pub table: [u8; 16], // table[block] is what block turns into
+}
+
+
+// well known block rules (in MCell's notation)
+pub const BLOCK_RULES: [(&str, &str); 2] = [
+    ("Critters", "MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0"), // reversible, gliders bounce around and wreck whatever they hit
+    ("Tron", "MS,D15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0"), // reversible, squares grow out into patterns of nested lines
+];
+
+
+impl BlockRule {
+    pub fn critters() -> BlockRule {
+        return BlockRule::parse(BLOCK_RULES[0].1).unwrap();
+    }
+
+    // a well known rule's name (any case) or a table in MCell's notation (MS,D followed by what each of the 16 blocks turns into)
+    // rules that fill in empty blocks have to also empty full blocks (like critters does), since then the board can be shown inverted every other generation instead of flashing
+    pub fn parse(rule_string: &str) -> Result<BlockRule, String> {
+        let rule_string = rule_string.trim();
+        if let Some((_, table)) = BLOCK_RULES.iter().find(|(name, _)| name.eq_ignore_ascii_case(rule_string)) {
+            return BlockRule::parse(table);
+        }
+        let invalid = || format!("\"{}\" isn't a block rule (expected critters, tron or MS,D followed by 16 numbers split up with semicolons)", rule_string);
+        let table_text = rule_string.strip_prefix("MS,D").or_else(|| rule_string.strip_prefix("ms,d")).ok_or_else(invalid)?;
+        let numbers: Vec<u8> = table_text.split(';')
+            .map(|number| number.trim().parse::<u8>().ok().filter(|&block| block < 16))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let table: [u8; 16] = numbers.try_into().map_err(|_| invalid())?;
+        if table[0] != 0 && (table[0] != 15 || table[15] != 0) {
+            return Err("block rules that fill in empty blocks also have to empty full blocks".to_string());
+        }
+        return Ok(BlockRule { table });
+    }
+
+    // what each block turns into when the blocks are lined up with the top left corner (or shifted by one when shifted is true)
+    // when empty blocks turn into full ones, every other generation is stored inverted so that empty space stays empty
+    pub fn table(&self, shifted: bool) -> [u8; 16] {
+        if self.table[0] == 0 {
+            return self.table;
+        }
+        let mut table = [0; 16];
+        for (block, turns_into) in table.iter_mut().enumerate() {
+            *turns_into = match shifted {
+                false => !self.table[block] & 15, // stored normally, turns into inverted
+                true => self.table[!block & 15], // stored inverted, turns back into normal
+            };
+        }
+        return table;
+    }
+
+    pub fn name(&self) -> String {
+        return match BLOCK_RULES.iter().find(|(_, table)| BlockRule::parse(table).as_ref() == Ok(self)) {
+            Some((name, _)) => name.to_string(),
+            None => self.to_string()
+        };
+    }
+}
+
+
+impl fmt::Display for BlockRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let numbers: Vec<String> = self.table.iter().map(|block| block.to_string()).collect();
+        return write!(f, "MS,D{}", numbers.join(";"));
+    }
+}
@@ -0,0 +1,292 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::board::CellSet;
+use crate::pattern::{self, Pattern};
+use crate::{Automaton, Board, Neighbourhood, Point, Rule};
+
+
+
+const MAX_PERIOD: u32 = 64; // objects that don't repeat within this many generations are left unclassified
+
+// well known objects in conway's life, by apgcode
+const KNOWN_OBJECTS: [(&str, &str); 23] = [
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs4_252", "tub"),
+    ("xs6_356", "ship"),
+    ("xs8_6996", "pond"),
+    ("xs7_25ac", "long boat"),
+    ("xs6_25a4", "barge"),
+    ("xs7_178c", "eater 1"),
+    ("xs6_bd", "snake"),
+    ("xs8_69ic", "mango"),
+    ("xs6_39c", "aircraft carrier"),
+    ("xs9_31ego", "integral sign"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar"),
+    ("xp15_4r4z4r4", "pentadecathlon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+    ("xq4_27dee6", "middleweight spaceship"),
+    ("xq4_27deee6", "heavyweight spaceship"),
+];
+
+
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator(u32), // period
+    Spaceship { period: u32, dx: i16, dy: i16 }, // moves dx,dy every period generations
+    Dies, // doesn't last on its own (like a piece of something that hasn't settled down yet)
+    Unclassified, // didn't repeat within MAX_PERIOD generations (or grew too far to tell)
+}
+
+
+// a group of cells on the board that keeps to itself, and what it does when left alone
+pub struct Object {
+    pub position: Point, // top left of the object on the board
+    pub cells: Vec<(Point, u8)>, // relative to position
+    pub kind: ObjectKind,
+    pub apgcode: Option<String>, // catagolue's name for it, only for 2 state rules on a square grid
+    pub name: Option<&'static str>, // for well known objects in conway's life
+}
+
+
+// how far away the furthest neighbour in the rule's neighbourhood is
+fn rule_range(rule: &Rule) -> i16 {
+    return rule.neighbourhood.offsets().iter().map(|(x, y)| x.abs().max(y.abs())).max().unwrap_or(1);
+}
+
+
+// splits cells up into groups where every cell is within distance (in both x and y) of another cell in the same group
+fn clusters(cells: &[(Point, u8)], distance: i16) -> Vec<Vec<(Point, u8)>> {
+    let states: BTreeMap<Point, u8> = cells.iter().cloned().collect();
+    let mut seen = CellSet::new();
+    let mut clusters = Vec::new();
+    for (start, _) in cells {
+        if seen.contains(start) {
+            continue;
+        }
+        seen.insert(start.clone());
+        let mut cluster = Vec::new();
+        let mut to_visit = vec![start.clone()];
+        while let Some(cell) = to_visit.pop() {
+            for y in cell.y - distance..=cell.y + distance {
+                for x in cell.x - distance..=cell.x + distance {
+                    let nearby = Point{x, y};
+                    if states.contains_key(&nearby) && !seen.contains(&nearby) {
+                        seen.insert(nearby.clone());
+                        to_visit.push(nearby);
+                    }
+                }
+            }
+            let state = states[&cell];
+            cluster.push((cell, state));
+        }
+        cluster.sort();
+        clusters.push(cluster);
+    }
+    return clusters;
+}
+
+
+// runs cells on their own for up to MAX_PERIOD generations and returns every generation (sorted so they can be compared)
+// stops early if everything dies or if it gets close enough to the edge of its board to be affected by it
+fn history(cells: &[(Point, u8)], rule: &Rule) -> Vec<Vec<(Point, u8)>> {
+    let margin = MAX_PERIOD as i16 * rule_range(rule) + 1; // even the speed of light can't get to the edge in time
+    let min_x = cells.iter().map(|(cell, _)| cell.x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|(cell, _)| cell.y).min().unwrap_or(0);
+    let width = cells.iter().map(|(cell, _)| cell.x - min_x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|(cell, _)| cell.y - min_y + 1).max().unwrap_or(0);
+    let mut board = Board::new((width + 2 * margin) as u32, (height + 2 * margin) as u32, Automaton::Life, rule.clone());
+    for (cell, state) in cells {
+        board.cells.insert(Point{x: cell.x - min_x + margin, y: cell.y - min_y + margin}, *state);
+    }
+
+    let mut history = vec![cells.to_vec()];
+    for _ in 0..MAX_PERIOD {
+        board.update_cells();
+        let touching_edge = board.cells.keys().any(|cell| cell.x == 0 || cell.y == 0 || cell.x as u32 == board.width - 1 || cell.y as u32 == board.height - 1);
+        if touching_edge {
+            break;
+        }
+        let mut generation: Vec<(Point, u8)> = board.cells.iter()
+            .map(|(cell, &state)| (Point{x: cell.x - margin + min_x, y: cell.y - margin + min_y}, state))
+            .collect();
+        generation.sort();
+        let died = generation.is_empty();
+        history.push(generation);
+        if died {
+            break;
+        }
+    }
+    return history;
+}
+
+
+// moves cells so their top left is at 0,0, and returns where the top left was
+fn normalised(cells: &[(Point, u8)]) -> (Point, Vec<(Point, u8)>) {
+    let top_left = Point{
+        x: cells.iter().map(|(cell, _)| cell.x).min().unwrap_or(0),
+        y: cells.iter().map(|(cell, _)| cell.y).min().unwrap_or(0)
+    };
+    let cells = cells.iter().map(|(cell, state)| (Point{x: cell.x - top_left.x, y: cell.y - top_left.y}, *state)).collect();
+    return (top_left, cells);
+}
+
+
+// what the first generation in history turns out to be, going by when (and where) the same shape comes back
+fn kind(history: &[Vec<(Point, u8)>]) -> ObjectKind {
+    let (start, shape) = normalised(&history[0]);
+    for (generation, cells) in history.iter().enumerate().skip(1) {
+        if cells.is_empty() {
+            return ObjectKind::Dies;
+        }
+        let (top_left, cells) = normalised(cells);
+        if cells != shape {
+            continue;
+        }
+        let (dx, dy) = (top_left.x - start.x, top_left.y - start.y);
+        return match (generation, dx, dy) {
+            (1, 0, 0) => ObjectKind::StillLife,
+            (period, 0, 0) => ObjectKind::Oscillator(period as u32),
+            (period, dx, dy) => ObjectKind::Spaceship{period: period as u32, dx, dy}
+        };
+    }
+    return ObjectKind::Unclassified;
+}
+
+
+// catagolue's apgcode for an object: xs followed by the population for still lifes, xp or xq followed by the period for oscillators and spaceships,
+// then the shortest (and then alphabetically first) extended wechsler encoding out of every phase in every orientation
+fn apgcode(history: &[Vec<(Point, u8)>], kind: ObjectKind, rule: &Rule) -> Option<String> {
+    if rule.num_states != 2 || matches!(rule.neighbourhood, Neighbourhood::Custom(_) | Neighbourhood::Hexagonal) {
+        return None; // rotating and flipping only keep square neighbourhoods the same
+    }
+    let (prefix, period) = match kind {
+        ObjectKind::StillLife => (format!("xs{}", history[0].len()), 1),
+        ObjectKind::Oscillator(period) => (format!("xp{}", period), period),
+        ObjectKind::Spaceship{period, ..} => (format!("xq{}", period), period),
+        ObjectKind::Dies | ObjectKind::Unclassified => return None
+    };
+    let mut best: Option<String> = None;
+    for phase in &history[..period as usize] {
+        let pattern = Pattern::from_cells(phase.clone(), None);
+        for mut orientation in [pattern.flipped_horizontally(), pattern] {
+            for _ in 0..4 {
+                let code = pattern::to_wechsler(&orientation);
+                if best.as_ref().is_none_or(|best| (code.len(), &code) < (best.len(), best)) {
+                    best = Some(code);
+                }
+                orientation = orientation.rotated_clockwise();
+            }
+        }
+    }
+    return best.map(|code| format!("{}_{}", prefix, code));
+}
+
+
+fn gcd(a: u32, b: u32) -> u32 {
+    return if b == 0 { a } else { gcd(b, a % b) };
+}
+
+
+impl Object {
+    fn new(history: &[Vec<(Point, u8)>], rule: &Rule, is_conways_life: bool) -> Object {
+        let kind = kind(history);
+        let apgcode = apgcode(history, kind, rule);
+        let name = match (is_conways_life, &apgcode) {
+            (true, Some(apgcode)) => KNOWN_OBJECTS.iter().find(|(code, _)| code == apgcode).map(|(_, name)| *name),
+            _ => None
+        };
+        let (position, cells) = normalised(&history[0]);
+        return Object { position, cells, kind, apgcode, name };
+    }
+
+    // "still life", "period 2 oscillator", "c/4 diagonal spaceship" and so on
+    pub fn description(&self) -> String {
+        return match self.kind {
+            ObjectKind::StillLife => "still life".to_string(),
+            ObjectKind::Oscillator(period) => format!("period {} oscillator", period),
+            ObjectKind::Spaceship{period, dx, dy} => {
+                let (dx, dy) = (dx.unsigned_abs() as u32, dy.unsigned_abs() as u32);
+                if dx != 0 && dy != 0 && dx != dy {
+                    return format!("({},{})c/{} oblique spaceship", dx, dy, period);
+                }
+                let distance = dx.max(dy);
+                let divisor = gcd(distance, period);
+                let speed = match distance / divisor {
+                    1 => format!("c/{}", period / divisor),
+                    distance => format!("{}c/{}", distance, period / divisor)
+                };
+                format!("{} {} spaceship", speed, if dx == dy { "diagonal" } else { "orthogonal" })
+            }
+            ObjectKind::Dies => "dies out".to_string(),
+            ObjectKind::Unclassified => format!("unclassified ({} cells)", self.cells.len()),
+        };
+    }
+
+    // the object's name if it has a well known one, then its apgcode, then what it is
+    pub fn label(&self) -> String {
+        return match (self.name, &self.apgcode) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(apgcode)) => apgcode.clone(),
+            (None, None) => self.description()
+        };
+    }
+}
+
+
+// splits a Life board up into objects and works out what each one is by running it on its own
+// cells close enough to affect each other are grouped together, then split back up into pieces if every piece is an object on its own and they don't affect each other (like a blinker next to a block)
+pub fn find_objects(board: &Board) -> Vec<Object> {
+    let rule = &board.rule;
+    let is_conways_life = *rule == Rule::conways_life();
+    let range = rule_range(rule);
+    let mut cells: Vec<(Point, u8)> = board.cells.iter().map(|(cell, &state)| (cell.clone(), state)).collect();
+    cells.sort(); // so objects always come out in the same order
+
+    let mut objects = Vec::new();
+    for cluster in clusters(&cells, 2 * range) { // 2 * range is as far apart as two cells can be and still both affect the cell between them
+        let cluster_history = history(&cluster, rule);
+        let pieces = clusters(&cluster, range);
+        if pieces.len() > 1 {
+            let piece_histories: Vec<Vec<Vec<(Point, u8)>>> = pieces.iter().map(|piece| history(piece, rule)).collect();
+            let piece_objects: Vec<Object> = piece_histories.iter().map(|piece_history| Object::new(piece_history, rule, is_conways_life)).collect();
+            let all_objects = piece_objects.iter().all(|object| !matches!(object.kind, ObjectKind::Dies | ObjectKind::Unclassified));
+            let independent = (0..cluster_history.len()).all(|generation| {
+                let mut together: Vec<(Point, u8)> = piece_histories.iter()
+                    .flat_map(|piece_history| piece_history.get(generation).cloned().unwrap_or_default())
+                    .collect();
+                together.sort();
+                return together == cluster_history[generation];
+            });
+            if all_objects && independent {
+                objects.extend(piece_objects);
+                continue;
+            }
+        }
+        objects.push(Object::new(&cluster_history, rule, is_conways_life));
+    }
+    return objects;
+}
+
+
+// how many of each kind of object there are, most common first
+pub fn census(objects: &[Object]) -> Vec<(usize, &Object)> {
+    let mut counts: BTreeMap<String, (usize, &Object)> = BTreeMap::new();
+    for object in objects {
+        counts.entry(object.label()).or_insert((0, object)).0 += 1;
+    }
+    let mut census: Vec<(usize, &Object)> = counts.into_values().collect();
+    census.sort_by_key(|(count, object)| (core::cmp::Reverse(*count), object.label()));
+    return census;
+}
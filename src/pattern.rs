@@ -0,0 +1,477 @@
+#[cfg(feature = "std")]
+use std::fs;
+use alloc::{format, vec};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::{Point, ALIVE};
+use crate::board::CellMap;
+
+
+
+// a bunch of cells read from a pattern file, positioned relative to the top left of the pattern
+#[derive(Clone)]
+pub struct Pattern {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<(Point, u8)>, // every cell that isn't dead along with its state
+    pub rule: Option<String>, // the rule the pattern says it was made for (if it says)
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum PatternFormat {
+    Rle,
+    Plaintext, // .cells files
+    Life106,
+}
+
+
+impl PatternFormat {
+    // guesses the format from a file extension
+    pub fn from_path(path: &str) -> Option<PatternFormat> {
+        let extension = path.rsplit('.').next()?.to_lowercase();
+        return match extension.as_str() {
+            "rle" => Some(PatternFormat::Rle),
+            "cells" => Some(PatternFormat::Plaintext),
+            "lif" | "life" => Some(PatternFormat::Life106),
+            _ => None
+        };
+    }
+
+    // guesses the format from what the pattern looks like, for patterns that don't come with a file name (like ones piped in)
+    pub fn detect(text: &str) -> PatternFormat {
+        if text.starts_with("#Life 1.06") {
+            return PatternFormat::Life106;
+        }
+        let first_line = match text.lines().map(|line| line.trim()).find(|line| !line.is_empty() && !line.starts_with('#')) {
+            Some(line) => line,
+            None => return PatternFormat::Rle
+        };
+        if first_line.starts_with('!') || first_line.chars().all(|c| matches!(c, '.' | 'O' | 'o' | '*')) {
+            return PatternFormat::Plaintext;
+        }
+        let words: Vec<&str> = first_line.split_whitespace().collect();
+        if words.len() == 2 && words.iter().all(|word| word.parse::<i16>().is_ok()) {
+            return PatternFormat::Life106;
+        }
+        return PatternFormat::Rle;
+    }
+}
+
+
+impl Pattern {
+    // moves cells so the top left of the pattern is at 0,0 and works out the pattern's size
+    pub fn from_cells(mut cells: Vec<(Point, u8)>, rule: Option<String>) -> Pattern {
+        let min_x = cells.iter().map(|(cell, _)| cell.x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|(cell, _)| cell.y).min().unwrap_or(0);
+        for (cell, _) in cells.iter_mut() {
+            cell.x -= min_x;
+            cell.y -= min_y;
+        }
+        let width = cells.iter().map(|(cell, _)| cell.x as u32 + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|(cell, _)| cell.y as u32 + 1).max().unwrap_or(0);
+        return Pattern { width, height, cells, rule };
+    }
+
+    // rotates the pattern a quarter turn clockwise
+    pub fn rotated_clockwise(&self) -> Pattern {
+        let cells = self.cells.iter()
+            .map(|(cell, state)| (Point{x: self.height as i16 - 1 - cell.y, y: cell.x}, *state))
+            .collect();
+        return Pattern { width: self.height, height: self.width, cells, rule: self.rule.clone() };
+    }
+
+    // mirrors the pattern left to right
+    pub fn flipped_horizontally(&self) -> Pattern {
+        let cells = self.cells.iter()
+            .map(|(cell, state)| (Point{x: self.width as i16 - 1 - cell.x, y: cell.y}, *state))
+            .collect();
+        return Pattern { cells, rule: self.rule.clone(), ..*self };
+    }
+
+    // mirrors the pattern top to bottom
+    pub fn flipped_vertically(&self) -> Pattern {
+        let cells = self.cells.iter()
+            .map(|(cell, state)| (Point{x: cell.x, y: self.height as i16 - 1 - cell.y}, *state))
+            .collect();
+        return Pattern { cells, rule: self.rule.clone(), ..*self };
+    }
+}
+
+
+// reads a pattern in run length encoded format (https://conwaylife.com/wiki/Run_Length_Encoded)
+// supports both the regular 2 state format (b/o) and the multi-state one (./A/B/C..., then pA/pB... past X like golly writes)
+pub fn parse_rle(text: &str) -> Result<Pattern, String> {
+    let mut header_width = 0;
+    let mut header_height = 0;
+    let mut rule = None;
+    let mut cells = Vec::new();
+    let mut found_header = false;
+    let mut x: i16 = 0;
+    let mut y: i16 = 0;
+    let mut run_count = String::new(); // digits of the current run count
+    let mut state_prefix = None; // p to y, which go in front of a letter for states past 24
+
+    'lines: for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue; // comment lines
+        }
+        if !found_header {
+            found_header = true;
+            if line.starts_with('x') {
+                let (line, rule_field) = match line.find("rule") { // the rule comes last and can have commas in it (R5,C0,M1,S34..58,B34..45,NM)
+                    Some(index) => (line[..index].trim_end().trim_end_matches(','), Some(&line[index..])),
+                    None => (line, None)
+                };
+                for field in line.split(',').chain(rule_field) {
+                    let (name, value) = match field.split_once('=') {
+                        Some((name, value)) => (name.trim(), value.trim()),
+                        None => return Err(format!("line {}: couldn't read header field \"{}\"", line_num + 1, field.trim()))
+                    };
+                    match name {
+                        "x" => header_width = value.parse().map_err(|_| format!("line {}: invalid width \"{}\"", line_num + 1, value))?,
+                        "y" => header_height = value.parse().map_err(|_| format!("line {}: invalid height \"{}\"", line_num + 1, value))?,
+                        "rule" => rule = Some(value.to_string()),
+                        _ => () // there are a few other header fields in the wild that we don't care about
+                    }
+                }
+                continue;
+            }
+        }
+
+        let too_big = || format!("line {}: pattern is too big", line_num + 1); // for runs that go past what a Point can hold
+        for c in line.chars() {
+            if c.is_ascii_digit() {
+                run_count.push(c);
+                continue;
+            }
+            if let ('p'..='y', None) = (c, state_prefix) {
+                state_prefix = Some(c);
+                continue;
+            }
+            let run_length: i16 = if run_count.is_empty() { 1 } else {
+                run_count.parse().map_err(|_| format!("line {}: run length {} is too long", line_num + 1, run_count))?
+            };
+            run_count.clear();
+            let state = match c {
+                'A'..='X' => {
+                    let state = state_prefix.take().map_or(0, |prefix| (prefix as u16 - 'p' as u16 + 1) * 24) + (c as u16 - 'A' as u16 + 1);
+                    if state > u8::MAX as u16 {
+                        return Err(format!("line {}: state {} is too high (the most there can be is {})", line_num + 1, state, u8::MAX));
+                    }
+                    state as u8
+                }
+                _ if state_prefix.is_some() => return Err(format!("line {}: expected a state letter after '{}'", line_num + 1, state_prefix.unwrap())),
+                'b' | '.' => 0,
+                'o' => ALIVE,
+                '$' => {
+                    y = y.checked_add(run_length).ok_or_else(too_big)?;
+                    x = 0;
+                    continue;
+                }
+                '!' => break 'lines,
+                c if c.is_whitespace() => continue,
+                _ => return Err(format!("line {}: unexpected character '{}'", line_num + 1, c))
+            };
+            let run_end = x.checked_add(run_length).ok_or_else(too_big)?;
+            if state != 0 {
+                for run_x in x..run_end {
+                    cells.push((Point{x: run_x, y}, state));
+                }
+            }
+            x = run_end;
+        }
+    }
+
+    // trust the cells over the header if they disagree
+    let width = cells.iter().map(|(cell, _)| cell.x as u32 + 1).max().unwrap_or(0).max(header_width);
+    let height = cells.iter().map(|(cell, _)| cell.y as u32 + 1).max().unwrap_or(0).max(header_height);
+    return Ok(Pattern { width, height, cells, rule });
+}
+
+
+// reads a plaintext pattern (https://conwaylife.com/wiki/Plaintext), '.' is dead and 'O' is alive
+pub fn parse_plaintext(text: &str) -> Result<Pattern, String> {
+    let mut cells = Vec::new();
+    let mut rule = None;
+    let mut y = 0;
+    for (line_num, line) in text.lines().enumerate() {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(rule_string) = comment.strip_prefix("Rule:") {
+                rule = Some(rule_string.trim().to_string()); // not part of the format but it's what to_plaintext writes
+            }
+            continue;
+        }
+        for (x, c) in line.trim_end().chars().enumerate() {
+            match c {
+                'O' | 'o' | '*' => cells.push((Point{x: x as i16, y}, ALIVE)),
+                '.' => (),
+                _ => return Err(format!("line {}: unexpected character '{}'", line_num + 1, c))
+            }
+        }
+        y += 1;
+    }
+    let mut pattern = Pattern::from_cells(cells, rule);
+    pattern.height = pattern.height.max(y as u32); // keep empty rows at the bottom
+    return Ok(pattern);
+}
+
+
+// reads a pattern in Life 1.06 format (https://conwaylife.com/wiki/Life_1.06), which is just a list of live cell coordinates
+pub fn parse_life_106(text: &str) -> Result<Pattern, String> {
+    let mut cells = Vec::new();
+    for (line_num, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let coordinates: Vec<Result<i16, _>> = line.split_whitespace().map(|coordinate| coordinate.parse::<i16>()).collect();
+        match coordinates.as_slice() {
+            [Ok(x), Ok(y)] => cells.push((Point{x: *x, y: *y}, ALIVE)),
+            _ => return Err(format!("line {}: expected \"x y\" but got \"{}\"", line_num + 1, line))
+        }
+    }
+    return Ok(Pattern::from_cells(cells, None));
+}
+
+
+// whether text is an apgcode for a still life, oscillator or spaceship (xs4_33, xp2_7, xq4_153), the shorthand catagolue names objects with
+pub fn is_apgcode(text: &str) -> bool {
+    let (prefix, code) = match text.split_once('_') {
+        Some(split) => split,
+        None => return false
+    };
+    let period = prefix.strip_prefix("xs").or_else(|| prefix.strip_prefix("xp")).or_else(|| prefix.strip_prefix("xq"));
+    return period.is_some_and(|period| !period.is_empty() && period.chars().all(|c| c.is_ascii_digit()))
+        && !code.is_empty()
+        && code.chars().all(|c| c.is_ascii_digit() || c.is_ascii_lowercase());
+}
+
+
+// decodes an apgcode's extended wechsler format (https://conwaylife.com/wiki/Apgcode)
+// the pattern is cut into strips 5 rows tall (split up by z), and each character is a column of a strip with a bit for each row (the top row is the lowest bit)
+// w and x are 2 and 3 blank columns, and y followed by a character is 4 or more blank columns
+pub fn parse_apgcode(apgcode: &str) -> Result<Pattern, String> {
+    if !is_apgcode(apgcode) {
+        return Err(format!("\"{}\" isn't an apgcode", apgcode));
+    }
+    let code = apgcode.split_once('_').map_or("", |(_, code)| code);
+    let mut cells = Vec::new();
+    let (mut x, mut strip): (i16, i16) = (0, 0);
+    let too_big = || format!("\"{}\" is too big", apgcode); // for codes that go past what a Point can hold
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'w' => x = x.checked_add(2).ok_or_else(too_big)?,
+            'x' => x = x.checked_add(3).ok_or_else(too_big)?,
+            'y' => match chars.next().and_then(|blanks| blanks.to_digit(36)) {
+                Some(blanks) => x = x.checked_add(4 + blanks as i16).ok_or_else(too_big)?,
+                None => return Err(format!("\"{}\" has a y without a number of columns after it", apgcode))
+            },
+            'z' => {
+                x = 0;
+                strip = strip.checked_add(1).ok_or_else(too_big)?;
+            }
+            _ => {
+                let column = c.to_digit(32).ok_or(format!("\"{}\" has an invalid character '{}'", apgcode, c))?; // 0-9 then a-v
+                let top = strip.checked_mul(5).filter(|top| top.checked_add(4).is_some()).ok_or_else(too_big)?;
+                for row in 0..5 {
+                    if column & 1 << row != 0 {
+                        cells.push((Point{x, y: top + row}, ALIVE));
+                    }
+                }
+                x = x.checked_add(1).ok_or_else(too_big)?;
+            }
+        }
+    }
+    return Ok(Pattern::from_cells(cells, None));
+}
+
+
+// the part of an apgcode after the underscore, for this exact orientation of the pattern (objects::Object works out which orientation and phase is the one catagolue uses)
+// every state that isn't dead is written as alive
+pub fn to_wechsler(pattern: &Pattern) -> String {
+    let cells: CellMap<u8> = pattern.cells.iter().cloned().collect();
+    let mut strips = Vec::new();
+    for strip in 0..(pattern.height as i16 + 4) / 5 {
+        let mut columns: Vec<u32> = (0..pattern.width as i16)
+            .map(|x| (0..5).filter(|row| cells.contains_key(&Point{x, y: strip * 5 + row})).map(|row| 1 << row).sum())
+            .collect();
+        while columns.last() == Some(&0) {
+            columns.pop(); // blank columns at the end of a strip are left out
+        }
+
+        let mut text = String::new();
+        let mut blanks = 0;
+        for column in columns {
+            if column == 0 {
+                blanks += 1;
+                continue;
+            }
+            while blanks > 0 { // a strip never ends with blanks so they always get written before the next column
+                let run = blanks.min(39); // y then z is as many as one run can be
+                text.push_str(&match run {
+                    1 => "0".to_string(),
+                    2 => "w".to_string(),
+                    3 => "x".to_string(),
+                    _ => format!("y{}", core::char::from_digit(run - 4, 36).unwrap())
+                });
+                blanks -= run;
+            }
+            text.push(core::char::from_digit(column, 32).unwrap());
+        }
+        strips.push(text);
+    }
+    return strips.join("z");
+}
+
+
+pub fn parse_pattern(text: &str, format: PatternFormat) -> Result<Pattern, String> {
+    return match format {
+        PatternFormat::Rle => parse_rle(text),
+        PatternFormat::Plaintext => parse_plaintext(text),
+        PatternFormat::Life106 => parse_life_106(text),
+    };
+}
+
+
+// reads a pattern file, working out the format from the header if there is one, then from the extension, then from what's in it
+#[cfg(feature = "std")]
+pub fn load_pattern(path: &str) -> Result<Pattern, String> {
+    if is_apgcode(path) && !std::path::Path::new(path).exists() { // xq4_153 can be loaded like a file
+        return parse_apgcode(path);
+    }
+    let text = fs::read_to_string(path).map_err(|error| format!("couldn't read {}: {}", path, error))?;
+    let format = if text.starts_with("#Life 1.06") {
+        PatternFormat::Life106
+    } else {
+        PatternFormat::from_path(path).unwrap_or_else(|| PatternFormat::detect(&text))
+    };
+    return parse_pattern(&text, format).map_err(|message| format!("{}: {}", path, message));
+}
+
+
+pub fn to_rle(pattern: &Pattern) -> String {
+    let cells: CellMap<u8> = pattern.cells.iter().cloned().collect();
+    let multi_state = cells.values().any(|&state| state > ALIVE);
+    let state_tag = |state: u8| -> String {
+        match (state, multi_state) {
+            (0, false) => "b".to_string(),
+            (_, false) => "o".to_string(),
+            (0, true) => ".".to_string(),
+            (1..=24, true) => ((b'A' + state - 1) as char).to_string(),
+            (_, true) => format!("{}{}", (b'p' + (state - 1) / 24 - 1) as char, (b'A' + (state - 1) % 24) as char), // golly's pA to yO for 25 to 255
+        }
+    };
+
+    // build a list of (run length, tag) pairs
+    let mut runs: Vec<(u32, String)> = Vec::new();
+    let mut push_run = |length: u32, tag: String| {
+        match runs.last_mut() {
+            Some((last_length, last_tag)) if *last_tag == tag => *last_length += length,
+            _ => runs.push((length, tag))
+        }
+    };
+    for y in 0..pattern.height as i16 {
+        if y > 0 {
+            push_run(1, "$".to_string());
+        }
+        let row_width = (0..pattern.width as i16).rev()
+            .find(|&x| cells.contains_key(&Point{x, y}))
+            .map_or(0, |x| x + 1); // trailing dead cells are left out
+        for x in 0..row_width {
+            push_run(1, state_tag(*cells.get(&Point{x, y}).unwrap_or(&0)));
+        }
+    }
+    while runs.last().is_some_and(|(_, tag)| tag == "$") {
+        runs.pop(); // empty rows at the end don't need to be written
+    }
+
+    let mut rle = format!("x = {}, y = {}", pattern.width, pattern.height);
+    if let Some(rule) = &pattern.rule {
+        rle.push_str(&format!(", rule = {}", rule));
+    }
+    rle.push('\n');
+    let mut line = String::new();
+    for (length, tag) in runs {
+        let run = if length == 1 { tag } else { format!("{}{}", length, tag) };
+        if line.len() + run.len() > 70 { // lines in RLE files aren't supposed to be longer than 70 characters
+            rle.push_str(&line);
+            rle.push('\n');
+            line.clear();
+        }
+        line.push_str(&run);
+    }
+    rle.push_str(&line);
+    rle.push_str("!\n");
+    return rle;
+}
+
+
+// multi-state cells are all written as alive since plaintext only has 2 states
+pub fn to_plaintext(pattern: &Pattern) -> String {
+    let mut rows = vec![vec!['.'; pattern.width as usize]; pattern.height as usize];
+    for (cell, _) in &pattern.cells {
+        rows[cell.y as usize][cell.x as usize] = 'O';
+    }
+    let mut plaintext = String::new();
+    if let Some(rule) = &pattern.rule {
+        plaintext.push_str(&format!("!Rule: {}\n", rule));
+    }
+    for row in rows {
+        plaintext.extend(row);
+        plaintext.push('\n');
+    }
+    return plaintext;
+}
+
+
+pub fn to_life_106(pattern: &Pattern) -> String {
+    let mut life_106 = "#Life 1.06\n".to_string();
+    for (cell, _) in &pattern.cells {
+        life_106.push_str(&format!("{} {}\n", cell.x, cell.y));
+    }
+    return life_106;
+}
+
+
+pub fn write_pattern(pattern: &Pattern, format: PatternFormat) -> String {
+    return match format {
+        PatternFormat::Rle => to_rle(pattern),
+        PatternFormat::Plaintext => to_plaintext(pattern),
+        PatternFormat::Life106 => to_life_106(pattern),
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_runs_past_the_biggest_board_are_an_error() {
+        assert_eq!(parse_rle("x = 3, y = 1\n20000b20000bo!").err(), Some("line 2: pattern is too big".to_string()));
+        assert_eq!(parse_rle("x = 3, y = 1\n20000$20000$o!").err(), Some("line 2: pattern is too big".to_string()));
+    }
+
+    #[test]
+    fn every_state_survives_a_round_trip_through_rle() {
+        let mut cells: Vec<(Point, u8)> = (1..=u8::MAX).map(|state| (Point{x: state as i16 % 16, y: state as i16 / 16}, state)).collect();
+        cells.sort();
+        let pattern = Pattern::from_cells(cells.clone(), None);
+        let rle = to_rle(&pattern);
+        assert!(rle.contains("pA") && rle.contains("yO"));
+        let mut parsed = parse_rle(&rle).unwrap().cells;
+        parsed.sort();
+        assert!(parsed == cells);
+    }
+
+    #[test]
+    fn apgcodes_past_the_biggest_board_are_an_error() {
+        let wide = format!("xs1_{}1", "yz".repeat(1000));
+        let tall = format!("xs1_{}1", "z".repeat(7000));
+        assert_eq!(parse_apgcode(&wide).err(), Some(format!("\"{}\" is too big", wide)));
+        assert_eq!(parse_apgcode(&tall).err(), Some(format!("\"{}\" is too big", tall)));
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use rand::Rng;
+use game_of_life::{Board, Point, ALIVE};
+
+
+
+const TARGET_COMPARTMENT_WIDTH: u32 = 24; // compartments end up around this size (walls included) unless the board is too small
+const TARGET_COMPARTMENT_HEIGHT: u32 = 12;
+const MAX_COMPARTMENTS: u32 = 16; // any more and the report doesn't fit under the board
+const SOUP_SIZE: i16 = 10; // soups are a square this big (or as big as fits) in the middle of each compartment
+const MAX_DETECTED_PERIOD: usize = 30; // compartments that repeat after more generations than this are just counted as active
+
+
+
+// one section of the board, walled off from the others
+struct Compartment {
+    left: i16, // inclusive
+    top: i16,
+    right: i16, // exclusive
+    bottom: i16,
+    history: VecDeque<u64>, // hashes of the compartment's cells over the last few generations (newest first), for spotting oscillators
+    population: usize,
+}
+
+
+// splits the board up into compartments with walls between them so that a bunch of soups can be run side by side without interfering with each other
+pub struct PetriDish {
+    compartments: Vec<Compartment>,
+    wall_thickness: u32, // as far as the rule's neighbourhood reaches, so no cell can see past a wall into the next compartment
+}
+
+
+// how thick the walls have to be for the board's rule
+fn wall_thickness(board: &Board) -> u32 {
+    return board.rule.neighbourhood.reach().max(1) as u32;
+}
+
+
+// where the walls go when splitting length cells into num_parts parts, each part is (start, end)
+fn split(length: u32, num_parts: u32, wall_thickness: u32) -> Vec<(i16, i16)> {
+    let part_length = (length + wall_thickness) / num_parts; // + wall_thickness since the last part doesn't need a wall
+    return (0..num_parts)
+        .map(|part| {
+            let start = part * part_length;
+            let end = if part == num_parts - 1 { length } else { start + part_length - wall_thickness };
+            (start as i16, end as i16)
+        })
+        .collect();
+}
+
+
+// how many parts length cells can be split into, going for parts around target long but leaving room in each one for something besides wall
+fn num_parts(length: u32, target: u32, wall_thickness: u32) -> u32 {
+    let most = (length + wall_thickness) / (wall_thickness + 1);
+    return (length / target).clamp(1, most.max(1));
+}
+
+
+impl PetriDish {
+    // builds the walls and puts a different random soup in every compartment (anything already on the board gets cleared)
+    pub fn new<R: Rng + ?Sized>(board: &mut Board, rng: &mut R) -> PetriDish {
+        let wall_thickness = wall_thickness(board);
+        let mut columns = num_parts(board.width, TARGET_COMPARTMENT_WIDTH, wall_thickness);
+        let mut rows = num_parts(board.height, TARGET_COMPARTMENT_HEIGHT, wall_thickness);
+        while columns * rows > MAX_COMPARTMENTS {
+            if columns >= rows {
+                columns -= 1;
+            } else {
+                rows -= 1;
+            }
+        }
+
+        let mut compartments = Vec::new();
+        for &(top, bottom) in &split(board.height, rows, wall_thickness) {
+            for &(left, right) in &split(board.width, columns, wall_thickness) {
+                compartments.push(Compartment { left, top, right, bottom, history: VecDeque::new(), population: 0 });
+            }
+        }
+
+        board.clear();
+        board.walls.clear();
+        for y in 0..board.height as i16 {
+            for x in 0..board.width as i16 {
+                if !compartments.iter().any(|compartment| compartment.contains(x, y)) {
+                    board.walls.insert(Point{x, y});
+                }
+            }
+        }
+
+        let mut dish = PetriDish { compartments, wall_thickness };
+        dish.reseed(board, rng);
+        return dish;
+    }
+
+    // puts a new random soup in every compartment
+    pub fn reseed<R: Rng + ?Sized>(&mut self, board: &mut Board, rng: &mut R) {
+        board.clear();
+        for compartment in self.compartments.iter_mut() {
+            let soup_width = SOUP_SIZE.min(compartment.right - compartment.left);
+            let soup_height = SOUP_SIZE.min(compartment.bottom - compartment.top);
+            let soup_left = compartment.left + (compartment.right - compartment.left - soup_width) / 2;
+            let soup_top = compartment.top + (compartment.bottom - compartment.top - soup_height) / 2;
+            for y in soup_top..(soup_top + soup_height) {
+                for x in soup_left..(soup_left + soup_width) {
+                    if rng.gen_bool(0.5) {
+                        board.cells.insert(Point{x, y}, ALIVE);
+                    }
+                }
+            }
+            compartment.history.clear();
+        }
+        self.record(board);
+    }
+
+    pub fn remove_walls(&self, board: &mut Board) {
+        board.walls.clear();
+    }
+
+    // whether the walls are still thick enough, a rule that reaches further than the one they were built for needs new ones
+    pub fn fits(&self, board: &Board) -> bool {
+        return wall_thickness(board) <= self.wall_thickness;
+    }
+
+    // keeps track of what each compartment looked like this generation, should be called after every generation
+    pub fn record(&mut self, board: &Board) {
+        let mut compartment_cells: Vec<Vec<(&Point, u8)>> = vec![Vec::new(); self.compartments.len()];
+        for (cell, &state) in &board.cells {
+            if let Some(index) = self.compartments.iter().position(|compartment| compartment.contains(cell.x, cell.y)) {
+                compartment_cells[index].push((cell, state));
+            }
+        }
+        for (compartment, mut cells) in self.compartments.iter_mut().zip(compartment_cells) {
+            cells.sort();
+            let mut hasher = DefaultHasher::new();
+            cells.hash(&mut hasher);
+            compartment.history.push_front(hasher.finish());
+            compartment.history.truncate(MAX_DETECTED_PERIOD + 1);
+            compartment.population = cells.iter().filter(|(_, state)| *state == ALIVE).count();
+        }
+    }
+
+    // one line per compartment saying what's going on in it
+    pub fn report(&self) -> Vec<String> {
+        return self.compartments.iter().enumerate()
+            .map(|(index, compartment)| format!("Dish {:>2}: {:<12} {}", index + 1, compartment.outcome(), compartment.population))
+            .collect();
+    }
+}
+
+
+impl Compartment {
+    fn contains(&self, x: i16, y: i16) -> bool {
+        return x >= self.left && x < self.right && y >= self.top && y < self.bottom;
+    }
+
+    fn outcome(&self) -> String {
+        if self.population == 0 {
+            return "died".to_string();
+        }
+        let current = self.history[0];
+        return match self.history.iter().skip(1).position(|&hash| hash == current) {
+            Some(0) => "still life".to_string(),
+            Some(period) => format!("period {}", period + 1),
+            None => "active".to_string(),
+        };
+    }
+}
@@ -0,0 +1,85 @@
+// just enough of a PNG encoder to turn a frame into an image file without any dependencies
+// the image data is stored without compression (deflate's "stored" blocks) so files are about as big as the raw pixels
+
+
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK: usize = 65535; // the most bytes that fit in one uncompressed deflate block
+
+
+fn crc32(chunk: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in chunk {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    return !crc;
+}
+
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    return (b << 16) | a;
+}
+
+
+// a chunk is its length, its type, its data and a checksum of the type and data
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+
+// how many bytes each pixel given to encode has
+#[derive(Clone, Copy)]
+pub enum ColourType {
+    Rgb,
+    Rgba,
+}
+
+
+// pixels go row by row from the top left, 3 or 4 bytes each depending on colour_type
+pub fn encode(pixels: &[u8], width: u32, height: u32, colour_type: ColourType) -> Vec<u8> {
+    let (bytes_per_pixel, colour_type_code) = match colour_type {
+        ColourType::Rgb => (3, 2),
+        ColourType::Rgba => (4, 6),
+    };
+    let mut header = Vec::new();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, colour_type_code, 0, 0, 0]); // 8 bits per channel, no interlacing
+
+    // every row starts with the filter it uses, 0 is no filter
+    let row_length = width as usize * bytes_per_pixel;
+    let mut filtered = Vec::with_capacity((row_length + 1) * height as usize);
+    for row in pixels.chunks(row_length).take(height as usize) {
+        filtered.push(0);
+        filtered.extend_from_slice(row);
+    }
+
+    let mut zlib = vec![0x78, 0x01]; // zlib header: deflate with a 32K window and no preset dictionary
+    let num_blocks = filtered.len().div_ceil(MAX_STORED_BLOCK);
+    for (index, block) in filtered.chunks(MAX_STORED_BLOCK).enumerate() {
+        zlib.push((index == num_blocks - 1) as u8); // the last block is marked so the decoder knows where the data ends
+        zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&filtered).to_be_bytes());
+
+    let mut png = SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    return png;
+}
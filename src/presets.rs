@@ -0,0 +1,54 @@
+use game_of_life::Rule;
+
+
+
+// a well known rule with its name
+pub struct RulePreset {
+    pub name: &'static str,
+    pub rule: &'static str,
+}
+
+
+// what [ and ] cycle through, and what tab completes after "rule" on the : prompt (any other B/S rule can still be typed out)
+pub const RULE_PRESETS: [RulePreset; 13] = [
+    RulePreset { name: "Life", rule: "B3/S23" },
+    RulePreset { name: "HighLife", rule: "B36/S23" }, // has a small replicator
+    RulePreset { name: "Seeds", rule: "B2/S" }, // everything dies every generation, but explodes anyway
+    RulePreset { name: "Day & Night", rule: "B3678/S34678" }, // dead and alive cells behave the same way
+    RulePreset { name: "Life without Death", rule: "B3/S012345678" }, // cells never die so patterns leave ladders behind
+    RulePreset { name: "Diamoeba", rule: "B35678/S5678" }, // big diamond shaped blobs
+    RulePreset { name: "Anneal", rule: "B4678/S35678" }, // soups clump into smooth edged blobs
+    RulePreset { name: "Maze", rule: "B3/S12345" }, // grows into mazes
+    RulePreset { name: "Brian's Brain", rule: "B2/S/3" },
+    RulePreset { name: "Replicator", rule: "B1357/S1357" }, // every pattern copies itself
+    RulePreset { name: "Morley", rule: "B368/S245" },
+    RulePreset { name: "Hexagonal Life", rule: "B2/S34H" }, // on a hex grid, has a glider
+    RulePreset { name: "Bugs", rule: "R5,C0,M1,S34..58,B34..45,NM" }, // Larger than Life, blobs that crawl around
+];
+
+
+// which preset a rule is, if it's one of them
+pub fn preset_index(rule: &Rule) -> Option<usize> {
+    return RULE_PRESETS.iter().position(|preset| Rule::parse(preset.rule).as_ref() == Ok(rule));
+}
+
+
+// the preset after (or before, going backwards) the one the rule is, starting from the first (or last) one if it isn't a preset
+pub fn cycle(rule: &Rule, backwards: bool) -> usize {
+    let length = RULE_PRESETS.len();
+    return match (preset_index(rule), backwards) {
+        (Some(index), false) => (index + 1) % length,
+        (Some(index), true) => (index + length - 1) % length,
+        (None, false) => 0,
+        (None, true) => length - 1,
+    };
+}
+
+
+// the preset's name if the rule is one, otherwise just the rule
+pub fn rule_name(rule: &Rule) -> String {
+    return match preset_index(rule) {
+        Some(index) => RULE_PRESETS[index].name.to_string(),
+        None => rule.to_string()
+    };
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::Path;
+use game_of_life::board::{CellMap, CellSet};
+use game_of_life::{pattern, Board, IncrementalBoard, Point, Rule, ALIVE};
+
+
+
+// a puzzle gives a starting board and a target, and the player has to edit the start so that it turns into the target, one line per setting:
+//     # comment
+//     name Pond from a blinker
+//     rule B3/S23
+//     start xp2_7 at 3 1
+//     target xs8_6996 at 1 1
+//     generations 5
+//     budget 2
+// pattern paths are relative to the puzzle file (or can be apgcodes), positions are from the top left of the puzzle (which goes in the middle of the board)
+// start can be left out to start with an empty board, name and rule are optional
+pub struct Puzzle {
+    pub name: String,
+    pub rule: Option<Rule>,
+    start: Vec<Point>, // relative to the top left of the puzzle
+    target: Vec<Point>,
+    generations: u32, // how long after the attempt starts the board has to match the target
+    budget: usize, // how many cells can be changed from the start
+    pub width: u32, // big enough for both the start and the target
+    pub height: u32,
+    offset: Point, // where the top left of the puzzle is on the board
+    attempts: u32,
+    phase: Phase,
+}
+
+
+enum Phase {
+    Editing,
+    Running { start: CellMap<u8>, generation: u32 }, // start is the board when the attempt started
+    Failed { start: CellMap<u8> },
+    Solved { edits: usize },
+}
+
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, what: &str, line_num: usize) -> Result<T, String> {
+    return word
+        .and_then(|word| word.parse().ok())
+        .ok_or(format!("line {}: expected {}", line_num + 1, what));
+}
+
+
+// a pattern file (or apgcode) followed by an optional "at x y", as the pattern's cells moved to that position
+fn parse_placed_pattern<'a>(mut words: impl Iterator<Item = &'a str>, directory: &Path, line_num: usize) -> Result<Vec<Point>, String> {
+    let pattern_path = words.next().ok_or(format!("line {}: expected a pattern file", line_num + 1))?;
+    let path = match pattern::is_apgcode(pattern_path) && !directory.join(pattern_path).exists() {
+        true => pattern_path.to_string(),
+        false => directory.join(pattern_path).to_string_lossy().to_string()
+    };
+    let mut position = Point{x: 0, y: 0};
+    match words.next() {
+        None => (),
+        Some("at") => position = Point{
+            x: parse_number(words.next(), "an x coordinate", line_num)?,
+            y: parse_number(words.next(), "a y coordinate", line_num)?
+        },
+        Some(word) => return Err(format!("line {}: unknown option \"{}\"", line_num + 1, word))
+    }
+    let pattern = pattern::load_pattern(&path)?;
+    return Ok(pattern.cells.iter()
+        .filter(|(_, state)| *state == ALIVE)
+        .map(|(cell, _)| Point{x: position.x + cell.x, y: position.y + cell.y})
+        .collect());
+}
+
+
+impl Puzzle {
+    pub fn load(path: &str) -> Result<Puzzle, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("couldn't read {}: {}", path, error))?;
+        let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+        let (mut name, mut rule, mut start, mut target, mut generations, mut budget) = (None, None, Vec::new(), None, None, None);
+        for (line_num, line) in text.lines().enumerate() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => (),
+                Some(word) if word.starts_with('#') => (),
+                Some("name") => name = Some(words.collect::<Vec<&str>>().join(" ")),
+                Some("rule") => {
+                    let rule_string = words.next().ok_or(format!("line {}: expected a rule", line_num + 1))?;
+                    rule = Some(Rule::parse(rule_string).map_err(|message| format!("line {}: {}", line_num + 1, message))?);
+                }
+                Some("start") => start = parse_placed_pattern(words, directory, line_num)?,
+                Some("target") => target = Some(parse_placed_pattern(words, directory, line_num)?),
+                Some("generations") => generations = Some(parse_number(words.next(), "a number of generations", line_num)?).filter(|&generations: &u32| generations > 0),
+                Some("budget") => budget = Some(parse_number(words.next(), "a number of cells", line_num)?),
+                Some(word) => return Err(format!("line {}: unknown setting \"{}\"", line_num + 1, word))
+            }
+        }
+        let target: Vec<Point> = target.ok_or(format!("{}: the puzzle doesn't have a target", path))?;
+        if start.iter().chain(&target).any(|cell| cell.x < 0 || cell.y < 0) {
+            return Err(format!("{}: patterns can't be placed at negative positions", path));
+        }
+        return Ok(Puzzle {
+            name: name.unwrap_or_else(|| path.to_string()),
+            rule,
+            width: start.iter().chain(&target).map(|cell| cell.x as u32 + 1).max().unwrap_or(0),
+            height: start.iter().chain(&target).map(|cell| cell.y as u32 + 1).max().unwrap_or(0),
+            start,
+            target,
+            generations: generations.ok_or(format!("{}: the puzzle doesn't say how many generations the target is after the start", path))?,
+            budget: budget.ok_or(format!("{}: the puzzle doesn't have a budget", path))?,
+            offset: Point{x: 0, y: 0},
+            attempts: 0,
+            phase: Phase::Editing,
+        });
+    }
+
+    // puts the start in the middle of the board (anything already there gets cleared)
+    pub fn set_up(&mut self, board: &mut Board) {
+        self.offset = Point{
+            x: (board.width as i16 - self.width as i16) / 2,
+            y: (board.height as i16 - self.height as i16) / 2
+        };
+        board.clear();
+        for cell in self.on_board(&self.start) {
+            board.cells.insert(cell, ALIVE);
+        }
+    }
+
+    fn on_board(&self, cells: &[Point]) -> CellSet {
+        return cells.iter().map(|cell| Point{x: self.offset.x + cell.x, y: self.offset.y + cell.y}).collect();
+    }
+
+    // how many of cells are different from the start
+    fn edits(&self, cells: &CellMap<u8>) -> usize {
+        let start = self.on_board(&self.start);
+        let alive: CellSet = cells.iter().filter(|(_, &state)| state == ALIVE).map(|(cell, _)| cell.clone()).collect();
+        return alive.symmetric_difference(&start).count();
+    }
+
+    // what space does in a puzzle: starts an attempt while editing, and goes back to how the attempt started if it's running or failed
+    // returns whether the game should be paused and what to say, or None once it's been solved (then space goes back to pausing like normal)
+    pub fn space(&mut self, board: &mut Board) -> Option<(bool, String)> {
+        let phase = std::mem::replace(&mut self.phase, Phase::Editing);
+        return match phase {
+            Phase::Editing => {
+                let edits = self.edits(&board.cells);
+                if edits > self.budget {
+                    return Some((true, format!("That's {} edits but the budget is {}, undo some first", edits, self.budget)));
+                }
+                self.attempts += 1;
+                self.phase = Phase::Running { start: board.cells.clone(), generation: 0 };
+                Some((false, format!("Attempt {}: running {} generations", self.attempts, self.generations)))
+            }
+            Phase::Running { start, .. } | Phase::Failed { start } => {
+                board.cells = start;
+                Some((true, "Back to how the attempt started, edit it and press Space to try again".to_string()))
+            }
+            Phase::Solved { edits } => {
+                self.phase = Phase::Solved { edits };
+                None
+            }
+        };
+    }
+
+    // called after every generation, once an attempt has run for long enough it gets checked and this says how it went (along with the cells the board should show)
+    // the check runs the attempt again from the start on a board of its own, so cells drawn in while it was running don't count
+    pub fn generation(&mut self, board: &Board) -> Option<(CellMap<u8>, String)> {
+        let start = match &mut self.phase {
+            Phase::Running { start, generation } => {
+                *generation += 1;
+                if *generation < self.generations {
+                    return None;
+                }
+                start.clone()
+            }
+            _ => return None
+        };
+        let mut attempt = Board::new(board.width, board.height, board.automaton, board.rule.clone());
+        attempt.walls = board.walls.clone();
+        attempt.sources = board.sources.clone();
+        attempt.cells = start.clone();
+        let mut attempt = IncrementalBoard::new(attempt);
+        for _ in 0..self.generations {
+            attempt.update_cells();
+        }
+        let cells = attempt.into_board().cells;
+        let alive: CellSet = cells.iter().filter(|(_, &state)| state == ALIVE).map(|(cell, _)| cell.clone()).collect();
+        let cells_off = alive.symmetric_difference(&self.on_board(&self.target)).count();
+        if cells_off == 0 {
+            let edits = self.edits(&start);
+            self.phase = Phase::Solved { edits };
+            let tries = if self.attempts == 1 { "try" } else { "tries" };
+            return Some((cells, format!("Solved \"{}\" with {} edits in {} {}! Space plays on from here", self.name, edits, self.attempts, tries)));
+        }
+        self.phase = Phase::Failed { start };
+        return Some((cells, format!("Attempt {}: {} cells off the target, press Space to go back and try again", self.attempts, cells_off)));
+    }
+
+    // for showing under the board, with a picture of the target (alive is how alive cells are drawn and empty is how empty ones are)
+    pub fn report(&self, board: &Board, alive: char, empty: char) -> Vec<String> {
+        let (edits, status) = match &self.phase { // once an attempt has started its edits are the ones it started with
+            Phase::Editing => (self.edits(&board.cells), "press Space to try".to_string()),
+            Phase::Running { start, generation } => (self.edits(start), format!("generation {}/{}", generation, self.generations)),
+            Phase::Failed { start } => (self.edits(start), "not quite".to_string()),
+            Phase::Solved { edits } => (*edits, "solved!".to_string()),
+        };
+        let mut lines = vec![
+            format!("Puzzle: {}", self.name),
+            format!("Edits: {}/{}", edits, self.budget),
+            format!("Attempts: {} ({})", self.attempts, status),
+            format!("Target after {} generations:", self.generations),
+        ];
+        let target: CellSet = self.target.iter().cloned().collect();
+        for y in 0..self.height as i16 {
+            lines.push((0..self.width as i16).map(|x| if target.contains(&Point{x, y}) { alive } else { empty }).collect());
+        }
+        return lines;
+    }
+}
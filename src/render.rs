@@ -0,0 +1,155 @@
+use std::{fs, process, time};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use game_of_life::{Ant, Automaton, Board, ALIVE, ELECTRON_HEAD, ELECTRON_TAIL, PLAYER_TWO};
+use game_of_life::board::CellMap;
+use crate::{check_board_size, headless_board, headless_engine, png, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: game_of_life render <pattern file> --out <video file> [--generations <n>] [--fps <n>] [--scale <pixels per cell>] [--engine <engine>]";
+
+
+// where rendered frames end up
+enum FrameOutput {
+    Ffmpeg(process::Child), // frames get piped to ffmpeg's stdin
+    Directory(String), // one .ppm file per frame
+}
+
+
+fn cell_colour(automaton: Automaton, state: u8, num_states: u8) -> [u8; 3] {
+    if automaton == Automaton::Wireworld {
+        return match state {
+            ELECTRON_HEAD => [40, 120, 255],
+            ELECTRON_TAIL => [255, 70, 30],
+            _ => [230, 190, 40], // conductor
+        };
+    }
+    if automaton == Automaton::Immigration && state == PLAYER_TWO {
+        return [255, 70, 30];
+    }
+    if state == ALIVE {
+        return [255, 255, 255];
+    }
+    let brightness = (200 - 150 * (state - ALIVE - 1) as u32 / num_states.max(3) as u32) as u8; // dying cells get darker as they get closer to dead
+    return [brightness; 3];
+}
+
+
+// renders the board as a scale x scale square of pixels per cell (rgb, 3 bytes per pixel)
+pub fn render_frame(board: &Board, scale: u32) -> Vec<u8> {
+    return render_cells(board, &board.cells, &board.ants, scale);
+}
+
+
+// render_frame for cells and ants that have moved on from the ones on board (like an engine's), board still says how big the frame is and what colour everything is
+fn render_cells(board: &Board, cells: &CellMap<u8>, ants: &[Ant], scale: u32) -> Vec<u8> {
+    let frame_width = (board.width * scale) as usize;
+    let mut frame = vec![0; frame_width * (board.height * scale) as usize * 3];
+    let mut fill_cell = |x: i16, y: i16, colour: [u8; 3]| {
+        for pixel_y in (y as usize * scale as usize)..((y as usize + 1) * scale as usize) {
+            for pixel_x in (x as usize * scale as usize)..((x as usize + 1) * scale as usize) {
+                let index = (pixel_y * frame_width + pixel_x) * 3;
+                frame[index..index + 3].copy_from_slice(&colour);
+            }
+        }
+    };
+    for (cell, &state) in cells {
+        fill_cell(cell.x, cell.y, cell_colour(board.automaton, state, board.table.as_ref().map_or(board.rule.num_states, |table| table.num_states.min(255) as u8)));
+    }
+    for ant in ants {
+        fill_cell(ant.position.x, ant.position.y, [255, 40, 40]);
+    }
+    return frame;
+}
+
+
+// writes the board to a png with a scale x scale square of pixels per cell, returns what to tell the user
+pub fn save_screenshot(board: &Board, scale: u32, path: &str) -> Result<String, String> {
+    let scale = scale.max(1);
+    let image = png::encode(&render_frame(board, scale), board.width * scale, board.height * scale, png::ColourType::Rgb);
+    fs::write(path, image).map_err(|error| format!("couldn't write {}: {}", path, error))?;
+    return Ok(format!("Saved screenshot to {}", path));
+}
+
+
+// a new file name in the current directory for each screenshot
+pub fn screenshot_path() -> String {
+    let seconds = time::SystemTime::now().duration_since(time::UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0);
+    let mut path = format!("game_of_life_{}.png", seconds);
+    let mut copy = 1;
+    while fs::metadata(&path).is_ok() { // more than one screenshot in the same second
+        copy += 1;
+        path = format!("game_of_life_{}_{}.png", seconds, copy);
+    }
+    return path;
+}
+
+
+// the "render" subcommand: simulates a pattern without the UI and turns every generation into a video frame
+// the frames get piped to ffmpeg if it's installed, otherwise they get written out as image files
+pub fn run_render(args: Args) {
+    let board = headless_board(&args, USAGE);
+    let out_path = args.out_path.clone().unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    });
+    let scale = args.scale.max(1);
+    check_board_size(&args, board.width, board.height, SIMULATION_BYTES_PER_CELL + 3 * scale as u64 * scale as u64); // frames are 3 bytes per pixel
+    let (frame_width, frame_height) = (board.width * scale, board.height * scale);
+    let mut engine = headless_engine(&args, board.clone()); // board stays the way it started, for the size and colours of the frames
+
+    let ffmpeg = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24"])
+        .args(["-s", &format!("{}x{}", frame_width, frame_height)])
+        .args(["-r", &args.fps.to_string()])
+        .args(["-i", "-"])
+        .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p"]) // most video codecs need even dimensions
+        .arg(&out_path)
+        .stdin(Stdio::piped())
+        .spawn();
+    let mut output = match ffmpeg {
+        Ok(child) => FrameOutput::Ffmpeg(child),
+        Err(_) => {
+            let directory = format!("{}_frames", out_path.rsplit_once('.').map_or(out_path.as_str(), |(stem, _)| stem));
+            eprintln!("couldn't run ffmpeg, writing frames to {} instead", directory);
+            if let Err(error) = fs::create_dir_all(&directory) {
+                eprintln!("couldn't create {}: {}", directory, error);
+                process::exit(1);
+            }
+            FrameOutput::Directory(directory)
+        }
+    };
+
+    for generation in 0..=args.generations {
+        if generation > 0 {
+            engine.step();
+        }
+        let frame = render_cells(&board, &engine.cells(), engine.ants(), scale);
+        let written = match &mut output {
+            FrameOutput::Ffmpeg(child) => child.stdin.as_mut().unwrap().write_all(&frame),
+            FrameOutput::Directory(directory) => {
+                let mut ppm = format!("P6\n{} {}\n255\n", frame_width, frame_height).into_bytes();
+                ppm.extend(frame);
+                fs::write(format!("{}/frame_{:06}.ppm", directory, generation), ppm)
+            }
+        };
+        if let Err(error) = written {
+            eprintln!("\ncouldn't write frame {}: {}", generation, error);
+            process::exit(1);
+        }
+        eprint!("\rrendered {}/{} frames", generation + 1, args.generations + 1);
+    }
+    eprintln!();
+
+    if let FrameOutput::Ffmpeg(mut child) = output {
+        drop(child.stdin.take()); // closing stdin tells ffmpeg there are no more frames
+        match child.wait() {
+            Ok(status) if status.success() => (),
+            _ => {
+                eprintln!("ffmpeg failed to write {}", out_path);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::{self, Write};
+use game_of_life::{Board, Point};
+use crate::graphics::Graphics;
+use crate::theme::{ColourDepth, Rgb, THEMES};
+use crate::{cell_char, cell_colour, PIXEL_DEFAULT_COLOUR};
+
+
+
+// where a front end's drawing goes, so it doesn't have to know whether that's termion, terminal graphics or anything else
+// positions count from 1 like the terminal's do, and nothing has to show up until present (a renderer can save it all up until then)
+pub trait Renderer {
+    fn set_theme(&mut self, theme: usize); // index into THEMES, for the cells and the status line
+    fn clear(&mut self);
+    // draws each of cells the way it is on the board now (dead cells get rubbed out), with the board's top left corner at left, top
+    fn draw_cells(&mut self, board: &Board, cells: &[Point], left: u16, top: u16);
+    fn draw_text(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>);
+    fn draw_highlighted(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>); // the colour goes behind the text instead, for things that have to stand out from what's under them
+    fn draw_status(&mut self, top: u16, text: &str); // a whole line, cut off so it doesn't wrap
+    // an rgba image with its top left corner at left, top (see-through pixels show what's under them), however big the renderer can draw it
+    fn draw_image(&mut self, left: u16, top: u16, rgba: &[u8], width: u32, height: u32);
+    fn place_cursor(&mut self, position: Option<(u16, u16)>); // None hides it
+    fn present(&mut self) -> io::Result<()>;
+}
+
+
+// every cell on the board, for draw_cells when the whole board needs drawing
+pub fn every_cell(board: &Board) -> Vec<Point> {
+    return (0..board.height as i16).flat_map(|y| (0..board.width as i16).map(move |x| Point{x, y})).collect();
+}
+
+
+// the renderer for a terminal, which draws images with graphics if the terminal can and with characters if it can't
+pub fn for_terminal<'a, W: Write + 'a>(output: W, colour_depth: ColourDepth, theme: usize, columns: u16, graphics: Option<Graphics>) -> Box<dyn Renderer + 'a> {
+    let text = TermionRenderer::new(output, colour_depth, theme, columns);
+    return match graphics {
+        Some(graphics) => Box::new(GraphicsRenderer { text, graphics, canvases: Vec::new() }),
+        None => Box::new(text)
+    };
+}
+
+
+// draws to a terminal with termion's escape codes, saving a frame up in a string until it's presented
+pub struct TermionRenderer<W: Write> {
+    output: W,
+    colour_depth: ColourDepth,
+    theme: usize,
+    columns: u16, // how wide the terminal is
+    frame: String,
+    current_colour: Option<Rgb>, // what the last thing in frame was drawn in
+}
+
+
+impl<W: Write> TermionRenderer<W> {
+    pub fn new(output: W, colour_depth: ColourDepth, theme: usize, columns: u16) -> TermionRenderer<W> {
+        return TermionRenderer { output, colour_depth, theme, columns, frame: String::new(), current_colour: None };
+    }
+
+    fn set_colour(&mut self, colour: Option<Rgb>) {
+        if colour != self.current_colour {
+            self.frame.push_str(&self.colour_depth.fg(colour));
+            self.current_colour = colour;
+        }
+    }
+
+    // for escape codes that draw at the cursor, like graphics' images
+    fn draw_escape(&mut self, left: u16, top: u16, escape: &str) {
+        self.frame.push_str(&termion::cursor::Goto(left, top).to_string());
+        self.frame.push_str(escape);
+    }
+}
+
+
+impl<W: Write> Renderer for TermionRenderer<W> {
+    fn set_theme(&mut self, theme: usize) {
+        self.theme = theme;
+    }
+
+    fn clear(&mut self) {
+        self.frame.push_str(termion::clear::All.as_ref());
+    }
+
+    fn draw_cells(&mut self, board: &Board, cells: &[Point], left: u16, top: u16) {
+        let theme = &THEMES[self.theme];
+        let mut last: Option<&Point> = None;
+        for cell in cells {
+            let (character, colour) = match board.cells.get(cell) {
+                Some(&state) => (cell_char(board.automaton, state, true), cell_colour(board.automaton, state, theme)),
+                None => (' ', self.current_colour)
+            };
+            let follows_last = last.is_some_and(|last| last.y == cell.y && last.x + 1 == cell.x);
+            if !follows_last { // the cursor's already there after drawing the cell to the left of it
+                self.frame.push_str(&termion::cursor::Goto(left + cell.x as u16, top + cell.y as u16).to_string());
+            }
+            self.set_colour(colour);
+            self.frame.push(character);
+            last = Some(cell);
+        }
+    }
+
+    fn draw_text(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>) {
+        self.frame.push_str(&termion::cursor::Goto(left, top).to_string());
+        self.set_colour(colour);
+        self.frame.push_str(text);
+    }
+
+    fn draw_highlighted(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>) {
+        self.frame.push_str(&termion::cursor::Goto(left, top).to_string());
+        self.set_colour(colour);
+        self.frame.push_str(&format!("{}{}{}", termion::style::Invert, text, termion::style::NoInvert));
+    }
+
+    fn draw_status(&mut self, top: u16, text: &str) {
+        let text: String = text.chars().take(self.columns.saturating_sub(1) as usize).collect(); // anything that wrapped past the bottom line would scroll the screen
+        self.frame.push_str(&format!("{}{}", termion::cursor::Goto(1, top), termion::clear::CurrentLine));
+        self.set_colour(THEMES[self.theme].status);
+        self.frame.push_str(&text);
+    }
+
+    // characters can't do pixels, so each one is two of them: the top one as a half block and the bottom one behind it
+    fn draw_image(&mut self, left: u16, top: u16, rgba: &[u8], width: u32, height: u32) {
+        let pixel = |x: u32, y: u32| -> Option<Rgb> {
+            let index = (y as usize * width as usize + x as usize) * 4;
+            return match rgba.get(index..index + 4) {
+                Some(&[r, g, b, alpha]) if y < height && alpha > 0 => Some(Rgb(r, g, b)),
+                _ => None
+            };
+        };
+        for row in 0..height.div_ceil(2) {
+            self.frame.push_str(&termion::cursor::Goto(left, top + row as u16).to_string());
+            for x in 0..width {
+                let (character, colour, background) = match (pixel(x, 2 * row), pixel(x, 2 * row + 1)) {
+                    (Some(upper), lower) => ('▀', Some(upper), lower),
+                    (None, Some(lower)) => ('▄', Some(lower), None),
+                    (None, None) => (' ', self.current_colour, None)
+                };
+                self.set_colour(colour);
+                self.frame.push_str(&self.colour_depth.bg(background));
+                self.frame.push(character);
+            }
+            self.frame.push_str(&self.colour_depth.bg(None));
+        }
+    }
+
+    fn place_cursor(&mut self, position: Option<(u16, u16)>) {
+        match position {
+            Some((left, top)) => self.frame.push_str(&format!("{}{}", termion::cursor::Goto(left, top), termion::cursor::Show)),
+            None => self.frame.push_str(termion::cursor::Hide.as_ref())
+        }
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        self.set_colour(None); // so whatever gets drawn next without a renderer isn't in the last colour
+        let written = write!(self.output, "{}", self.frame).and_then(|_| self.output.flush());
+        self.frame.clear();
+        return written;
+    }
+}
+
+
+// an image on the screen that draw_cells keeps up to date, so only the cells that changed have to be drawn into it
+struct Canvas {
+    left: u16,
+    top: u16,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+
+// draws boards as images with the terminal's graphics protocol (kitty, iTerm2 or sixels) and everything else with a TermionRenderer
+pub struct GraphicsRenderer<W: Write> {
+    text: TermionRenderer<W>,
+    graphics: Graphics,
+    canvases: Vec<Canvas>, // one for each place a board or image has been drawn since the screen was last cleared
+}
+
+
+impl<W: Write> GraphicsRenderer<W> {
+    // the index of the canvas at left, top, which gets made (or emptied if it was a different size) if it needs to be
+    fn canvas(&mut self, left: u16, top: u16, width: u32, height: u32) -> usize {
+        let empty = vec![0; width as usize * height as usize * 4];
+        let index = match self.canvases.iter().position(|canvas| canvas.left == left && canvas.top == top) {
+            Some(index) => index,
+            None => {
+                self.canvases.push(Canvas { left, top, width, height, rgba: empty });
+                return self.canvases.len() - 1;
+            }
+        };
+        let canvas = &mut self.canvases[index];
+        if (canvas.width, canvas.height) != (width, height) {
+            *canvas = Canvas { left, top, width, height, rgba: empty };
+        }
+        return index;
+    }
+
+    // sends the whole canvas, which replaces the last image sent for it
+    fn show(&mut self, index: usize) {
+        let canvas = &self.canvases[index];
+        let (columns, rows) = self.graphics.screen_size(canvas.width, canvas.height);
+        let image = self.graphics.image(&canvas.rgba, canvas.width, canvas.height, columns, rows, index as u32 + 1);
+        let (left, top) = (canvas.left, canvas.top);
+        self.text.draw_escape(left, top, &image);
+    }
+}
+
+
+impl<W: Write> Renderer for GraphicsRenderer<W> {
+    fn set_theme(&mut self, theme: usize) {
+        self.text.set_theme(theme);
+    }
+
+    fn clear(&mut self) {
+        self.canvases.clear();
+        let clear_images = self.graphics.clear(); // clearing the screen doesn't get rid of kitty's images
+        self.text.draw_escape(1, 1, &clear_images);
+        self.text.clear();
+    }
+
+    fn draw_cells(&mut self, board: &Board, cells: &[Point], left: u16, top: u16) {
+        let index = self.canvas(left, top, board.width, board.height);
+        let theme = &THEMES[self.text.theme];
+        let canvas = &mut self.canvases[index];
+        for cell in cells {
+            let pixel = match board.cells.get(cell) {
+                Some(&state) => {
+                    let Rgb(r, g, b) = cell_colour(board.automaton, state, theme).unwrap_or(PIXEL_DEFAULT_COLOUR);
+                    [r, g, b, 255]
+                }
+                None => [0; 4]
+            };
+            let offset = (cell.y as usize * canvas.width as usize + cell.x as usize) * 4;
+            canvas.rgba[offset..offset + 4].copy_from_slice(&pixel);
+        }
+        self.show(index);
+    }
+
+    fn draw_text(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>) {
+        self.text.draw_text(left, top, text, colour);
+    }
+
+    fn draw_highlighted(&mut self, left: u16, top: u16, text: &str, colour: Option<Rgb>) {
+        self.text.draw_highlighted(left, top, text, colour);
+    }
+
+    fn draw_status(&mut self, top: u16, text: &str) {
+        self.text.draw_status(top, text);
+    }
+
+    fn draw_image(&mut self, left: u16, top: u16, rgba: &[u8], width: u32, height: u32) {
+        let index = self.canvas(left, top, width, height);
+        self.canvases[index].rgba.copy_from_slice(rgba);
+        self.show(index);
+    }
+
+    fn place_cursor(&mut self, position: Option<(u16, u16)>) {
+        self.text.place_cursor(position);
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        return self.text.present();
+    }
+}
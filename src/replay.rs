@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+use termion::event::Key;
+
+
+
+const HEADER: &str = "# game of life replay (play it back with --replay)";
+
+
+// something that happened in the game loop that changed the board (or could go on to change it)
+// drawing is left out since it can all be worked out again from these
+pub enum Event {
+    Step, // generations_per_frame generations went by
+    Skip(u32), // this many generations of a skip got done in one frame
+    Key(Key),
+}
+
+
+// writes everything needed to play a game back again: the seed the random boards came from, the arguments, and every event with when it happened
+pub struct Recorder {
+    file: BufWriter<fs::File>,
+    path: String,
+    start: Instant,
+    failed: bool, // writing to the file didn't work, so the rest of the game isn't recorded
+}
+
+
+// a recording read back in, with the events still to come at the front
+pub struct Replay {
+    pub path: String,
+    pub seed: u64,
+    pub args: Vec<String>,
+    pub width: u32, // the board size is kept since the default size depends on the terminal
+    pub height: u32,
+    pub pattern_of_the_day: Option<String>, // which pattern it was on the day it was recorded
+    pub events: VecDeque<(Duration, Event)>, // how long after the start each one happened
+    pub hash: Option<u64>, // Board::state_hash at the end of the game, None if the game didn't end properly
+}
+
+
+// the arguments to keep in a recording, everything except --record and --replay (and the files that go with them)
+pub fn recorded_args(args: &[String]) -> Vec<String> {
+    let mut kept = Vec::new();
+    let mut skip_next = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        } else if arg == "--record" || arg == "--replay" {
+            skip_next = true;
+        } else {
+            kept.push(arg.clone());
+        }
+    }
+    return kept;
+}
+
+
+// chars are written as numbers so spaces and newlines (enter) survive being put in a line of text
+fn key_to_string(key: Key) -> String {
+    return match key {
+        Key::Char(c) => format!("Char {}", c as u32),
+        Key::Alt(c) => format!("Alt {}", c as u32),
+        Key::Ctrl(c) => format!("Ctrl {}", c as u32),
+        Key::F(number) => format!("F {}", number),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Up => "Up".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PageUp".to_string(),
+        Key::PageDown => "PageDown".to_string(),
+        Key::BackTab => "BackTab".to_string(),
+        Key::Delete => "Delete".to_string(),
+        Key::Insert => "Insert".to_string(),
+        Key::Esc => "Esc".to_string(),
+        _ => "Null".to_string(), // nothing handles these anyway
+    };
+}
+
+
+fn parse_key(text: &str) -> Option<Key> {
+    let (name, value) = match text.split_once(' ') {
+        Some((name, value)) => (name, Some(value)),
+        None => (text, None)
+    };
+    let char_value = || value?.parse::<u32>().ok().and_then(char::from_u32);
+    return match name {
+        "Char" => char_value().map(Key::Char),
+        "Alt" => char_value().map(Key::Alt),
+        "Ctrl" => char_value().map(Key::Ctrl),
+        "F" => value?.parse::<u8>().ok().map(Key::F),
+        "Backspace" => Some(Key::Backspace),
+        "Left" => Some(Key::Left),
+        "Right" => Some(Key::Right),
+        "Up" => Some(Key::Up),
+        "Down" => Some(Key::Down),
+        "Home" => Some(Key::Home),
+        "End" => Some(Key::End),
+        "PageUp" => Some(Key::PageUp),
+        "PageDown" => Some(Key::PageDown),
+        "BackTab" => Some(Key::BackTab),
+        "Delete" => Some(Key::Delete),
+        "Insert" => Some(Key::Insert),
+        "Esc" => Some(Key::Esc),
+        "Null" => Some(Key::Null),
+        _ => None
+    };
+}
+
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64, args: &[String], width: u32, height: u32, pattern_of_the_day: Option<&str>) -> Result<Recorder, String> {
+        let file = fs::File::create(path).map_err(|error| format!("couldn't create {}: {}", path, error))?;
+        let mut recorder = Recorder { file: BufWriter::new(file), path: path.to_string(), start: Instant::now(), failed: false };
+        let mut header = format!("{}\nseed {}\n", HEADER, seed);
+        for arg in args {
+            header.push_str(&format!("arg {}\n", arg));
+        }
+        header.push_str(&format!("size {} {}\n", width, height));
+        if let Some(name) = pattern_of_the_day {
+            header.push_str(&format!("potd {}\n", name));
+        }
+        recorder.file.write_all(header.as_bytes()).map_err(|error| format!("couldn't write to {}: {}", path, error))?;
+        return Ok(recorder);
+    }
+
+    // a recording that can't be written to isn't worth stopping the game over, so it just stops recording
+    // returns what went wrong the first time writing fails, and Ok after that
+    pub fn record(&mut self, event: &Event) -> Result<(), String> {
+        if self.failed {
+            return Ok(());
+        }
+        let event = match event {
+            Event::Step => "step".to_string(),
+            Event::Skip(generations) => format!("skip {}", generations),
+            Event::Key(key) => format!("key {}", key_to_string(*key)),
+        };
+        return writeln!(self.file, "{} {}", self.start.elapsed().as_millis(), event).map_err(|error| {
+            self.failed = true;
+            return format!("Stopped recording, couldn't write to {}: {}", self.path, error);
+        });
+    }
+
+    // the hash of the board at the end lets a replay check that it came out the same
+    pub fn finish(mut self, hash: u64) {
+        writeln!(self.file, "hash {:016x}", hash).ok();
+        self.file.flush().ok();
+    }
+}
+
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let contents = fs::read_to_string(path).map_err(|error| format!("couldn't read {}: {}", path, error))?;
+        let mut seed = None;
+        let mut size = None;
+        let mut replay = Replay {
+            path: path.to_string(),
+            seed: 0,
+            args: Vec::new(),
+            width: 0,
+            height: 0,
+            pattern_of_the_day: None,
+            events: VecDeque::new(),
+            hash: None,
+        };
+        for (line_number, line) in contents.lines().enumerate() {
+            let invalid = || format!("{}:{}: invalid line \"{}\"", path, line_number + 1, line);
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let (name, value) = line.split_once(' ').ok_or_else(invalid)?;
+            match name {
+                "seed" => seed = Some(value.parse::<u64>().map_err(|_| invalid())?),
+                "arg" => replay.args.push(value.to_string()),
+                "size" => {
+                    let (width, height) = value.split_once(' ').ok_or_else(invalid)?;
+                    size = Some((width.parse::<u32>().map_err(|_| invalid())?, height.parse::<u32>().map_err(|_| invalid())?));
+                }
+                "potd" => replay.pattern_of_the_day = Some(value.to_string()),
+                "hash" => replay.hash = Some(u64::from_str_radix(value, 16).map_err(|_| invalid())?),
+                _ => { // an event, which starts with when it happened
+                    let time = Duration::from_millis(name.parse::<u64>().map_err(|_| invalid())?);
+                    let event = match value.split_once(' ') {
+                        None if value == "step" => Event::Step,
+                        Some(("skip", generations)) => Event::Skip(generations.parse::<u32>().map_err(|_| invalid())?),
+                        Some(("key", key)) => Event::Key(parse_key(key).ok_or_else(invalid)?),
+                        _ => return Err(invalid())
+                    };
+                    replay.events.push_back((time, event));
+                }
+            }
+        }
+        replay.seed = seed.ok_or(format!("{} isn't a replay (it has no seed)", path))?;
+        (replay.width, replay.height) = size.ok_or(format!("{} isn't a replay (it has no board size)", path))?;
+        return Ok(replay);
+    }
+
+    // keys at the front of the events that are due by now (a step or skip in the way means they aren't due until that's happened)
+    pub fn due_keys(&mut self, elapsed: Duration) -> Vec<Key> {
+        let mut keys = Vec::new();
+        while let Some((time, Event::Key(key))) = self.events.front() {
+            if *time > elapsed {
+                break;
+            }
+            keys.push(*key);
+            self.events.pop_front();
+        }
+        return keys;
+    }
+}
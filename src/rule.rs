@@ -0,0 +1,342 @@
+use core::fmt;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+
+
+const MAX_RANGE: i16 = 7; // the furthest away a neighbour can be, so counts always fit in a u8
+
+
+
+// which cells around a cell count as its neighbours
+#[derive(Clone, PartialEq)]
+pub enum Neighbourhood {
+    Moore(u8), // every cell within this range, diagonals included (range 1 is the usual 8 cells)
+    VonNeumann(u8), // every cell within this many steps without going diagonally (range 1 is the 4 cells next to it)
+    Custom(Vec<(i8, i8)>), // any list of x,y offsets from the cell
+    Hexagonal, // the 6 cells touching it on a hex grid, stored like golly does with each row shifted half a cell to the left of the one above (so up-right and down-left aren't neighbours)
+}
+
+
+// a birth/survival rule like B3/S23 (regular life) or a Generations rule like B2/S/3 (brian's brain)
+// Generations rules have more than 2 states, every state after "alive" is a "dying" state that a cell has to count down through before it's dead again
+// Larger than Life rules can be written the way golly does (R5,C0,M1,S34..58,B34..45,NM for bugs)
+// a /N part picks the neighbourhood (B2/S013/NV for von neumann, B2/S34/NH for hexagonal, B5-7/S4-8/NM2 for range 2 moore or B2/S/N-1,0;1,0;0,2 for a custom one)
+#[derive(Clone, PartialEq)]
+pub struct Rule {
+    pub birth: Vec<bool>, // birth[n] is true if a dead cell with n live neighbours comes alive (one for every count from 0 to the neighbourhood's size)
+    pub survival: Vec<bool>, // survival[n] is true if a live cell with n live neighbours stays alive
+    pub num_states: u8, // 2 for regular life-like rules (dead and alive)
+    pub neighbourhood: Neighbourhood,
+}
+
+
+impl Neighbourhood {
+    // the x,y offsets of every neighbour
+    pub fn offsets(&self) -> Vec<(i16, i16)> {
+        let range = |range: u8| -(range as i16)..=range as i16;
+        return match self {
+            Neighbourhood::Moore(size) => range(*size)
+                .flat_map(|y| range(*size).map(move |x| (x, y)))
+                .filter(|&offset| offset != (0, 0))
+                .collect(),
+            Neighbourhood::VonNeumann(size) => range(*size)
+                .flat_map(|y| range(*size).map(move |x| (x, y)))
+                .filter(|&(x, y)| (x, y) != (0, 0) && x.abs() + y.abs() <= *size as i16)
+                .collect(),
+            Neighbourhood::Custom(offsets) => offsets.iter().map(|&(x, y)| (x as i16, y as i16)).collect(),
+            Neighbourhood::Hexagonal => vec![(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+        };
+    }
+
+    // how many neighbours every cell has, the most that can be counted
+    pub fn size(&self) -> usize {
+        return match self {
+            Neighbourhood::Moore(range) => (2 * *range as usize + 1).pow(2) - 1,
+            Neighbourhood::VonNeumann(range) => 2 * *range as usize * (*range as usize + 1),
+            Neighbourhood::Custom(offsets) => offsets.len(),
+            Neighbourhood::Hexagonal => 6,
+        };
+    }
+
+    // how far away the furthest neighbour is, across or down (whichever is further)
+    pub fn reach(&self) -> u16 {
+        return self.offsets().iter().map(|&(x, y)| x.unsigned_abs().max(y.unsigned_abs())).max().unwrap_or(0);
+    }
+
+    // M or V followed by an optional range (M2), H, or a list of offsets (-1,0;1,0;0,-1;0,1)
+    fn parse(text: &str) -> Result<Neighbourhood, String> {
+        let parse_range = |digits: &str| -> Result<u8, String> {
+            if digits.is_empty() {
+                return Ok(1);
+            }
+            return match digits.parse::<u8>() {
+                Ok(range) if range >= 1 && range as i16 <= MAX_RANGE => Ok(range),
+                _ => Err(format!("neighbourhood range \"{}\" should be from 1 to {}", digits, MAX_RANGE))
+            };
+        };
+        let mut chars = text.chars();
+        return match chars.next() {
+            Some('M') | Some('m') => parse_range(chars.as_str()).map(Neighbourhood::Moore),
+            Some('V') | Some('v') => parse_range(chars.as_str()).map(Neighbourhood::VonNeumann),
+            Some('H') | Some('h') if chars.as_str().is_empty() => Ok(Neighbourhood::Hexagonal),
+            _ => {
+                let mut offsets = Vec::new();
+                for pair in text.split(';').filter(|pair| !pair.is_empty()) {
+                    let invalid = || format!("\"{}\" isn't an x,y neighbour offset", pair);
+                    let (x, y) = pair.split_once(',').ok_or_else(invalid)?;
+                    let (x, y) = (x.trim().parse::<i8>().map_err(|_| invalid())?, y.trim().parse::<i8>().map_err(|_| invalid())?);
+                    if (x, y) == (0, 0) || (x as i16).abs() > MAX_RANGE || (y as i16).abs() > MAX_RANGE {
+                        return Err(format!("neighbour offset {},{} should be from -{} to {} and not 0,0", x, y, MAX_RANGE, MAX_RANGE));
+                    }
+                    if !offsets.contains(&(x, y)) {
+                        offsets.push((x, y));
+                    }
+                }
+                if offsets.is_empty() {
+                    return Err(format!("couldn't understand neighbourhood \"{}\" (expected M, V, H, M2 or a list of offsets like -1,0;1,0)", text));
+                }
+                Ok(Neighbourhood::Custom(offsets))
+            }
+        };
+    }
+}
+
+
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let range = |range: u8| if range == 1 { String::new() } else { range.to_string() };
+        return match self {
+            Neighbourhood::Moore(size) => write!(f, "M{}", range(*size)),
+            Neighbourhood::VonNeumann(size) => write!(f, "V{}", range(*size)),
+            Neighbourhood::Custom(offsets) => {
+                let pairs: Vec<String> = offsets.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+                write!(f, "{}", pairs.join(";"))
+            }
+            Neighbourhood::Hexagonal => write!(f, "H"),
+        };
+    }
+}
+
+
+impl Rule {
+    pub fn conways_life() -> Rule {
+        return Rule::parse("B3/S23").unwrap();
+    }
+
+    // accepts B3/S23 style rules, optionally followed by a /C part for Generations rules (B2/S/3 or B2/S/C3) and a /N part for the neighbourhood
+    // golly's way of writing von neumann and hexagonal rules (B2/S013V and B2/S34H) works too
+    pub fn parse(rule_string: &str) -> Result<Rule, String> {
+        if rule_string.trim().starts_with(['R', 'r']) {
+            return Rule::parse_larger_than_life(rule_string);
+        }
+        let mut birth = None;
+        let mut survival = None;
+        let mut num_states = None;
+        let mut neighbourhood = None;
+        let mut rule_text = rule_string.trim();
+        if !rule_text.rsplit('/').next().unwrap_or("").starts_with(['N', 'n']) {
+            if let Some(without_v) = rule_text.strip_suffix(['V', 'v']) {
+                rule_text = without_v;
+                neighbourhood = Some(Neighbourhood::VonNeumann(1));
+            } else if let Some(without_h) = rule_text.strip_suffix(['H', 'h']) {
+                rule_text = without_h;
+                neighbourhood = Some(Neighbourhood::Hexagonal);
+            }
+        }
+        for part in rule_text.split('/') {
+            let mut chars = part.chars();
+            let (kind, digits) = match chars.next() {
+                Some('B') | Some('b') => ('B', chars.as_str()),
+                Some('S') | Some('s') => ('S', chars.as_str()),
+                Some('C') | Some('c') | Some('G') | Some('g') => ('C', chars.as_str()),
+                Some('N') | Some('n') => ('N', chars.as_str()),
+                Some(c) if c.is_ascii_digit() && birth.is_some() && survival.is_some() => ('C', part), // bare number after B and S is the state count
+                _ => return Err(format!("couldn't understand \"{}\" in rule \"{}\"", part, rule_string))
+            };
+            match kind {
+                'B' if birth.is_none() => birth = Some(digits), // the counts can't be read until the neighbourhood's known
+                'S' if survival.is_none() => survival = Some(digits),
+                'C' if num_states.is_none() => {
+                    let states = digits.parse::<u8>().map_err(|_| format!("\"{}\" isn't a valid number of states", digits))?;
+                    if states < 2 {
+                        return Err("a rule needs at least 2 states".to_string());
+                    }
+                    num_states = Some(states);
+                }
+                'N' if neighbourhood.is_none() => neighbourhood = Some(Neighbourhood::parse(digits)?),
+                _ => return Err(format!("rule \"{}\" has more than one {} part", rule_string, kind))
+            }
+        }
+        let neighbourhood = neighbourhood.unwrap_or(Neighbourhood::Moore(1));
+        let (birth, survival) = match (birth, survival) {
+            (Some(birth), Some(survival)) => (
+                parse_neighbour_counts(birth, neighbourhood.size())?,
+                parse_neighbour_counts(survival, neighbourhood.size())?
+            ),
+            _ => return Err(format!("rule \"{}\" needs both a B and an S part", rule_string))
+        };
+        if birth[0] {
+            return Err("B0 rules aren't supported".to_string()); // every empty cell on the board would have to come alive
+        }
+        return Ok(Rule {
+            birth,
+            survival,
+            num_states: num_states.unwrap_or(2),
+            neighbourhood,
+        });
+    }
+
+    // Rr,Cc,Mm,Smin..max,Bmin..max,Nn where r is the range, c the number of states (0 means 2), m is 1 if a cell counts itself as a neighbour and n is M for moore or N for von neumann
+    fn parse_larger_than_life(rule_string: &str) -> Result<Rule, String> {
+        let mut values = [None; 4]; // R, C, M and N
+        let mut ranges = [None; 2]; // S and B
+        for part in rule_string.trim().split(',') {
+            let invalid = || format!("couldn't understand \"{}\" in rule \"{}\"", part, rule_string);
+            let mut chars = part.chars();
+            let kind = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+            let text = chars.as_str();
+            let (slot, value) = match kind {
+                'R' => (&mut values[0], text.parse::<u8>().map_err(|_| invalid())?),
+                'C' => (&mut values[1], text.parse::<u8>().map_err(|_| invalid())?),
+                'M' => (&mut values[2], text.parse::<u8>().ok().filter(|&middle| middle <= 1).ok_or_else(invalid)?),
+                'N' => (&mut values[3], match text {
+                    "M" | "m" => 0,
+                    "N" | "n" => 1,
+                    _ => return Err(format!("only moore (NM) and von neumann (NN) Larger than Life neighbourhoods are supported, not \"{}\"", part))
+                }),
+                'S' | 'B' => {
+                    let (min, max) = text.split_once("..").ok_or_else(invalid)?;
+                    let range = (min.parse::<usize>().map_err(|_| invalid())?, max.parse::<usize>().map_err(|_| invalid())?);
+                    let slot = &mut ranges[if kind == 'S' { 0 } else { 1 }];
+                    if slot.replace(range).is_some() {
+                        return Err(format!("rule \"{}\" has more than one {} part", rule_string, kind));
+                    }
+                    continue;
+                }
+                _ => return Err(invalid())
+            };
+            if slot.replace(value).is_some() {
+                return Err(format!("rule \"{}\" has more than one {} part", rule_string, kind));
+            }
+        }
+        let (survival, birth) = match ranges {
+            [Some(survival), Some(birth)] => (survival, birth),
+            _ => return Err(format!("rule \"{}\" needs both an S and a B part", rule_string))
+        };
+        let range = values[0].unwrap_or(1);
+        if range == 0 || range as i16 > MAX_RANGE {
+            return Err(format!("neighbourhood range \"{}\" should be from 1 to {}", range, MAX_RANGE));
+        }
+        let neighbourhood = match values[3] {
+            Some(1) => Neighbourhood::VonNeumann(range),
+            _ => Neighbourhood::Moore(range)
+        };
+
+        // a live cell that counts itself has one more neighbour than it would here, so M1 is the same as taking 1 off the survival counts
+        let middle = values[2].unwrap_or(0) as usize;
+        let survival = (survival.0.saturating_sub(middle), survival.1.checked_sub(middle));
+        let counts = |min: usize, max: Option<usize>| -> Result<Vec<bool>, String> {
+            let counts: Vec<bool> = (0..=neighbourhood.size()).map(|n| Some(n) <= max && n >= min).collect();
+            return match max {
+                Some(max) if max > neighbourhood.size() => Err(format!("{} is more neighbours than this neighbourhood has ({})", max, neighbourhood.size())),
+                _ => Ok(counts)
+            };
+        };
+        let rule = Rule {
+            birth: counts(birth.0, Some(birth.1))?,
+            survival: counts(survival.0, survival.1)?,
+            num_states: values[1].unwrap_or(0).max(2),
+            neighbourhood,
+        };
+        if rule.birth[0] {
+            return Err("B0 rules aren't supported".to_string());
+        }
+        return Ok(rule);
+    }
+
+    // Larger than Life rules are written in golly's format so other programs can read them, which only works when they're moore or von neumann and each of B and S is one range of counts
+    fn larger_than_life_string(&self) -> Option<String> {
+        let range = match self.neighbourhood {
+            Neighbourhood::Moore(range) | Neighbourhood::VonNeumann(range) if range > 1 => range,
+            _ => return None
+        };
+        let count_range = |counts: &[bool]| -> Option<(usize, usize)> {
+            let min = counts.iter().position(|&count| count)?;
+            let max = counts.iter().rposition(|&count| count)?;
+            return counts[min..=max].iter().all(|&count| count).then_some((min, max));
+        };
+        let (birth, survival) = (count_range(&self.birth)?, count_range(&self.survival)?);
+        let neighbourhood = if let Neighbourhood::Moore(_) = self.neighbourhood { 'M' } else { 'N' };
+        let states = if self.num_states > 2 { self.num_states } else { 0 };
+        return Some(format!("R{},C{},M0,S{}..{},B{}..{},N{}", range, states, survival.0, survival.1, birth.0, birth.1, neighbourhood));
+    }
+}
+
+
+// a digit for each count (B36), or counts and ranges split up with commas for neighbourhoods big enough to need counts over 9 (B3,10-12)
+fn parse_neighbour_counts(text: &str, max: usize) -> Result<Vec<bool>, String> {
+    let mut counts = vec![false; max + 1];
+    let parse_count = |count: &str| -> Result<usize, String> {
+        return match count.trim().parse::<usize>() {
+            Ok(n) if n <= max => Ok(n),
+            _ => Err(format!("\"{}\" isn't a valid neighbour count (this neighbourhood has {} cells)", count, max))
+        };
+    };
+    if text.contains([',', '-']) {
+        for item in text.split(',').filter(|item| !item.is_empty()) {
+            let (start, end) = match item.split_once('-') {
+                Some((start, end)) => (parse_count(start)?, parse_count(end)?),
+                None => (parse_count(item)?, parse_count(item)?)
+            };
+            counts.iter_mut().take(end + 1).skip(start).for_each(|count| *count = true);
+        }
+        return Ok(counts);
+    }
+    for digit in text.chars() {
+        counts[parse_count(digit.encode_utf8(&mut [0; 4]))?] = true;
+    }
+    return Ok(counts);
+}
+
+
+// the opposite of parse_neighbour_counts
+fn neighbour_counts_string(counts: &[bool]) -> String {
+    let set: Vec<usize> = (0..counts.len()).filter(|&n| counts[n]).collect();
+    if set.iter().all(|&n| n < 10) {
+        return set.iter().map(|n| n.to_string()).collect();
+    }
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for n in set {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == n => *end = n,
+            _ => ranges.push((n, n))
+        }
+    }
+    let items: Vec<String> = ranges.iter()
+        .map(|&(start, end)| if start == end { start.to_string() } else { format!("{}-{}", start, end) })
+        .collect();
+    if items.len() == 1 && !items[0].contains('-') {
+        return format!("{}-{}", items[0], items[0]); // a lone number would be read as separate digits
+    }
+    return items.join(",");
+}
+
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(larger_than_life) = self.larger_than_life_string() {
+            return write!(f, "{}", larger_than_life);
+        }
+        write!(f, "B{}/S{}", neighbour_counts_string(&self.birth), neighbour_counts_string(&self.survival))?;
+        if self.num_states > 2 {
+            write!(f, "/{}", self.num_states)?;
+        }
+        if self.neighbourhood != Neighbourhood::Moore(1) {
+            write!(f, "/N{}", self.neighbourhood)?;
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,272 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::rule::Neighbourhood;
+
+
+
+// one of the states in a transition, either a state or a variable that can be any of a set of states
+#[derive(Clone, Copy, PartialEq)]
+enum Element {
+    State(u8),
+    Variable(usize), // index into RuleTable.variables
+}
+
+
+// a line of the table: what a cell in state `from` turns into if its neighbours match `neighbours`
+#[derive(Clone)]
+struct Transition {
+    from: Element,
+    neighbours: Vec<Element>, // in golly's order for the neighbourhood, which goes clockwise from north
+    to: Element,
+}
+
+
+// the @TABLE part of a golly .rule file, a list of transitions that say what each cell turns into given its state and its neighbours' states
+// any state/neighbours combination that doesn't match a transition stays the same
+#[derive(Clone)]
+pub struct RuleTable {
+    pub name: String, // from the @RULE line
+    pub num_states: u16, // up to 256
+    pub neighbourhood: Neighbourhood, // for drawing (hexagonal tables get drawn as a hex grid)
+    offsets: Vec<(i16, i16)>, // the neighbours in the order transitions list them
+    variables: Vec<Vec<u8>>, // every state each variable can be
+    transitions: Vec<Transition>,
+    permutations: Vec<Vec<usize>>, // every way the symmetries let the neighbours be rearranged (empty for "permute", where any order goes)
+    cache: BTreeMap<Vec<u8>, u8>, // the state and neighbours of every cell that's been looked up before, and what it turned into
+}
+
+
+// the neighbours of each neighbourhood golly's tables can use, clockwise from north
+fn neighbourhood_offsets(name: &str) -> Option<(Neighbourhood, Vec<(i16, i16)>)> {
+    return match name {
+        "Moore" => Some((Neighbourhood::Moore(1), vec![(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)])),
+        "vonNeumann" => Some((Neighbourhood::VonNeumann(1), vec![(0, -1), (1, 0), (0, 1), (-1, 0)])),
+        "hexagonal" => Some((Neighbourhood::Hexagonal, vec![(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)])),
+        "oneDimensional" => Some((Neighbourhood::Custom(vec![(-1, 0), (1, 0)]), vec![(-1, 0), (1, 0)])),
+        _ => None
+    };
+}
+
+
+// all the rotations (and reflections if reflect is true) of n neighbours going round in a circle, rotating by n / rotations each time
+fn symmetry_permutations(n: usize, rotations: usize, reflect: bool) -> Vec<Vec<usize>> {
+    let mut permutations: Vec<Vec<usize>> = Vec::new();
+    for rotation in 0..rotations {
+        let rotated: Vec<usize> = (0..n).map(|i| (i + rotation * n / rotations) % n).collect();
+        let reflected: Vec<usize> = (0..n).map(|i| rotated[(n - i) % n]).collect(); // mirrored through north
+        permutations.push(rotated);
+        if reflect {
+            permutations.push(reflected);
+        }
+    }
+    permutations.sort();
+    permutations.dedup(); // a neighbourhood with 2 cells has the same rotations and reflections
+    return permutations;
+}
+
+
+impl RuleTable {
+    pub fn parse(text: &str) -> Result<RuleTable, String> {
+        let mut name = String::new();
+        let mut section = "";
+        let mut num_states = None;
+        let mut neighbourhood = None;
+        let mut symmetries = "none".to_string();
+        let mut variable_names: Vec<String> = Vec::new();
+        let mut variables: Vec<Vec<u8>> = Vec::new();
+        let mut transitions = Vec::new();
+        let mut found_table = false;
+
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let error = |message: String| format!("line {}: {}", line_number + 1, message);
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('@') {
+                let (header, value) = header.split_once(char::is_whitespace).unwrap_or((header, ""));
+                section = header;
+                if header == "RULE" {
+                    name = value.trim().to_string();
+                }
+                found_table |= header == "TABLE";
+                continue;
+            }
+            if section != "TABLE" {
+                continue; // colours, icons and so on are for golly's UI
+            }
+
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+                match key.trim() {
+                    "n_states" => num_states = Some(value.parse::<u16>().ok().filter(|n| (2..=256).contains(n)).ok_or_else(|| error(format!("\"{}\" isn't a valid number of states (2 to 256)", value)))?),
+                    "neighborhood" => neighbourhood = Some(neighbourhood_offsets(value).ok_or_else(|| error(format!("unsupported neighborhood \"{}\" (expected Moore, vonNeumann, hexagonal or oneDimensional)", value)))?),
+                    "symmetries" => symmetries = value.to_string(),
+                    other => return Err(error(format!("unknown setting \"{}\"", other)))
+                }
+                continue;
+            }
+
+            // the states and variables in a line, with the names of variables replaced by their index
+            let states = num_states.ok_or_else(|| error("n_states has to come before any variables or transitions".to_string()))?;
+            let parse_element = |element: &str| -> Result<Element, String> {
+                let element = element.trim();
+                if let Some(index) = variable_names.iter().position(|name| name == element) {
+                    return Ok(Element::Variable(index));
+                }
+                return match element.parse::<u16>() {
+                    Ok(state) if state < states => Ok(Element::State(state as u8)),
+                    _ => Err(error(format!("\"{}\" isn't a state or a variable", element)))
+                };
+            };
+
+            if let Some(variable) = line.strip_prefix("var ") {
+                let (variable_name, values) = variable.split_once('=').ok_or_else(|| error("variables look like var a={0,1,2}".to_string()))?;
+                let values = values.trim().trim_start_matches('{').trim_end_matches('}');
+                let mut set = Vec::new();
+                for value in values.split(',') {
+                    match parse_element(value)? {
+                        Element::State(state) => set.push(state),
+                        Element::Variable(index) => set.extend_from_slice(&variables[index]), // variables can be made out of other variables
+                    }
+                }
+                variable_names.push(variable_name.trim().to_string());
+                variables.push(set);
+                continue;
+            }
+
+            let (_, offsets) = neighbourhood.as_ref().ok_or_else(|| error("neighborhood has to come before any transitions".to_string()))?;
+            let elements: Vec<Element> = match line.contains(',') {
+                true => line.split(',').map(parse_element).collect::<Result<Vec<Element>, String>>()?,
+                false => line.chars().map(|c| parse_element(c.encode_utf8(&mut [0; 4]))).collect::<Result<Vec<Element>, String>>()?, // single digit states can leave out the commas
+            };
+            if elements.len() != offsets.len() + 2 {
+                return Err(error(format!("transitions need {} states (the cell, its {} neighbours and what it turns into)", offsets.len() + 2, offsets.len())));
+            }
+            let to = elements[elements.len() - 1];
+            if let Element::Variable(index) = to {
+                if !elements[..elements.len() - 1].contains(&to) && variables[index].len() > 1 {
+                    return Err(error("a transition can only turn a cell into a variable that it also matched".to_string()));
+                }
+            }
+            transitions.push(Transition { from: elements[0], neighbours: elements[1..elements.len() - 1].to_vec(), to });
+        }
+
+        if !found_table {
+            return Err("only rule files with a @TABLE section are supported".to_string());
+        }
+        let (neighbourhood, offsets) = neighbourhood.ok_or("the table doesn't say which neighborhood it uses")?;
+        let n = offsets.len();
+        let permutations = match symmetries.as_str() {
+            "permute" => Vec::new(),
+            "none" => symmetry_permutations(n, 1, false),
+            "reflect" | "reflect_horizontal" => symmetry_permutations(n, 1, true),
+            other => {
+                let reflect = other.ends_with("reflect");
+                let rotations = other.trim_start_matches("rotate").trim_end_matches("reflect").parse::<usize>()
+                    .ok()
+                    .filter(|&rotations| rotations > 0 && n % rotations == 0)
+                    .ok_or(format!("unsupported symmetries \"{}\" for this neighborhood", other))?;
+                symmetry_permutations(n, rotations, reflect)
+            }
+        };
+        let mut table = RuleTable {
+            name,
+            num_states: num_states.ok_or("the table doesn't say how many states it has")?,
+            neighbourhood,
+            offsets,
+            variables,
+            transitions,
+            permutations,
+            cache: BTreeMap::new(),
+        };
+        if table.next_state(&vec![0; n + 1]) != 0 {
+            return Err("rules where empty space comes alive aren't supported".to_string()); // every empty cell on the board would have to be checked every generation
+        }
+        return Ok(table);
+    }
+
+    // where each neighbour is, in the order next_state wants their states
+    pub fn offsets(&self) -> &[(i16, i16)] {
+        return &self.offsets;
+    }
+
+    // what a cell turns into, cell_and_neighbours is its state followed by its neighbours' states
+    pub fn next_state(&mut self, cell_and_neighbours: &[u8]) -> u8 {
+        if let Some(&state) = self.cache.get(cell_and_neighbours) {
+            return state;
+        }
+        let state = self.transitions.iter()
+            .find_map(|transition| self.apply(transition, cell_and_neighbours))
+            .unwrap_or(cell_and_neighbours[0]); // nothing matched so it stays the same
+        self.cache.insert(cell_and_neighbours.to_vec(), state);
+        return state;
+    }
+
+    // what the transition turns the cell into, if it matches
+    fn apply(&self, transition: &Transition, cell_and_neighbours: &[u8]) -> Option<u8> {
+        let mut bound = vec![None; self.variables.len()]; // a variable that appears more than once has to be the same state every time
+        if !self.matches(transition.from, cell_and_neighbours[0], &mut bound) {
+            return None;
+        }
+        let neighbours = &cell_and_neighbours[1..];
+        let matched = match self.permutations.is_empty() {
+            true => self.matches_in_any_order(&transition.neighbours, neighbours, &mut vec![false; neighbours.len()], &mut bound),
+            false => self.permutations.iter().any(|permutation| {
+                let mut trying = bound.clone();
+                if permutation.iter().zip(neighbours).all(|(&index, &state)| self.matches(transition.neighbours[index], state, &mut trying)) {
+                    bound = trying;
+                    return true;
+                }
+                return false;
+            }),
+        };
+        if !matched {
+            return None;
+        }
+        return Some(match transition.to {
+            Element::State(state) => state,
+            Element::Variable(index) => bound[index].unwrap_or(self.variables[index][0]),
+        });
+    }
+
+    fn matches(&self, element: Element, state: u8, bound: &mut [Option<u8>]) -> bool {
+        return match element {
+            Element::State(expected) => expected == state,
+            Element::Variable(index) => match bound[index] {
+                Some(bound_state) => bound_state == state,
+                None if self.variables[index].contains(&state) => {
+                    bound[index] = Some(state);
+                    true
+                }
+                None => false
+            }
+        };
+    }
+
+    // for permute symmetry: tries matching the first element to each neighbour that hasn't been used yet, then the rest of them to what's left
+    fn matches_in_any_order(&self, elements: &[Element], neighbours: &[u8], used: &mut Vec<bool>, bound: &mut Vec<Option<u8>>) -> bool {
+        let (element, rest) = match elements.split_first() {
+            Some(split) => split,
+            None => return true
+        };
+        for i in 0..neighbours.len() {
+            if used[i] {
+                continue;
+            }
+            let mut trying = bound.clone();
+            if self.matches(*element, neighbours[i], &mut trying) {
+                used[i] = true;
+                if self.matches_in_any_order(rest, neighbours, used, &mut trying) {
+                    *bound = trying;
+                    return true;
+                }
+                used[i] = false;
+            }
+        }
+        return false;
+    }
+}
@@ -0,0 +1,124 @@
+use std::{process, thread, time};
+use std::collections::VecDeque;
+use std::io::Write;
+use rand::Rng;
+use game_of_life::{Automaton, Board, Rule};
+use crate::graphics::Graphics;
+use crate::renderer::{self, every_cell};
+use crate::theme::{ColourDepth, THEMES};
+use crate::{check_board_size, terminal, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const SETTLED_PERIOD: usize = 30; // a board that repeats itself within this many generations has settled down
+const MAX_ROUND_GENERATIONS: u32 = 5000; // in case it settles into something with a longer period than that
+const SETTLED_TIME: time::Duration = time::Duration::from_secs(2); // how long a board that's settled stays up before the next round
+
+
+// a random theme that isn't the last one (or mono, which has no colours)
+fn pick_theme<R: Rng>(rng: &mut R, last: usize) -> usize {
+    loop {
+        let theme = rng.gen_range(1..THEMES.len());
+        if theme != last {
+            return theme;
+        }
+    }
+}
+
+
+// the "screensaver" subcommand: a random board that fills the whole terminal with nothing else on the screen
+// whenever it dies out or settles down it gets reseeded with a new colour theme, until a key is pressed
+// with --graphics (and a terminal that can draw images) it's drawn as an image, with a cell for every few pixels instead of every character
+#[allow(unused_must_use)]
+pub fn run_screensaver(args: Args) {
+    let (columns, rows) = terminal::terminal_size().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    let graphics = Graphics::detect(args.graphics).filter(|_| args.graphics);
+    let (width, height) = graphics.map_or((columns, rows), |graphics| graphics.cells_in(columns, rows));
+    let width = args.board_width.map_or(width, |board_width| board_width.min(width)) as u32;
+    let height = args.board_height.map_or(height, |board_height| board_height.min(height)) as u32;
+    check_board_size(&args, width, height, SIMULATION_BYTES_PER_CELL);
+    if args.automaton == Automaton::LangtonsAnt {
+        eprintln!("ants never settle down, so the screensaver can't run them");
+        process::exit(1);
+    }
+    let mut board = Board::new(width, height, args.automaton, args.rule.clone().unwrap_or_else(Rule::conways_life));
+    board.table = args.rule_table.clone();
+    let mut rng = rand::thread_rng();
+    let colour_depth = ColourDepth::detect();
+    let frame_delay = time::Duration::from_secs(1) / args.fps.max(1);
+
+    let key_source = terminal::key_source().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+
+    terminal::save_terminal_state();
+    terminal::install_panic_hook();
+    terminal::install_signal_handlers();
+    let _terminal_guard = terminal::TerminalGuard;
+    let mut stdout = terminal::enter_raw_mode().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    let key_input = terminal::spawn_key_reader(key_source);
+    write!(stdout, "{}", termion::cursor::Hide);
+    let mut renderer = renderer::for_terminal(&mut stdout, colour_depth, 0, columns, graphics);
+    renderer.clear();
+
+    let mut theme = 0;
+    let mut terminal_error = None;
+    'rounds: while !terminal::quit_requested() {
+        board.init_randomly_with(&mut rng);
+        theme = pick_theme(&mut rng, theme);
+        renderer.set_theme(theme);
+        let mut changed = every_cell(&board); // the new board and its new colours
+        let mut history: VecDeque<u64> = VecDeque::new();
+        let mut settled: Option<time::Instant> = None;
+        let mut generation = 0;
+        let mut next_frame_time = time::Instant::now();
+        while !terminal::quit_requested() && settled.is_none_or(|settled| settled.elapsed() < SETTLED_TIME) {
+            renderer.draw_cells(&board, &changed, 1, 1);
+            if let Err(error) = renderer.present() {
+                terminal_error = Some(error);
+                break 'rounds;
+            }
+            changed.clear();
+            if key_input.try_iter().next().is_some() {
+                break 'rounds;
+            }
+
+            if settled.is_none() {
+                changed = board.step().changed;
+                generation += 1;
+                let hash = board.state_hash();
+                if board.population() == 0 || history.contains(&hash) || generation >= MAX_ROUND_GENERATIONS {
+                    settled = Some(time::Instant::now());
+                }
+                history.push_back(hash);
+                if history.len() > SETTLED_PERIOD {
+                    history.pop_front();
+                }
+            }
+
+            next_frame_time += frame_delay;
+            let now = time::Instant::now();
+            if next_frame_time > now {
+                thread::sleep(next_frame_time - now);
+            } else {
+                next_frame_time = now;
+            }
+        }
+    }
+    renderer.clear();
+    renderer.present();
+    drop(renderer);
+    write!(stdout, "{}", termion::cursor::Show);
+    if let Some(error) = terminal_error {
+        drop(stdout); // leave the alternate screen first so the message stays up
+        eprintln!("couldn't draw to the terminal: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+use std::{process, thread, time};
+use rand::Rng;
+use game_of_life::engine::{Engine, EngineKind};
+use game_of_life::{objects, Automaton, Board, Point, Rule, DEAD};
+use game_of_life::objects::{Object, ObjectKind};
+use crate::census::describe;
+use crate::leaderboard::{Leaderboard, Methuselah, LEADERBOARD_LENGTH};
+use crate::{check_board_size, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: game_of_life search [--soups <n>] [--size <width>x<height>] [--seed <n>] [--rule <rule>] [--engine <engine>]";
+const SOUP_PADDING: u32 = 1000; // room around each soup so that anything it sends flying doesn't hit the edge before the soup settles down
+const ESCAPE_DISTANCE: u32 = 500; // anything this far from where the soup started has flown away and gets taken off the board before it can hit the edge
+const MAX_GENERATIONS: u32 = 20000; // soups that haven't settled down by then get their census taken anyway
+const POPULATION_WINDOW: usize = 256; // how many generations in a row the population has to repeat for before a soup counts as settled
+const MAX_SETTLED_PERIOD: usize = 64; // the longest period the population can repeat with
+const SHOWN_METHUSELAHS: usize = 5; // how many of the longest lived soups get listed after the census
+
+
+// how often one kind of object turned up
+struct Tally {
+    count: u64,
+    soups: u32, // how many soups it turned up in
+    first_soup: u32, // index of the first soup it turned up in
+    apgcode: Option<String>,
+    description: String,
+}
+
+
+// everything one thread found
+struct SearchResults {
+    tallies: BTreeMap<String, Tally>, // by object label
+    unsettled: u32, // soups that were still going after MAX_GENERATIONS
+    longest_lived: Vec<Methuselah>, // the soups that took the longest to settle, longest first
+}
+
+
+// a soup after it's settled down
+struct Soup {
+    board: Board,
+    escaped: Vec<Object>, // spaceships that flew away and were taken off the board
+    lifespan: Option<u32>, // None if it didn't settle within MAX_GENERATIONS
+    peak_population: usize,
+}
+
+
+// if the population has been repeating (with a period of up to MAX_SETTLED_PERIOD) for all of the last POPULATION_WINDOW generations,
+// the generation it started repeating at (populations has the population of every generation so far)
+// spaceships flying off keep the same population so they don't stop a soup from counting as settled
+fn settled_at(populations: &[i64]) -> Option<u32> {
+    if populations.len() < POPULATION_WINDOW {
+        return None;
+    }
+    let last = populations.len() - 1;
+    let period = (1..=MAX_SETTLED_PERIOD).find(|&period| (last + 1 - POPULATION_WINDOW + period..=last).all(|i| populations[i] == populations[i - period]))?;
+    let mut start = last + 1 - POPULATION_WINDOW;
+    while start > 0 && populations[start - 1] == populations[start - 1 + period] {
+        start -= 1;
+    }
+    return Some(start as u32);
+}
+
+
+// takes anything that's got far enough away from the soup (along with anything close enough to it to be part of the same object) off the board
+// so that spaceships flying away don't hit the edge and turn into debris, and returns what it was
+fn remove_escaped(engine: &mut dyn Engine, rule: &Rule) -> Vec<Object> {
+    let edge = (SOUP_PADDING - ESCAPE_DISTANCE) as i16;
+    let (width, height) = engine.bounds();
+    let (right, bottom) = (width as i16 - edge, height as i16 - edge);
+    let mut cells = engine.cells();
+    let mut to_visit: Vec<Point> = cells.keys().filter(|cell| cell.x < edge || cell.y < edge || cell.x >= right || cell.y >= bottom).cloned().collect();
+    let mut escaped = Board::new(width, height, Automaton::Life, rule.clone());
+    while let Some(cell) = to_visit.pop() {
+        if let Some(state) = cells.remove(&cell) {
+            for y in cell.y - 2..=cell.y + 2 {
+                for x in cell.x - 2..=cell.x + 2 {
+                    if cells.contains_key(&Point{x, y}) {
+                        to_visit.push(Point{x, y});
+                    }
+                }
+            }
+            engine.set_cell(&cell, DEAD);
+            escaped.cells.insert(cell, state);
+        }
+    }
+    return objects::find_objects(&escaped);
+}
+
+
+// the population of each phase of a spaceship, starting from the one it's in
+fn phase_populations(spaceship: &Object, period: u32, rule: &Rule) -> Vec<i64> {
+    let padding = period as i16 + 1;
+    let width = spaceship.cells.iter().map(|(cell, _)| cell.x + 1).max().unwrap_or(0) + 2 * padding;
+    let height = spaceship.cells.iter().map(|(cell, _)| cell.y + 1).max().unwrap_or(0) + 2 * padding;
+    let mut board = Board::new(width as u32, height as u32, Automaton::Life, rule.clone());
+    for (cell, state) in &spaceship.cells {
+        board.cells.insert(Point{x: cell.x + padding, y: cell.y + padding}, *state);
+    }
+    let mut populations = Vec::new();
+    for _ in 0..period {
+        populations.push(board.population() as i64);
+        board.update_cells();
+    }
+    return populations;
+}
+
+
+// fills a soup from its seed in the middle of a big empty board and runs it on engine_kind until it settles down
+fn run_soup(seed: u64, soup_size: (u16, u16), rule: &Rule, engine_kind: EngineKind) -> Soup {
+    let mut soup = Board::new(soup_size.0 as u32, soup_size.1 as u32, Automaton::Life, rule.clone());
+    soup.init_from_seed(seed);
+    let mut board = Board::new(soup.width + 2 * SOUP_PADDING, soup.height + 2 * SOUP_PADDING, Automaton::Life, rule.clone());
+    for (cell, &state) in &soup.cells {
+        board.cells.insert(Point{x: cell.x + SOUP_PADDING as i16, y: cell.y + SOUP_PADDING as i16}, state);
+    }
+    let mut engine = engine_kind.engine_for(board).unwrap(); // run_search already checked the rule works on it
+
+    // populations doesn't count spaceships that have been taken off the board, even from before they were taken off, so that taking them off doesn't look like the soup changing
+    let mut populations = vec![engine.population() as i64];
+    let mut peak_population = engine.population();
+    let mut escaped = Vec::new();
+    let mut lifespan = None;
+    for generation in 1..=MAX_GENERATIONS {
+        engine.step();
+        populations.push(engine.population() as i64);
+        peak_population = peak_population.max(engine.population());
+        if !(generation as usize).is_multiple_of(MAX_SETTLED_PERIOD) {
+            continue;
+        }
+        for object in remove_escaped(engine.as_mut(), rule) {
+            if let ObjectKind::Spaceship{period, ..} = object.kind {
+                let phases = phase_populations(&object, period, rule);
+                for (past_generation, population) in populations.iter_mut().enumerate() {
+                    let phase = (past_generation as i64 - generation as i64).rem_euclid(period as i64);
+                    *population -= phases[phase as usize];
+                }
+            }
+            escaped.push(object);
+        }
+        lifespan = settled_at(&populations);
+        if lifespan.is_some() {
+            break;
+        }
+    }
+    let mut board = Board::new(engine.bounds().0, engine.bounds().1, Automaton::Life, rule.clone());
+    board.cells = engine.cells();
+    return Soup { board, escaped, lifespan, peak_population };
+}
+
+
+// runs every soup_index that's thread_index more than a multiple of num_threads
+fn search_thread(thread_index: u32, num_threads: u32, args: &Args, rule: &Rule, seed: u64, engine_kind: EngineKind) -> SearchResults {
+    let mut results = SearchResults { tallies: BTreeMap::new(), unsettled: 0, longest_lived: Vec::new() };
+    for soup_index in (thread_index..args.soups).step_by(num_threads as usize) {
+        let soup_seed = seed.wrapping_add(soup_index as u64);
+        let soup = run_soup(soup_seed, args.soup_size, rule, engine_kind);
+        match soup.lifespan {
+            Some(lifespan) => {
+                results.longest_lived.push(Methuselah { lifespan, peak_population: soup.peak_population, seed: soup_seed, size: args.soup_size, rule: rule.to_string() });
+                results.longest_lived.sort_by_key(|methuselah| std::cmp::Reverse(methuselah.lifespan));
+                results.longest_lived.truncate(LEADERBOARD_LENGTH); // nothing past this could get on the leaderboard
+            }
+            None => results.unsettled += 1
+        }
+        let mut objects = objects::find_objects(&soup.board);
+        objects.extend(soup.escaped);
+        for (count, object) in objects::census(&objects) {
+            let tally = results.tallies.entry(object.label()).or_insert_with(|| Tally {
+                count: 0,
+                soups: 0,
+                first_soup: soup_index,
+                apgcode: object.apgcode.clone(),
+                description: describe(object)
+            });
+            tally.count += count as u64;
+            tally.soups += 1;
+        }
+    }
+    return results;
+}
+
+
+// the "search" subcommand: a small soup search like apgsearch's, which runs lots of random soups until they settle down and counts up what's left
+// soup n's seed is --seed plus n, so any soup can be run again on its own with --soups 1 and its seed
+pub fn run_search(args: Args) {
+    if args.automaton != Automaton::Life {
+        eprintln!("soup searches only work in Life\n{}", USAGE);
+        process::exit(1);
+    }
+    let rule = args.rule.clone().unwrap_or_else(Rule::conways_life);
+    let engine_kind = args.engine.unwrap_or(EngineKind::Incremental); // most of a soup settles down long before it's finished
+    if let Err(message) = engine_kind.engine_for(Board::new(1, 1, Automaton::Life, rule.clone())) {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+    let seed = args.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let (width, height) = (args.soup_size.0 as u32 + 2 * SOUP_PADDING, args.soup_size.1 as u32 + 2 * SOUP_PADDING);
+    check_board_size(&args, width, height, SIMULATION_BYTES_PER_CELL);
+    let num_threads = thread::available_parallelism().map_or(1, |threads| threads.get() as u32).min(args.soups.max(1));
+
+    let start = time::Instant::now();
+    let thread_results: Vec<SearchResults> = thread::scope(|scope| {
+        let threads: Vec<thread::ScopedJoinHandle<SearchResults>> = (0..num_threads)
+            .map(|thread_index| {
+                let (args, rule) = (&args, &rule);
+                return scope.spawn(move || search_thread(thread_index, num_threads, args, rule, seed, engine_kind));
+            })
+            .collect();
+        return threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    });
+
+    // put every thread's counts together
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut unsettled = 0;
+    let mut longest_lived = Vec::new();
+    for results in thread_results {
+        unsettled += results.unsettled;
+        longest_lived.extend(results.longest_lived);
+        for (label, tally) in results.tallies {
+            match tallies.get_mut(&label) {
+                Some(total) => {
+                    total.count += tally.count;
+                    total.soups += tally.soups;
+                    total.first_soup = total.first_soup.min(tally.first_soup);
+                }
+                None => {
+                    tallies.insert(label, tally);
+                }
+            }
+        }
+    }
+    let mut tallies: Vec<Tally> = tallies.into_values().collect();
+    tallies.sort_by_key(|tally| (std::cmp::Reverse(tally.count), tally.first_soup));
+    longest_lived.sort_by_key(|methuselah| (std::cmp::Reverse(methuselah.lifespan), methuselah.seed));
+
+    let seconds = start.elapsed().as_secs_f64();
+    let stdout = io::stdout();
+    let mut output = BufWriter::new(stdout.lock());
+    let mut lines = vec![format!(
+        "Searched {} {}x{} soups in {} from seed {} in {:.1}s on {} thread{} ({:.1} soups per second)",
+        args.soups, args.soup_size.0, args.soup_size.1, rule, seed, seconds, num_threads, if num_threads == 1 { "" } else { "s" }, args.soups as f64 / seconds
+    )];
+    if unsettled > 0 {
+        lines.push(format!("{} soups were still going after {} generations", unsettled, MAX_GENERATIONS));
+    }
+    lines.push(format!("{:>10} {:>8} {:>14}  {:<20} {:<20} object", "count", "soups", "rarity", "first soup seed", "apgcode"));
+    for tally in tallies {
+        lines.push(format!(
+            "{:>10} {:>8} {:>14}  {:<20} {:<20} {}",
+            tally.count,
+            tally.soups,
+            format!("1 in {}", args.soups / tally.soups),
+            seed.wrapping_add(tally.first_soup as u64),
+            tally.apgcode.as_deref().unwrap_or("-"),
+            tally.description
+        ));
+    }
+
+    // the longest lived soups, and any that made it onto the leaderboard
+    lines.push(String::new());
+    lines.push("Longest lived soups:".to_string());
+    lines.extend(Leaderboard::to_lines(&longest_lived[..longest_lived.len().min(SHOWN_METHUSELAHS)]));
+    let mut leaderboard = Leaderboard::load();
+    let places: Vec<usize> = longest_lived.iter().filter_map(|methuselah| leaderboard.add(methuselah.clone())).collect();
+    if let Some(best_place) = places.first() { // the longest lived one always gets the best place
+        lines.push(String::new());
+        lines.push(match leaderboard.save() {
+            Ok(path) => format!(
+                "{} of these soups got onto the leaderboard in {}, the longest lived one at number {} (the leaderboard subcommand lists it)",
+                places.len(), path, best_place
+            ),
+            Err(message) => message
+        });
+    }
+    for line in lines {
+        if writeln!(output, "{}", line).is_err() {
+            return; // whatever was reading the output stopped, which is fine
+        }
+    }
+    output.flush().ok();
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+
+
+// sixel images are sent as bands of 6 rows of pixels, one colour at a time
+const BAND_HEIGHT: u32 = 6;
+const MAX_PALETTE_SIZE: usize = 256; // most terminals only have this many colour registers
+const BACKGROUND: [u8; 3] = [0, 0, 0]; // what see-through pixels get drawn as, sixels can't leave pixels alone without also leaving the last frame behind
+
+
+// the colour of a pixel, rounded to the 6x6x6 colour cube if the image has too many colours for the palette
+fn palette_colour(pixel: &[u8], reduce: bool) -> [u8; 3] {
+    let colour = if pixel[3] == 0 { BACKGROUND } else { [pixel[0], pixel[1], pixel[2]] };
+    if reduce {
+        return colour.map(|value| ((value as u16 * 5 + 127) / 255 * 51) as u8);
+    }
+    return colour;
+}
+
+
+// a run of the same sixel character, using the repeat introducer when it's shorter
+fn push_run(sixels: &mut String, character: char, length: u32) {
+    if length > 3 {
+        sixels.push_str(&format!("!{}{}", length, character));
+    } else {
+        for _ in 0..length {
+            sixels.push(character);
+        }
+    }
+}
+
+
+// turns an rgba image into the escape code that draws it, with every pixel blown up to scale x scale
+pub fn encode(rgba: &[u8], width: u32, height: u32, scale: u32) -> String {
+    let (scaled_width, scaled_height) = (width * scale, height * scale);
+    let mut distinct_colours = HashSet::new();
+    let reduce = rgba.chunks(4).any(|pixel| {
+        distinct_colours.insert(palette_colour(pixel, false));
+        return distinct_colours.len() > MAX_PALETTE_SIZE;
+    });
+
+    // every pixel's index into the palette
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut palette_indexes: HashMap<[u8; 3], usize> = HashMap::new();
+    let indexes: Vec<usize> = rgba.chunks(4)
+        .map(|pixel| {
+            let colour = palette_colour(pixel, reduce);
+            return *palette_indexes.entry(colour).or_insert_with(|| {
+                palette.push(colour);
+                return palette.len() - 1;
+            });
+        })
+        .collect();
+
+    // every pixel gets drawn so P2=1 in the header stops the terminal filling the image with its background first, the raster attributes give the size up front
+    let mut sixels = format!("\x1bP0;1;0q\"1;1;{};{}", scaled_width, scaled_height);
+    for (index, colour) in palette.iter().enumerate() {
+        let percent = |value: u8| value as u32 * 100 / 255; // sixel colours go from 0 to 100
+        sixels.push_str(&format!("#{};2;{};{};{}", index, percent(colour[0]), percent(colour[1]), percent(colour[2])));
+    }
+    for band_top in (0..scaled_height).step_by(BAND_HEIGHT as usize) {
+        // which rows of the band each colour is in for every column (a bit per row)
+        let mut colour_rows: Vec<Vec<u8>> = vec![vec![0; scaled_width as usize]; palette.len()];
+        let mut used = vec![false; palette.len()];
+        for row in 0..BAND_HEIGHT.min(scaled_height - band_top) {
+            let y = (band_top + row) / scale;
+            for x in 0..scaled_width {
+                let colour = indexes[(y * width + x / scale) as usize];
+                colour_rows[colour][x as usize] |= 1 << row;
+                used[colour] = true;
+            }
+        }
+        let mut first_colour = true;
+        for (colour, columns) in colour_rows.iter().enumerate().filter(|(colour, _)| used[*colour]) {
+            if !first_colour {
+                sixels.push('$'); // back to the start of the band for the next colour
+            }
+            first_colour = false;
+            sixels.push_str(&format!("#{}", colour));
+            let mut run_start = 0;
+            for x in 1..=columns.len() {
+                if x == columns.len() || columns[x] != columns[run_start] {
+                    push_run(&mut sixels, (0x3f + columns[run_start]) as char, (x - run_start) as u32);
+                    run_start = x;
+                }
+            }
+        }
+        sixels.push('-'); // on to the next band
+    }
+    sixels.push_str("\x1b\\");
+    return sixels;
+}
@@ -0,0 +1,79 @@
+use std::{fs, process};
+use std::collections::HashSet;
+use game_of_life::engine::Engine;
+use game_of_life::{Point, ALIVE};
+use crate::{headless_board, headless_engine, Args};
+
+
+struct GenerationStats {
+    generation: u32,
+    population: usize,
+    births: usize,
+    deaths: usize,
+}
+
+
+fn alive_cells(engine: &dyn Engine) -> HashSet<Point> {
+    return engine.cells().iter()
+        .filter(|(_, &state)| state == ALIVE)
+        .map(|(cell, _)| cell.clone())
+        .collect();
+}
+
+
+fn to_csv(stats: &[GenerationStats]) -> String {
+    let mut csv = "generation,population,births,deaths\n".to_string();
+    for row in stats {
+        csv.push_str(&format!("{},{},{},{}\n", row.generation, row.population, row.births, row.deaths));
+    }
+    return csv;
+}
+
+
+fn to_json(stats: &[GenerationStats]) -> String {
+    let rows: Vec<String> = stats.iter()
+        .map(|row| format!(
+            "  {{\"generation\": {}, \"population\": {}, \"births\": {}, \"deaths\": {}}}",
+            row.generation, row.population, row.births, row.deaths
+        ))
+        .collect();
+    return format!("[\n{}\n]\n", rows.join(",\n"));
+}
+
+
+// the "stats" subcommand: runs a pattern without the UI and writes out the population, births and deaths for every generation
+// printing to stderr for errors since stdout might be where the stats are going
+pub fn run_stats(args: Args) {
+    let board = headless_board(&args, "usage: game_of_life stats --pattern <file> [--generations <n>] [--out <file.csv|file.json>] [--engine <engine>]");
+    let mut engine = headless_engine(&args, board);
+
+    let mut stats = vec![GenerationStats {
+        generation: 0,
+        population: engine.population(),
+        births: 0,
+        deaths: 0
+    }];
+    let mut alive = alive_cells(engine.as_ref());
+    for generation in 1..=args.generations {
+        engine.step();
+        let new_alive = alive_cells(engine.as_ref());
+        stats.push(GenerationStats {
+            generation,
+            population: new_alive.len(),
+            births: new_alive.difference(&alive).count(),
+            deaths: alive.difference(&new_alive).count()
+        });
+        alive = new_alive;
+    }
+
+    match args.out_path {
+        Some(out_path) => {
+            let output = if out_path.ends_with(".json") { to_json(&stats) } else { to_csv(&stats) };
+            if let Err(error) = fs::write(&out_path, output) {
+                eprintln!("couldn't write {}: {}", out_path, error);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", to_csv(&stats))
+    }
+}
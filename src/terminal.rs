@@ -0,0 +1,151 @@
+use std::{env, io, panic, thread};
+use std::io::Write;
+use std::sync::{mpsc, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use termion::event::Key;
+use termion::input::TermRead; // for Stdin::keys method
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::AlternateScreen;
+
+
+
+// the terminal settings from before we entered raw mode, so that the terminal can be put back to normal from places that can't get at the RawTerminal (panic hooks)
+static ORIGINAL_TERMIOS: OnceLock<libc::termios> = OnceLock::new();
+
+// set by the signal handler, the game loop checks this every frame and quits normally when it's set
+static QUIT_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// set by the key reader if reading a key fails, the game quits with it instead of carrying on without a keyboard
+static KEY_READ_ERROR: OnceLock<String> = OnceLock::new();
+
+
+// the size of the terminal in characters, with an error that says what to do about it if there isn't one
+// without a terminal COLUMNS and LINES are used if they're set (watch sets them for the commands it runs)
+pub fn terminal_size() -> Result<(u16, u16), String> {
+    return termion::terminal_size().or_else(|error| {
+        let from_env = |name| env::var(name).ok().and_then(|value| value.parse::<u16>().ok());
+        return match (from_env("COLUMNS"), from_env("LINES")) {
+            (Some(columns), Some(lines)) => Ok((columns, lines)),
+            _ => Err(format!("couldn't get the size of the terminal ({}), the game has to be run in one or have COLUMNS and LINES set (--emit can write it to a pipe instead)", error))
+        };
+    });
+}
+
+
+// switches to the alternate screen and raw mode (keys don't get echoed or wait for enter), both get undone when the result is dropped
+pub fn enter_raw_mode() -> Result<AlternateScreen<RawTerminal<io::Stdout>>, String> {
+    if !termion::is_tty(&io::stdout()) {
+        return Err("the output isn't a terminal, the game has to be drawn in one (--emit can write it to a pipe instead)".to_string());
+    }
+    let raw_terminal = io::stdout().into_raw_mode().map_err(|error| format!("couldn't put the terminal into raw mode: {}", error))?;
+    return Ok(AlternateScreen::from(raw_terminal));
+}
+
+
+// needs to be called before entering raw mode
+pub fn save_terminal_state() {
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut termios) } == 0 {
+        ORIGINAL_TERMIOS.set(termios).ok();
+    }
+}
+
+
+// shows the cursor, leaves the alternate screen and leaves raw mode
+// safe to call more than once (it gets called by the guard and again when the RawTerminal gets dropped)
+pub fn restore_terminal() {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}{}", termion::cursor::Show, termion::screen::ToMainScreen).ok();
+    stdout.flush().ok();
+    if let Some(termios) = ORIGINAL_TERMIOS.get() {
+        unsafe { libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, termios) };
+    }
+}
+
+
+// restores the terminal before the panic message gets printed, otherwise the message gets lost in the alternate screen and the shell is left in raw mode
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        restore_terminal();
+        default_hook(panic_info);
+    }));
+}
+
+
+// all a signal handler can safely do is set a flag, unless the flag's already set
+// then the game's stuck on something and didn't notice the first one, so the terminal gets put back here (with only calls that are safe in a signal handler) and it exits right away
+extern "C" fn handle_quit_signal(signal: libc::c_int) {
+    if !QUIT_REQUESTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    const RESTORE: &[u8] = b"\x1b[?25h\x1b[?1049l"; // show the cursor and leave the alternate screen, the same as restore_terminal
+    unsafe {
+        libc::write(libc::STDOUT_FILENO, RESTORE.as_ptr() as *const libc::c_void, RESTORE.len());
+        if let Some(termios) = ORIGINAL_TERMIOS.get() {
+            libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, termios);
+        }
+        libc::_exit(128 + signal); // the usual exit code for being killed by a signal
+    }
+}
+
+
+// makes SIGTERM, SIGINT and SIGHUP quit the game normally instead of killing it with the terminal still in raw mode
+// sending one twice quits straight away, in case the game's too busy to check for the first
+pub fn install_signal_handlers() {
+    let handler = handle_quit_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    for signal in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+        unsafe { libc::signal(signal, handler) };
+    }
+}
+
+
+pub fn quit_requested() -> bool {
+    return QUIT_REQUESTED.load(Ordering::SeqCst);
+}
+
+
+// what went wrong if the key reader stopped because it couldn't read from the terminal (None if it's still going, or stdin just ended)
+pub fn key_read_error() -> Option<&'static String> {
+    return KEY_READ_ERROR.get();
+}
+
+
+// restores the terminal when it goes out of scope, even if that's because of a panic
+pub struct TerminalGuard;
+
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+
+// where keys get read from: stdin, or the terminal itself if a pattern was piped into stdin
+// opened before entering raw mode so that not having a terminal to read from can be reported normally
+pub fn key_source() -> Result<Box<dyn io::Read + Send>, String> {
+    if termion::is_tty(&io::stdin()) {
+        return Ok(Box::new(io::stdin()));
+    }
+    let tty = termion::get_tty().map_err(|error| format!("couldn't open the terminal to read keys from: {}", error))?;
+    return Ok(Box::new(tty));
+}
+
+
+// reads keys from the key source on a new thread and sends them down the returned channel
+pub fn spawn_key_reader(input: Box<dyn io::Read + Send>) -> mpsc::Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for key in input.keys() {
+            match key {
+                Ok(key) => if sender.send(key).is_err() { break; }, // the game's over
+                Err(error) => {
+                    KEY_READ_ERROR.set(format!("couldn't read keys from the terminal: {}", error)).ok(); // only the first error matters
+                    break;
+                }
+            }
+        }
+    });
+    return receiver;
+}
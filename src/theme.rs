@@ -0,0 +1,194 @@
+use std::env;
+use termion::color;
+
+
+
+// the colours every theme is defined with get turned into the closest thing the terminal can show
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColourDepth {
+    Ansi16,
+    Ansi256,
+    TrueColour,
+}
+
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+
+// None for any of these means the terminal's normal text colour
+pub struct Theme {
+    pub name: &'static str,
+    pub cell: Option<Rgb>, // alive cells and electron heads
+    pub dying: Option<Rgb>, // dying cells and electron tails
+    pub conductor: Option<Rgb>,
+    pub rival: Option<Rgb>, // player two's cells in immigration
+    pub wall: Option<Rgb>,
+    pub source: Option<Rgb>, // cells that are always alive
+    pub ant: Option<Rgb>,
+    pub border: Option<Rgb>, // the box around the board and the help
+    pub grid: Option<Rgb>, // the grid over the board (:grid), faint so that it stays out of the way
+    pub text: Option<Rgb>, // the help and everything under the board
+    pub status: Option<Rgb>, // the line at the bottom with the frame time and messages
+}
+
+
+pub const THEMES: [Theme; 5] = [
+    Theme {
+        name: "mono",
+        cell: None,
+        dying: None,
+        conductor: None,
+        rival: None,
+        wall: None,
+        source: None,
+        ant: None,
+        border: None,
+        grid: None,
+        text: None,
+        status: None,
+    },
+    Theme {
+        name: "green",
+        cell: Some(Rgb(51, 255, 51)),
+        dying: Some(Rgb(0, 140, 0)),
+        conductor: Some(Rgb(0, 100, 0)),
+        rival: Some(Rgb(255, 60, 200)),
+        wall: Some(Rgb(0, 70, 0)),
+        source: Some(Rgb(220, 255, 0)),
+        ant: Some(Rgb(170, 255, 170)),
+        border: Some(Rgb(0, 160, 0)),
+        grid: Some(Rgb(0, 80, 0)),
+        text: Some(Rgb(0, 200, 0)),
+        status: Some(Rgb(51, 255, 51)),
+    },
+    Theme {
+        name: "amber",
+        cell: Some(Rgb(255, 176, 0)),
+        dying: Some(Rgb(170, 100, 0)),
+        conductor: Some(Rgb(120, 70, 0)),
+        rival: Some(Rgb(80, 160, 255)),
+        wall: Some(Rgb(90, 55, 0)),
+        source: Some(Rgb(255, 120, 0)),
+        ant: Some(Rgb(255, 220, 140)),
+        border: Some(Rgb(200, 130, 0)),
+        grid: Some(Rgb(100, 65, 0)),
+        text: Some(Rgb(255, 176, 0)),
+        status: Some(Rgb(255, 200, 60)),
+    },
+    Theme {
+        name: "solarized",
+        cell: Some(Rgb(38, 139, 210)),
+        dying: Some(Rgb(108, 113, 196)),
+        conductor: Some(Rgb(88, 110, 117)),
+        rival: Some(Rgb(220, 50, 47)),
+        wall: Some(Rgb(7, 54, 66)),
+        source: Some(Rgb(133, 153, 0)),
+        ant: Some(Rgb(203, 75, 22)),
+        border: Some(Rgb(42, 161, 152)),
+        grid: Some(Rgb(20, 70, 80)),
+        text: Some(Rgb(131, 148, 150)),
+        status: Some(Rgb(181, 137, 0)),
+    },
+    Theme {
+        name: "high-contrast",
+        cell: Some(Rgb(255, 255, 255)),
+        dying: Some(Rgb(255, 255, 0)),
+        conductor: Some(Rgb(0, 255, 255)),
+        rival: Some(Rgb(255, 60, 60)),
+        wall: Some(Rgb(128, 128, 128)),
+        source: Some(Rgb(0, 255, 0)),
+        ant: Some(Rgb(255, 0, 255)),
+        border: Some(Rgb(255, 255, 255)),
+        grid: Some(Rgb(90, 90, 90)),
+        text: Some(Rgb(255, 255, 255)),
+        status: Some(Rgb(255, 255, 0)),
+    },
+];
+
+
+// what the 16 basic colours usually look like (xterm's defaults), for finding the closest one
+const ANSI_16_COLOURS: [Rgb; 16] = [
+    Rgb(0, 0, 0), Rgb(205, 0, 0), Rgb(0, 205, 0), Rgb(205, 205, 0),
+    Rgb(0, 0, 238), Rgb(205, 0, 205), Rgb(0, 205, 205), Rgb(229, 229, 229),
+    Rgb(127, 127, 127), Rgb(255, 0, 0), Rgb(0, 255, 0), Rgb(255, 255, 0),
+    Rgb(92, 92, 255), Rgb(255, 0, 255), Rgb(0, 255, 255), Rgb(255, 255, 255),
+];
+
+
+pub fn theme_index(name: &str) -> Option<usize> {
+    return THEMES.iter().position(|theme| theme.name == name);
+}
+
+
+impl ColourDepth {
+    // there's no reliable way to ask the terminal so this goes by the environment variables that most terminals set
+    pub fn detect() -> ColourDepth {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColourDepth::TrueColour;
+        }
+        if env::var("TERM").unwrap_or_default().contains("256color") {
+            return ColourDepth::Ansi256;
+        }
+        return ColourDepth::Ansi16;
+    }
+
+    // the escape code that switches the text to colour (or back to normal for None)
+    pub fn fg(self, colour: Option<Rgb>) -> String {
+        return self.escape(colour, false);
+    }
+
+    // same as fg but for the colour behind the text
+    pub fn bg(self, colour: Option<Rgb>) -> String {
+        return self.escape(colour, true);
+    }
+
+    fn escape(self, colour: Option<Rgb>, background: bool) -> String {
+        let Rgb(r, g, b) = match (colour, background) {
+            (Some(colour), _) => colour,
+            (None, false) => return color::Fg(color::Reset).to_string(),
+            (None, true) => return color::Bg(color::Reset).to_string()
+        };
+        let ansi_value = |value: u8| match background {
+            false => color::Fg(color::AnsiValue(value)).to_string(),
+            true => color::Bg(color::AnsiValue(value)).to_string()
+        };
+        return match self {
+            ColourDepth::TrueColour if background => color::Bg(color::Rgb(r, g, b)).to_string(),
+            ColourDepth::TrueColour => color::Fg(color::Rgb(r, g, b)).to_string(),
+            ColourDepth::Ansi256 => { // the 6x6x6 colour cube
+                let level = |value: u8| (value as u16 * 5 + 127) / 255;
+                ansi_value((16 + 36 * level(r) + 6 * level(g) + level(b)) as u8)
+            }
+            ColourDepth::Ansi16 => {
+                let distance = |other: &Rgb| {
+                    let (dr, dg, db) = (r as i32 - other.0 as i32, g as i32 - other.1 as i32, b as i32 - other.2 as i32);
+                    return dr * dr + dg * dg + db * db;
+                };
+                let closest = (0..ANSI_16_COLOURS.len()).min_by_key(|&index| distance(&ANSI_16_COLOURS[index])).unwrap();
+                let code = if closest < 8 { 30 + closest } else { 90 + closest - 8 };
+                format!("\x1b[{}m", if background { code + 10 } else { code }) // background codes are all 10 more than the foreground ones
+            }
+        };
+    }
+}
+
+
+// the neighbour count overlay's colours for cells that are about to be born, survive, die, or stay empty
+pub const BIRTH_COLOUR: Rgb = Rgb(80, 230, 80);
+pub const SURVIVAL_COLOUR: Rgb = Rgb(235, 235, 235);
+pub const DEATH_COLOUR: Rgb = Rgb(235, 60, 60);
+pub const STILL_EMPTY_COLOUR: Rgb = Rgb(100, 100, 100);
+
+
+// cold to hot (dark blue, cyan, yellow, red) for heat between 0 and 1
+pub fn heat_colour(heat: f64) -> Rgb {
+    const STOPS: [(f64, f64, f64); 4] = [(0.0, 0.0, 128.0), (0.0, 200.0, 255.0), (255.0, 230.0, 0.0), (255.0, 0.0, 0.0)];
+    let position = heat.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let index = (position as usize).min(STOPS.len() - 2);
+    let fraction = position - index as f64;
+    let (from, to) = (STOPS[index], STOPS[index + 1]);
+    let blend = |from: f64, to: f64| (from + (to - from) * fraction).round() as u8;
+    return Rgb(blend(from.0, to.0), blend(from.1, to.1), blend(from.2, to.2));
+}
@@ -0,0 +1,160 @@
+use std::process;
+use game_of_life::board::CellMap;
+use game_of_life::engine::{EngineKind, ENGINE_KINDS};
+use game_of_life::{pattern, Automaton, Board, Point, Rule};
+use crate::{check_board_size, Args, SIMULATION_BYTES_PER_CELL};
+
+
+
+const USAGE: &str = "usage: game_of_life verify [--engine <engine>] [--seed <n> [--generations <n>] [--width <n>] [--height <n>] [--expect-hash <hash>]]";
+const DEFAULT_BOARD_SIZE: u16 = 100;
+const GOLDEN_PADDING: u32 = 8; // empty cells around a golden pattern, so the edges don't get involved
+const SETTLED_BOARD_SIZE: u32 = 700; // big enough that the gliders a pattern throws off don't reach the edges while it's being checked
+const SETTLED_CHECK_GENERATIONS: u32 = 100; // how long the population has to stay the same for a pattern to have settled
+
+
+// what a golden pattern is known to do in B3/S23
+enum Expected {
+    Period(u32), // comes back to how it started after this many generations and not before
+    Moves { generations: u32, x: i16, y: i16 }, // after this many generations it's where it started moved by x, y
+    Settles(u32), // its population stops changing at this generation (gliders it throws off keep their cells)
+}
+
+
+struct GoldenPattern {
+    name: &'static str,
+    rle: &'static str,
+    expected: Expected,
+}
+
+
+const GOLDEN_PATTERNS: [GoldenPattern; 4] = [
+    GoldenPattern { name: "blinker", rle: "3o!", expected: Expected::Period(2) },
+    GoldenPattern { name: "glider", rle: "bo$2bo$3o!", expected: Expected::Moves { generations: 4, x: 1, y: 1 } },
+    GoldenPattern {
+        name: "pulsar",
+        rle: "2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        expected: Expected::Period(3)
+    },
+    GoldenPattern { name: "R-pentomino", rle: "b2o$2o$bo!", expected: Expected::Settles(1103) },
+];
+
+
+// the cells moved by x, y
+fn moved(cells: &CellMap<u8>, x: i16, y: i16) -> CellMap<u8> {
+    return cells.iter().map(|(cell, &state)| (Point{x: cell.x + x, y: cell.y + y}, state)).collect();
+}
+
+
+// runs a golden pattern and says what it did if that isn't what it's known to do
+fn check_golden_pattern(golden: &GoldenPattern, engine_kind: EngineKind) -> Result<(), String> {
+    let pattern = pattern::parse_rle(golden.rle)?;
+    let (width, height) = match golden.expected {
+        Expected::Settles(_) => (SETTLED_BOARD_SIZE, SETTLED_BOARD_SIZE),
+        _ => (pattern.width + 2 * GOLDEN_PADDING, pattern.height + 2 * GOLDEN_PADDING)
+    };
+    let mut board = Board::new(width, height, Automaton::Life, Rule::conways_life());
+    board.place_pattern(&pattern, &Point{
+        x: (width as i16 - pattern.width as i16) / 2,
+        y: (height as i16 - pattern.height as i16) / 2
+    });
+    let start = board.cells.clone();
+    let mut engine = engine_kind.engine_for(board)?;
+    match golden.expected {
+        Expected::Period(period) => {
+            for generation in 1..=period {
+                engine.step();
+                let back = engine.cells() == start;
+                if back && generation < period {
+                    return Err(format!("came back after {} generations instead of {}", generation, period));
+                }
+                if !back && generation == period {
+                    return Err(format!("didn't come back after {} generations", period));
+                }
+            }
+        }
+        Expected::Moves { generations, x, y } => {
+            for _ in 0..generations {
+                engine.step();
+            }
+            if engine.cells() != moved(&start, x, y) {
+                return Err(format!("wasn't moved by {}, {} after {} generations", x, y, generations));
+            }
+        }
+        Expected::Settles(settled) => {
+            let mut populations = vec![engine.population()];
+            for _ in 0..settled + SETTLED_CHECK_GENERATIONS {
+                engine.step();
+                populations.push(engine.population());
+            }
+            let last = *populations.last().unwrap();
+            let settled_at = populations.iter().rposition(|&population| population != last).map_or(0, |generation| generation + 1);
+            if settled_at != settled as usize {
+                return Err(format!("settled at generation {} instead of {}", settled_at, settled));
+            }
+        }
+    }
+    return Ok(());
+}
+
+
+// verify without a seed: runs each golden pattern on every engine (or just --engine) and says whether it did what it's known to
+// exits with code 1 if any of them didn't
+fn run_golden_patterns(only_engine: Option<EngineKind>) {
+    let mut failures = 0;
+    for golden in &GOLDEN_PATTERNS {
+        for &(engine_kind, engine) in ENGINE_KINDS.iter().filter(|(kind, _)| only_engine.is_none_or(|only_engine| *kind == only_engine)) {
+            match check_golden_pattern(golden, engine_kind) {
+                Ok(()) => println!("pass: {} ({} engine)", golden.name, engine),
+                Err(message) => {
+                    println!("FAIL: {} ({} engine) {}", golden.name, engine, message);
+                    failures += 1;
+                }
+            }
+        }
+    }
+    if failures > 0 {
+        eprintln!("{} of the golden pattern checks failed", failures);
+        process::exit(1);
+    }
+}
+
+
+// the "verify" subcommand: fills a board from a seed, runs it and prints a hash of where it ends up
+// with --expect-hash it fails (with exit code 1) if the hash is different, so a build or platform can be checked for giving bit-identical results
+// without a seed it checks the engine against some patterns whose behaviour is well known instead
+pub fn run_verify(args: Args) {
+    let seed = match args.seed {
+        Some(seed) => seed,
+        None if args.expect_hash.is_none() => return run_golden_patterns(args.engine),
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    };
+    let width = args.board_width.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    let height = args.board_height.unwrap_or(DEFAULT_BOARD_SIZE) as u32;
+    check_board_size(&args, width, height, SIMULATION_BYTES_PER_CELL);
+    let mut board = Board::new(width, height, args.automaton, args.rule.clone().unwrap_or_else(Rule::conways_life));
+    board.table = args.rule_table.clone();
+    board.init_from_seed(seed);
+    let mut engine = args.engine.unwrap_or(EngineKind::Incremental).engine_for(board).unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        process::exit(1);
+    });
+    for _ in 0..args.generations {
+        engine.step();
+    }
+
+    let hash = format!("{:016x}", engine.state_hash());
+    match &args.expect_hash {
+        Some(expected) if expected.trim_start_matches("0x").eq_ignore_ascii_case(&hash) => {
+            println!("ok: seed {} after {} generations hashes to {}", seed, args.generations, hash);
+        }
+        Some(expected) => {
+            eprintln!("mismatch: seed {} after {} generations hashes to {} but {} was expected", seed, args.generations, hash, expected);
+            process::exit(1);
+        }
+        None => println!("{}", hash)
+    }
+}
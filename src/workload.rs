@@ -0,0 +1,101 @@
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::time::Instant;
+use crate::board::{Automaton, Board, Point};
+#[cfg(feature = "std")]
+use crate::engine::{Engine, EngineKind, ENGINE_KINDS};
+use crate::pattern;
+use crate::rule::Rule;
+
+
+
+// range 2 rules for the dense engine, which only gets used once the cells' neighbourhoods add up to more than the whole board
+const DENSE_SOUP_RULE: &str = "R2,C0,M0,S5..9,B6..8,NM"; // one that keeps going instead of dying out
+const DENSE_MAX_DENSITY_RULE: &str = "R2,C0,M1,S0..24,B7..24,NM";
+const MAX_DENSITY_RULE: &str = "B3/S012345678"; // nothing ever dies so a soup fills in until nearly every cell is alive
+#[cfg(feature = "std")]
+const DENSE_ENGINE_NAME: &str = "dense (range 2)"; // the range 2 version of a workload, which the sparse engine counts by going along every row of the board
+pub const GOSPER_GLIDER_GUN: &str = "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!";
+
+
+// the standard boards that engines get timed on, by the bench subcommand and by `cargo bench`
+#[derive(Clone, Copy, PartialEq)]
+pub enum Workload {
+    RandomSoup,
+    GliderGun,
+    MaxDensity,
+}
+
+
+pub const WORKLOADS: [(Workload, &str); 3] = [(Workload::RandomSoup, "random soup"), (Workload::GliderGun, "glider gun"), (Workload::MaxDensity, "max density")];
+
+
+impl Workload {
+    // the board it starts from, dense is for the range 2 version of it (None if there isn't one)
+    pub fn set_up(self, dense: bool, width: u32, height: u32, seed: u64) -> Option<Board> {
+        let rule_string = match (self, dense) {
+            (Workload::GliderGun, true) => return None, // guns are made for range 1
+            (Workload::RandomSoup, true) => DENSE_SOUP_RULE,
+            (Workload::MaxDensity, true) => DENSE_MAX_DENSITY_RULE,
+            (Workload::MaxDensity, false) => MAX_DENSITY_RULE,
+            _ => "B3/S23"
+        };
+        let mut board = Board::new(width, height, Automaton::Life, Rule::parse(rule_string).unwrap());
+        match self {
+            Workload::GliderGun => {
+                let gun = pattern::parse_rle(GOSPER_GLIDER_GUN).unwrap();
+                board.place_pattern(&gun, &Point{x: 1, y: 1}); // the gliders head down and right, across the whole board
+            }
+            _ => board.init_from_seed(seed)
+        }
+        return Some(board);
+    }
+}
+
+
+// how long it takes to run the engine for that many generations, in seconds
+#[cfg(feature = "std")]
+fn time_generations(engine: &mut dyn Engine, generations: u32) -> f64 {
+    let start = Instant::now();
+    for _ in 0..generations {
+        engine.step();
+    }
+    return start.elapsed().as_secs_f64();
+}
+
+
+// times each engine (or just only_engine) on every workload and writes a table of how many generations per second they manage, for the bench subcommand and `cargo bench`
+// every engine is compared against the sparse one on the same workload, apart from the dense rows which run a different rule (they're left out when there's an only_engine)
+#[cfg(feature = "std")]
+pub fn time_workloads(output: &mut dyn Write, width: u32, height: u32, generations: u32, seed: u64, only_engine: Option<EngineKind>) -> io::Result<()> {
+    writeln!(output, "{}x{} board, {} generations, seed {}", width, height, generations, seed)?;
+    writeln!(output, "{:<12} {:<16} {:>9} {:>16} {:>10}", "workload", "engine", "seconds", "generations/sec", "vs sparse")?;
+    let engines: Vec<(Option<EngineKind>, &str)> = ENGINE_KINDS.iter()
+        .filter(|(kind, _)| only_engine.is_none_or(|only_engine| *kind == only_engine))
+        .map(|&(kind, name)| (Some(kind), name))
+        .chain(Some((None, DENSE_ENGINE_NAME)).filter(|_| only_engine.is_none()))
+        .collect();
+    for (workload, workload_name) in WORKLOADS {
+        let mut sparse_seconds = None;
+        for &(engine_kind, engine_name) in &engines {
+            let board = match workload.set_up(engine_kind.is_none(), width, height, seed) {
+                Some(board) => board,
+                None => continue
+            };
+            let mut engine = engine_kind.unwrap_or(EngineKind::Sparse).engine_for(board).unwrap(); // the workloads are all plain life
+            let seconds = time_generations(engine.as_mut(), generations);
+            let compared = match (engine_kind, sparse_seconds) {
+                (Some(EngineKind::Sparse), _) => {
+                    sparse_seconds = Some(seconds);
+                    "1.00x".to_string()
+                }
+                (Some(_), Some(sparse_seconds)) => format!("{:.2}x", sparse_seconds / seconds),
+                _ => "-".to_string()
+            };
+            writeln!(output, "{:<12} {:<16} {:>9.3} {:>16.1} {:>10}", workload_name, engine_name, seconds, generations as f64 / seconds, compared)?;
+            output.flush()?; // so each line shows up as soon as it's done
+        }
+    }
+    return Ok(());
+}